@@ -0,0 +1,209 @@
+use super::memory_map::{MemoryMap, RegionKind};
+use super::symbols::SymbolTable;
+use super::variables::Memory;
+
+//shortest run of printable characters taken for a string
+const MIN_STRING: usize = 4;
+//longest string shown, longer ones get cut off
+const MAX_STRING: usize = 48;
+//pointers a telescope follows before giving up
+const MAX_HOPS: usize = 6;
+/// Lengths of `call rel32`, `call reg`, `call [reg+disp8]`... checked backwards from a return address
+const CALL_LENGTHS: [u64; 6] = [5, 2, 3, 6, 7, 4];
+
+/// What a value that looks like an address points to
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Annotation {
+    /// Right past a call, most likely pushed by it
+    ReturnAddress(String),
+    /// NUL terminated, printable text
+    String { kind: RegionKind, text: String },
+    Pointer {
+        kind: RegionKind,
+        /// Symbol or module the address belongs to
+        location: Option<String>,
+    },
+}
+
+impl Annotation {
+    pub fn describe(&self) -> String {
+        match self {
+            Self::ReturnAddress(location) => format!("return to {location}"),
+            Self::String { text, .. } => format!("{text:?}"),
+            Self::Pointer {
+                kind,
+                location: Some(location),
+            } => format!("{location} ({})", kind.name()),
+            Self::Pointer {
+                kind,
+                location: None,
+            } => format!("({})", kind.name()),
+        }
+    }
+}
+
+/// A value along with where it leads when followed as a pointer, and then as a pointer again
+#[derive(Clone, Debug)]
+pub struct Telescope {
+    /// The value itself comes first
+    pub hops: Vec<(u64, Option<Annotation>)>,
+    /// The chain points back into itself
+    pub looped: bool,
+}
+
+impl Telescope {
+    /// `(stack) → 0x5555555551a9 main+0x24 (code)`, leaving out the value the chain starts with
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+
+        for (index, (value, annotation)) in self.hops.iter().enumerate() {
+            if index > 0 {
+                parts.push(format!("{value:#x}"));
+            }
+
+            match annotation {
+                Some(Annotation::String { kind, text }) => {
+                    parts.push(format!("({})", kind.name()));
+                    parts.push(format!("→ {text:?}"));
+                }
+                Some(annotation) => parts.push(annotation.describe()),
+                None => (),
+            }
+
+            if index + 1 < self.hops.len() {
+                parts.push(String::from("→"));
+            }
+        }
+
+        if self.looped {
+            parts.push(String::from("→ ..."));
+        }
+
+        parts.join(" ")
+    }
+}
+
+/// `None` for values that don't point into any mapping
+pub fn annotate(
+    memory_map: &MemoryMap,
+    symbols: &SymbolTable,
+    memory: &dyn Memory,
+    value: u64,
+) -> Option<Annotation> {
+    let region = memory_map.region_at(value)?;
+    let location = || {
+        symbols
+            .symbolize(value)
+            .or_else(|| memory_map.describe(value))
+    };
+
+    if region.is_executable() && follows_call(memory, value) {
+        return Some(Annotation::ReturnAddress(
+            location().unwrap_or_else(|| format!("{value:#x}")),
+        ));
+    }
+
+    if region.is_readable()
+        && !region.is_executable()
+        && let Some(text) = read_string(memory, value)
+    {
+        return Some(Annotation::String {
+            kind: region.kind(),
+            text,
+        });
+    }
+
+    Some(Annotation::Pointer {
+        kind: region.kind(),
+        location: location(),
+    })
+}
+
+/// Follows `value` through memory for as long as it keeps pointing at data,
+/// code, strings and anything that isn't an address end the chain
+pub fn telescope(
+    memory_map: &MemoryMap,
+    symbols: &SymbolTable,
+    memory: &dyn Memory,
+    value: u64,
+) -> Telescope {
+    let mut telescope = Telescope {
+        hops: Vec::new(),
+        looped: false,
+    };
+    let mut value = value;
+
+    loop {
+        let annotation = annotate(memory_map, symbols, memory, value);
+        let next = match &annotation {
+            Some(Annotation::Pointer { kind, .. }) if *kind != RegionKind::Code => memory
+                .read_bytes(value, 8)
+                .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap())),
+            _ => None,
+        };
+        telescope.hops.push((value, annotation));
+
+        let Some(next) = next else {
+            break;
+        };
+
+        if telescope.hops.iter().any(|(hop, _)| *hop == next) {
+            telescope.looped = true;
+            break;
+        }
+
+        if telescope.hops.len() == MAX_HOPS {
+            break;
+        }
+
+        value = next;
+    }
+
+    telescope
+}
+
+/// Whether the instruction right before `address` is a call, which would make it a return address
+fn follows_call(memory: &dyn Memory, address: u64) -> bool {
+    CALL_LENGTHS.iter().any(|&length| {
+        let start = address.wrapping_sub(length);
+        let Some(code) = memory.read_bytes(start, length as usize) else {
+            return false;
+        };
+
+        let mut decoder =
+            iced_x86::Decoder::with_ip(64, &code, start, iced_x86::DecoderOptions::NONE);
+        let instruction = decoder.decode();
+
+        !instruction.is_invalid()
+            && instruction.len() as u64 == length
+            && matches!(
+                instruction.flow_control(),
+                iced_x86::FlowControl::Call | iced_x86::FlowControl::IndirectCall
+            )
+    })
+}
+
+fn read_string(memory: &dyn Memory, address: u64) -> Option<String> {
+    //strings close to the end of a mapping can't be read in one go
+    let bytes = memory
+        .read_bytes(address, MAX_STRING + 1)
+        .or_else(|| memory.read_bytes(address, MIN_STRING + 1))?;
+
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    let text = &bytes[..end];
+
+    let printable = text
+        .iter()
+        .all(|&b| b.is_ascii_graphic() || b == b' ' || b == b'\t' || b == b'\n');
+
+    if text.len() < MIN_STRING || !printable {
+        return None;
+    }
+
+    let mut text = String::from_utf8_lossy(&text[..text.len().min(MAX_STRING)]).into_owned();
+    if end > MAX_STRING {
+        text.push_str("...");
+    }
+
+    Some(text)
+}
@@ -0,0 +1,687 @@
+use iced_x86::{
+    BlockEncoder, BlockEncoderOptions, Code, Encoder, Instruction, InstructionBlock, OpCodeInfo,
+    OpCodeOperandKind, OpKind, Register,
+};
+
+use super::{expression, Debugee};
+
+/// Assembles Intel syntax (`mov eax, [rbp-0x14]; jmp main+0x20`) as if it was placed at `address`.
+/// Instructions are separated by newlines or `;`, numbers and addresses are `expression`s
+pub fn assemble(source: &str, address: u64, debugee: &Debugee) -> Result<Vec<u8>, String> {
+    let mut instructions = Vec::new();
+    let mut ip = address;
+
+    for line in source.split(['\n', ';']) {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let instruction = parse_line(line, ip, &|text| expression::evaluate(text, debugee))
+            .map_err(|e| format!("{line}: {e}"))?;
+        ip += instruction.len() as u64;
+        instructions.push(instruction);
+    }
+
+    if instructions.is_empty() {
+        return Err(String::from("Nothing to assemble"));
+    }
+
+    //takes care of branches between the instructions getting longer or shorter
+    let block = InstructionBlock::new(&instructions, address);
+    BlockEncoder::encode(64, block, BlockEncoderOptions::NONE)
+        .map(|result| result.code_buffer)
+        .map_err(|e| e.to_string())
+}
+
+#[derive(Clone, Copy)]
+enum Operand {
+    Register(Register),
+    Memory(Memory),
+    /// Also branch targets
+    Immediate(u64),
+}
+
+#[derive(Clone, Copy)]
+struct Memory {
+    /// From `byte`, `qword ptr`...
+    size: Option<usize>,
+    segment: Register,
+    base: Register,
+    index: Register,
+    scale: u32,
+    displacement: u64,
+}
+
+/// Numbers and addresses in operands, `expression::evaluate` against the debugee
+type Evaluate<'a> = dyn Fn(&str) -> Result<u64, String> + 'a;
+
+const PREFIXES: [&str; 6] = ["lock", "rep", "repe", "repz", "repne", "repnz"];
+
+/// Other names of condition codes, iced only knows one of each
+const CONDITION_ALIASES: [(&str, &str); 12] = [
+    ("z", "e"),
+    ("nz", "ne"),
+    ("c", "b"),
+    ("nae", "b"),
+    ("nc", "ae"),
+    ("nb", "ae"),
+    ("na", "be"),
+    ("nbe", "a"),
+    ("nge", "l"),
+    ("nl", "ge"),
+    ("ng", "le"),
+    ("nle", "g"),
+];
+
+fn parse_line(line: &str, ip: u64, evaluate: &Evaluate) -> Result<Instruction, String> {
+    let mut words = line.splitn(2, char::is_whitespace);
+    let mut mnemonic = words.next().unwrap_or_default().to_ascii_lowercase();
+    let mut rest = words.next().unwrap_or_default().trim();
+    let mut prefixes = Vec::new();
+
+    while PREFIXES.contains(&mnemonic.as_str()) {
+        prefixes.push(mnemonic);
+        let mut words = rest.splitn(2, char::is_whitespace);
+        mnemonic = words.next().unwrap_or_default().to_ascii_lowercase();
+        rest = words.next().unwrap_or_default().trim();
+    }
+
+    if mnemonic.is_empty() {
+        return Err(String::from("Missing mnemonic"));
+    }
+    let mnemonic = canonical_mnemonic(&mnemonic);
+
+    let operands = split_operands(rest)
+        .into_iter()
+        .map(|operand| parse_operand(operand, evaluate))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let codes = Code::values()
+        .filter(|code| {
+            let op_code = code.op_code();
+            op_code.is_instruction()
+                && op_code.mode64()
+                && format!("{:?}", code.mnemonic()).to_ascii_lowercase() == mnemonic
+        })
+        .collect::<Vec<_>>();
+
+    if codes.is_empty() {
+        return Err(format!("Unknown mnemonic '{mnemonic}'"));
+    }
+
+    let mut candidates = Vec::new();
+    let mut errors = Vec::new();
+    for code in codes {
+        let Some(mut instruction) = build(code, &operands) else {
+            continue;
+        };
+
+        for prefix in &prefixes {
+            match prefix.as_str() {
+                "lock" => instruction.set_has_lock_prefix(true),
+                "rep" => instruction.set_has_rep_prefix(true),
+                "repe" | "repz" => instruction.set_has_repe_prefix(true),
+                _ => instruction.set_has_repne_prefix(true),
+            }
+        }
+
+        match encode(&mut instruction, &operands, ip) {
+            Ok(length) => {
+                instruction.set_len(length);
+                candidates.push(instruction);
+            }
+            Err(e) => errors.push(e),
+        }
+    }
+
+    let hint = operands.iter().find_map(|operand| match operand {
+        Operand::Memory(memory) => Some(memory.size),
+        _ => None,
+    });
+
+    if let Some(Some(size)) = hint {
+        candidates.retain(|instruction| instruction.memory_size().size() == size);
+        if candidates.is_empty() {
+            return Err(format!("No form takes a {size} byte memory operand"));
+        }
+    }
+
+    let Some(first) = candidates.first() else {
+        //only worth showing when there was a single form to pick
+        return Err(match errors.as_slice() {
+            [error] => error.clone(),
+            _ => format!("No form of '{mnemonic}' takes these operands"),
+        });
+    };
+
+    //`inc [rax]` could be any of the four sizes
+    if hint == Some(None)
+        && candidates
+            .iter()
+            .any(|instruction| instruction.memory_size() != first.memory_size())
+    {
+        return Err(String::from(
+            "Ambiguous operand size, specify byte, word, dword or qword",
+        ));
+    }
+
+    Ok(candidates.into_iter().min_by_key(Instruction::len).unwrap())
+}
+
+/// `jz` -> `je`, `cmovnae` -> `cmovb`, `setc` -> `setb`...
+fn canonical_mnemonic(mnemonic: &str) -> String {
+    for prefix in ["j", "cmov", "set"] {
+        if let Some(condition) = mnemonic.strip_prefix(prefix)
+            && let Some((_, canonical)) = CONDITION_ALIASES
+                .iter()
+                .find(|(alias, _)| *alias == condition)
+        {
+            return format!("{prefix}{canonical}");
+        }
+    }
+
+    match mnemonic {
+        "jpe" => String::from("jp"),
+        "jpo" => String::from("jnp"),
+        _ => mnemonic.to_owned(),
+    }
+}
+
+/// Commas inside brackets or parentheses don't separate operands
+fn split_operands(text: &str) -> Vec<&str> {
+    if text.is_empty() {
+        return Vec::new();
+    }
+
+    let mut operands = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '[' | '(' => depth += 1,
+            ']' | ')' => depth -= 1,
+            ',' if depth == 0 => {
+                operands.push(text[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    operands.push(text[start..].trim());
+
+    operands
+}
+
+fn parse_operand(text: &str, evaluate: &Evaluate) -> Result<Operand, String> {
+    if let Some(register) = parse_register(text) {
+        return Ok(Operand::Register(register));
+    }
+
+    let mut words = text.split_whitespace().peekable();
+    let size = match words
+        .peek()
+        .map(|word| word.to_ascii_lowercase())
+        .as_deref()
+    {
+        Some("byte") => Some(1),
+        Some("word") => Some(2),
+        Some("dword") => Some(4),
+        Some("qword") => Some(8),
+        Some("tword" | "tbyte") => Some(10),
+        Some("xmmword" | "oword") => Some(16),
+        Some("ymmword") => Some(32),
+        Some("zmmword") => Some(64),
+        _ => None,
+    };
+
+    if size.is_some() {
+        words.next();
+        if words
+            .peek()
+            .is_some_and(|word| word.eq_ignore_ascii_case("ptr"))
+        {
+            words.next();
+        }
+    }
+
+    //`jmp short label`
+    if size.is_none()
+        && words.peek().is_some_and(|word| {
+            word.eq_ignore_ascii_case("short") || word.eq_ignore_ascii_case("near")
+        })
+    {
+        words.next();
+    }
+
+    let rest = words.collect::<Vec<_>>().join(" ");
+
+    //`fs:[rax]`
+    if let Some((segment, address)) = rest.split_once(':')
+        && let Some(address) = address.trim().strip_prefix('[')
+    {
+        let segment = parse_register(segment.trim())
+            .filter(|register| register.is_segment_register())
+            .ok_or_else(|| format!("Invalid segment '{segment}'"))?;
+        return parse_memory(address, size, segment, evaluate);
+    }
+
+    if let Some(address) = rest.strip_prefix('[') {
+        //`[fs:rax]`
+        if let Some((segment, address)) = address.split_once(':')
+            && let Some(segment) =
+                parse_register(segment.trim()).filter(|register| register.is_segment_register())
+        {
+            return parse_memory(address, size, segment, evaluate);
+        }
+
+        return parse_memory(address, size, Register::None, evaluate);
+    }
+
+    if size.is_some() {
+        return Err(format!(
+            "Expected a memory operand after the size in '{text}'"
+        ));
+    }
+
+    evaluate(&rest).map(Operand::Immediate)
+}
+
+/// `rax+rcx*8-0x10]`, anything that isn't a register is evaluated as an expression
+fn parse_memory(
+    text: &str,
+    size: Option<usize>,
+    segment: Register,
+    evaluate: &Evaluate,
+) -> Result<Operand, String> {
+    let text = text
+        .trim()
+        .strip_suffix(']')
+        .ok_or("Missing ']' in memory operand")?;
+
+    let mut memory = Memory {
+        size,
+        segment,
+        base: Register::None,
+        index: Register::None,
+        scale: 1,
+        displacement: 0,
+    };
+
+    let mut expressions = String::new();
+    for (negative, term) in split_terms(text) {
+        let term = term.trim();
+        if term.is_empty() {
+            return Err(format!("Invalid memory operand '[{text}]'"));
+        }
+
+        //`rcx*8`, `8*rcx`
+        let scaled = term.split_once('*').and_then(|(lhs, rhs)| {
+            let (lhs, rhs) = (lhs.trim(), rhs.trim());
+            parse_register(lhs)
+                .map(|register| (register, rhs))
+                .or_else(|| parse_register(rhs).map(|register| (register, lhs)))
+        });
+
+        if let Some((register, scale)) = scaled {
+            if negative || memory.index != Register::None {
+                return Err(format!("Invalid index '{term}'"));
+            }
+            memory.index = register;
+            memory.scale = match expression::parse_number(scale)? {
+                scale @ (1 | 2 | 4 | 8) => scale as u32,
+                _ => return Err(format!("Invalid scale '{scale}'")),
+            };
+        } else if let Some(register) = parse_register(term) {
+            if negative {
+                return Err(format!("Registers can't be subtracted '{term}'"));
+            } else if memory.base == Register::None {
+                memory.base = register;
+            } else if memory.index == Register::None {
+                memory.index = register;
+            } else {
+                return Err(format!("Too many registers in '[{text}]'"));
+            }
+        } else {
+            if negative {
+                expressions.push('-');
+            } else if !expressions.is_empty() {
+                expressions.push('+');
+            }
+            expressions.push_str(term);
+        }
+    }
+
+    if !expressions.is_empty() {
+        memory.displacement = evaluate(&expressions)?;
+    }
+
+    Ok(Operand::Memory(memory))
+}
+
+/// Splits at the top level `+` and `-` along with whether the term got subtracted
+fn split_terms(text: &str) -> Vec<(bool, &str)> {
+    let mut terms = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    let mut negative = false;
+
+    for (i, c) in text.char_indices() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            '+' | '-' if depth == 0 => {
+                //a leading sign doesn't end a term
+                if !text[start..i].trim().is_empty() {
+                    terms.push((negative, &text[start..i]));
+                    negative = false;
+                }
+                negative ^= c == '-';
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    terms.push((negative, &text[start..]));
+
+    terms
+}
+
+fn parse_register(name: &str) -> Option<Register> {
+    Register::values()
+        .filter(|register| *register != Register::None)
+        .find(|register| format!("{register:?}").eq_ignore_ascii_case(name))
+}
+
+/// An instruction with `code` and `operands`, if the operand kinds could fit
+fn build(code: Code, operands: &[Operand]) -> Option<Instruction> {
+    let op_code = code.op_code();
+
+    let mut instruction = Instruction::default();
+    instruction.set_code(code);
+
+    //string instructions written without their implicit operands
+    if operands.is_empty() && op_code.op_count() != 0 {
+        for operand in 0..op_code.op_count() {
+            set_implicit(&mut instruction, op_code, operand)?;
+        }
+        return Some(instruction);
+    }
+
+    if op_code.op_count() as usize != operands.len() {
+        return None;
+    }
+
+    for (i, operand) in operands.iter().enumerate() {
+        let i = i as u32;
+        match *operand {
+            Operand::Register(register) => {
+                instruction.set_op_kind(i, OpKind::Register);
+                instruction.set_op_register(i, register);
+            }
+            Operand::Memory(memory) => {
+                instruction.set_op_kind(i, OpKind::Memory);
+                instruction.set_segment_prefix(memory.segment);
+                instruction.set_memory_base(memory.base);
+                instruction.set_memory_index(memory.index);
+                instruction.set_memory_index_scale(memory.scale);
+                instruction.set_memory_displacement64(memory.displacement);
+            }
+            Operand::Immediate(value) => {
+                let kind = immediate_kind(op_code.op_kind(i), i, &instruction, value)?;
+                instruction.set_op_kind(i, kind);
+                if kind == OpKind::NearBranch64 {
+                    instruction.set_near_branch64(value);
+                } else {
+                    instruction.try_set_immediate_u64(i, value).ok()?;
+                }
+            }
+        }
+    }
+
+    Some(instruction)
+}
+
+fn set_implicit(instruction: &mut Instruction, op_code: &OpCodeInfo, operand: u32) -> Option<()> {
+    let (kind, register) = match op_code.op_kind(operand) {
+        OpCodeOperandKind::seg_rSI => (OpKind::MemorySegRSI, Register::None),
+        OpCodeOperandKind::es_rDI => (OpKind::MemoryESRDI, Register::None),
+        OpCodeOperandKind::seg_rDI => (OpKind::MemorySegRDI, Register::None),
+        OpCodeOperandKind::al => (OpKind::Register, Register::AL),
+        OpCodeOperandKind::ax => (OpKind::Register, Register::AX),
+        OpCodeOperandKind::eax => (OpKind::Register, Register::EAX),
+        OpCodeOperandKind::rax => (OpKind::Register, Register::RAX),
+        OpCodeOperandKind::dx => (OpKind::Register, Register::DX),
+        _ => return None,
+    };
+
+    instruction.set_op_kind(operand, kind);
+    if kind == OpKind::Register {
+        instruction.set_op_register(operand, register);
+    }
+
+    Some(())
+}
+
+/// How the operand stores `value`, None when it doesn't take an immediate or `value` doesn't fit
+fn immediate_kind(
+    kind: OpCodeOperandKind,
+    operand: u32,
+    instruction: &Instruction,
+    value: u64,
+) -> Option<OpKind> {
+    let signed = value as i64;
+    let is_i8 = |value: i64| i8::try_from(value).is_ok();
+    let is_i32 = |value: i64| i32::try_from(value).is_ok();
+
+    let (kind, fits) = match kind {
+        //`enter imm16, imm8`, `extrq xmm, imm8, imm8`
+        OpCodeOperandKind::imm8
+            if operand > 0
+                && matches!(
+                    instruction.op_kind(operand - 1),
+                    OpKind::Immediate8 | OpKind::Immediate16
+                ) =>
+        {
+            (OpKind::Immediate8_2nd, (-0x80..=0xff).contains(&signed))
+        }
+        OpCodeOperandKind::imm8 => (OpKind::Immediate8, (-0x80..=0xff).contains(&signed)),
+        OpCodeOperandKind::imm8_const_1 => (OpKind::Immediate8, value == 1),
+        OpCodeOperandKind::imm16 => (OpKind::Immediate16, (-0x8000..=0xffff).contains(&signed)),
+        OpCodeOperandKind::imm32 => (
+            OpKind::Immediate32,
+            (i64::from(i32::MIN)..=i64::from(u32::MAX)).contains(&signed),
+        ),
+        OpCodeOperandKind::imm64 => (OpKind::Immediate64, true),
+        //sign extended to the operand size, `add ax, 0xffff` is `add ax, -1`
+        OpCodeOperandKind::imm8sex16 => (
+            OpKind::Immediate8to16,
+            is_i8(signed) || (value <= 0xffff && is_i8(i64::from(value as u16 as i16))),
+        ),
+        OpCodeOperandKind::imm8sex32 => (
+            OpKind::Immediate8to32,
+            is_i8(signed) || (value <= 0xffff_ffff && is_i8(i64::from(value as u32 as i32))),
+        ),
+        OpCodeOperandKind::imm8sex64 => (OpKind::Immediate8to64, is_i8(signed)),
+        OpCodeOperandKind::imm32sex64 => (OpKind::Immediate32to64, is_i32(signed)),
+        OpCodeOperandKind::br64_1 | OpCodeOperandKind::br64_4 => (OpKind::NearBranch64, true),
+        _ => return None,
+    };
+
+    fits.then_some(kind)
+}
+
+/// Length of the instruction at `ip`, fixing up memory operands on the way: `[rip+x]` is relative
+/// to the next instruction and absolute addresses near `ip` get encoded rip relative
+fn encode(instruction: &mut Instruction, operands: &[Operand], ip: u64) -> Result<usize, String> {
+    let memory = operands.iter().find_map(|operand| match operand {
+        Operand::Memory(memory) => Some(*memory),
+        _ => None,
+    });
+
+    let encode = |instruction: &Instruction| {
+        Encoder::new(64)
+            .encode(instruction, ip)
+            .map_err(|e| e.to_string())
+    };
+
+    let Some(memory) = memory else {
+        return encode(instruction);
+    };
+
+    if memory.base == Register::RIP {
+        //the displacement is always 4 bytes, the length can't change once it's known
+        instruction.set_memory_displacement64(ip.wrapping_add(memory.displacement));
+        let length = encode(instruction)?;
+        instruction.set_memory_displacement64(ip.wrapping_add(length as u64 + memory.displacement));
+        return encode(instruction);
+    }
+
+    let absolute = memory.base == Register::None && memory.index == Register::None;
+    let near = (memory.displacement.wrapping_sub(ip) as i64).unsigned_abs() < 0x7000_0000;
+
+    if absolute && !is_i32(memory.displacement) && near {
+        instruction.set_memory_base(Register::RIP);
+        return encode(instruction);
+    }
+
+    instruction.set_memory_displ_size(match (absolute, memory.displacement) {
+        (true, _) => 8,
+        (false, 0) => 0,
+        //the encoder picks 1 or 4 bytes
+        (false, _) => 1,
+    });
+
+    encode(instruction)
+}
+
+fn is_i32(value: u64) -> bool {
+    i32::try_from(value as i64).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Plain numbers, optionally negated, in place of the debugee's expressions
+    fn number(text: &str) -> Result<u64, String> {
+        match text.strip_prefix('-') {
+            Some(text) => expression::parse_number(text).map(u64::wrapping_neg),
+            None => expression::parse_number(text),
+        }
+    }
+
+    fn memory(text: &str) -> Memory {
+        match parse_operand(text, &number) {
+            Ok(Operand::Memory(memory)) => memory,
+            Ok(_) => panic!("'{text}' isn't a memory operand"),
+            Err(error) => panic!("'{text}': {error}"),
+        }
+    }
+
+    fn assemble_line(line: &str, ip: u64) -> Result<Vec<u8>, String> {
+        let instruction = parse_line(line, ip, &number)?;
+        let mut encoder = Encoder::new(64);
+        encoder
+            .encode(&instruction, ip)
+            .map_err(|e| e.to_string())?;
+        Ok(encoder.take_buffer())
+    }
+
+    #[test]
+    fn operands_split_outside_of_brackets() {
+        assert_eq!(
+            split_operands("eax, [rax+rcx*8], fs:[0x28]"),
+            ["eax", "[rax+rcx*8]", "fs:[0x28]"]
+        );
+        assert_eq!(split_operands("(1, 2), 3"), ["(1, 2)", "3"]);
+        assert!(split_operands("").is_empty());
+    }
+
+    #[test]
+    fn registers_and_immediates() {
+        assert!(matches!(
+            parse_operand("R8D", &number),
+            Ok(Operand::Register(Register::R8D))
+        ));
+        assert!(matches!(
+            parse_operand("short 0x1000", &number),
+            Ok(Operand::Immediate(0x1000))
+        ));
+        assert!(parse_operand("byte 5", &number).is_err());
+    }
+
+    #[test]
+    fn base_index_scale_displacement() {
+        let operand = memory("[rax+rcx*8-0x10]");
+        assert_eq!(operand.size, None);
+        assert_eq!(operand.base, Register::RAX);
+        assert_eq!(operand.index, Register::RCX);
+        assert_eq!(operand.scale, 8);
+        assert_eq!(operand.displacement, 0x10u64.wrapping_neg());
+
+        let operand = memory("dword ptr [8*rcx+rbx]");
+        assert_eq!(operand.size, Some(4));
+        assert_eq!(operand.base, Register::RBX);
+        assert_eq!(operand.index, Register::RCX);
+        assert_eq!(operand.scale, 8);
+
+        let operand = memory("[rsi+rdi]");
+        assert_eq!(operand.base, Register::RSI);
+        assert_eq!(operand.index, Register::RDI);
+        assert_eq!(operand.scale, 1);
+    }
+
+    #[test]
+    fn segments() {
+        let operand = memory("qword ptr fs:[0x28]");
+        assert_eq!(operand.size, Some(8));
+        assert_eq!(operand.segment, Register::FS);
+        assert_eq!(operand.base, Register::None);
+        assert_eq!(operand.displacement, 0x28);
+
+        let operand = memory("[gs:rax]");
+        assert_eq!(operand.segment, Register::GS);
+        assert_eq!(operand.base, Register::RAX);
+    }
+
+    #[test]
+    fn invalid_memory_operands() {
+        assert!(parse_operand("[rax*3]", &number).is_err());
+        assert!(parse_operand("[rax-rbx]", &number).is_err());
+        assert!(parse_operand("[rax+rbx+rcx]", &number).is_err());
+        assert!(parse_operand("[rax", &number).is_err());
+        assert!(parse_operand("[rax+]", &number).is_err());
+    }
+
+    #[test]
+    fn condition_aliases() {
+        assert_eq!(canonical_mnemonic("jz"), "je");
+        assert_eq!(canonical_mnemonic("cmovnae"), "cmovb");
+        assert_eq!(canonical_mnemonic("setc"), "setb");
+        assert_eq!(canonical_mnemonic("jpe"), "jp");
+        assert_eq!(canonical_mnemonic("mov"), "mov");
+    }
+
+    #[test]
+    fn lines() {
+        assert_eq!(
+            assemble_line("mov eax, [rbp-0x14]", 0x1000).unwrap(),
+            [0x8b, 0x45, 0xec]
+        );
+        assert_eq!(assemble_line("jz 0x1010", 0x1000).unwrap(), [0x74, 0x0e]);
+        assert_eq!(
+            assemble_line("lock inc dword [rax]", 0x1000).unwrap(),
+            [0xf0, 0xff, 0x00]
+        );
+        //sign extended imm8 is the shortest form of -1
+        assert_eq!(
+            assemble_line("add rax, 0xffffffffffffffff", 0x1000).unwrap(),
+            [0x48, 0x83, 0xc0, 0xff]
+        );
+        assert!(assemble_line("inc [rax]", 0x1000)
+            .unwrap_err()
+            .contains("Ambiguous"));
+        assert!(assemble_line("frobnicate eax", 0x1000).is_err());
+    }
+}
@@ -6,49 +6,134 @@ pub trait Breakpoint {
     fn enabled(&self) -> bool;
     fn hardware(&self) -> bool;
     fn address(&self) -> u64;
-    fn size(&self) -> usize;
     fn original_bytes<'a>(&'a self) -> Option<&'a [u8]>;
+    /// Index of the DR0-DR3 register holding this breakpoint, hardware breakpoints only
+    fn debug_register(&self) -> Option<usize>;
 
     fn enable(&mut self, debugee: &Debugee);
     fn disable(&mut self, debugee: &Debugee);
+    /// `data` got written at `address`, whatever of it lands under the breakpoint is what disabling
+    /// it restores from now on
+    fn overwritten(&mut self, address: u64, data: &[u8]);
+}
+
+pub type BreakpointId = u32;
+
+/// A breakpoint along with the bookkeeping the debugger keeps for it
+pub struct BreakpointEntry {
+    pub id: BreakpointId,
+    pub breakpoint: Box<dyn Breakpoint>,
+    pub hit_count: u64,
+    /// Expression evaluated on every hit, execution only stops when it's non-zero
+    pub condition: Option<String>,
+    /// Deleted after the first hit
+    pub one_shot: bool,
+    /// Placed by the debugger itself (step over, run to cursor), not shown to the user
+    pub internal: bool,
+    /// Breakpoints sharing a group get enabled and disabled together
+    pub group: Option<String>,
+    /// Description of the symbolic breakpoint this one was resolved from, or the source line it was set on
+    pub location: Option<String>,
+}
+
+impl BreakpointEntry {
+    pub fn new(id: BreakpointId, breakpoint: Box<dyn Breakpoint>) -> Self {
+        Self {
+            id,
+            breakpoint,
+            hit_count: 0,
+            condition: None,
+            one_shot: false,
+            internal: false,
+            group: None,
+            location: None,
+        }
+    }
+}
+
+/// A breakpoint placed by symbol name (`malloc`, `libssl.so!SSL_read`) or regex,
+/// resolved again whenever modules get loaded so it can be set before its library is
+#[derive(Clone)]
+pub struct SymbolicBreakpoint {
+    pub pattern: String,
+    pub is_regex: bool,
+    /// Locations the user deleted, they don't get resolved again
+    pub removed: Vec<u64>,
+}
+
+impl SymbolicBreakpoint {
+    pub fn new(pattern: &str, is_regex: bool) -> Result<Self, String> {
+        let pattern = pattern.trim();
+
+        if pattern.is_empty() {
+            return Err(String::from("Empty symbol name"));
+        }
+
+        if is_regex {
+            regex::Regex::new(pattern).map_err(|e| e.to_string())?;
+        }
+
+        Ok(Self {
+            pattern: pattern.to_owned(),
+            is_regex,
+            removed: Vec::new(),
+        })
+    }
+
+    pub fn same_pattern(&self, other: &Self) -> bool {
+        self.pattern == other.pattern && self.is_regex == other.is_regex
+    }
+
+    pub fn regex(&self) -> Option<regex::Regex> {
+        self.is_regex
+            .then(|| regex::Regex::new(&self.pattern).ok())
+            .flatten()
+    }
+
+    pub fn description(&self) -> String {
+        if self.is_regex {
+            format!("/{}/", self.pattern)
+        } else {
+            self.pattern.clone()
+        }
+    }
 }
 pub struct SoftwareBreakpoint {
     enabled: bool,
     address: u64,
-    size: usize,
     original_bytes: Vec<u8>,
 }
 
 impl SoftwareBreakpoint {
-    pub const fn new(address: u64, instruction_size: u64) -> Self {
+    pub const fn new(address: u64) -> Self {
         Self {
             enabled: false,
             address,
-            size: instruction_size as usize,
             original_bytes: Vec::new(),
         }
     }
 }
 
 impl Breakpoint for SoftwareBreakpoint {
-    #[allow(unreachable_code, unused)]
+    /// The original byte is read past the int3s of the other breakpoints, one of them could be
+    /// on the same address already
     fn enable(&mut self, debugee: &Debugee) {
         if self.enabled {
             return;
         }
 
-        self.original_bytes = debugee.read_memory(self.address as usize, 1);
-        debugee.write_memory(self.address as usize + 1, &vec![0xCCu8]);
-
-        println!("enabled");
+        self.original_bytes = debugee.read_code(self.address as usize, 1);
+        debugee.poke_memory(self.address as usize, &[0xCC]);
 
         self.enabled = true;
     }
 
     fn disable(&mut self, debugee: &Debugee) {
-        debugee.write_memory(self.address as usize + 1, &self.original_bytes);
+        if !self.enabled {
+            return;
+        }
 
-        println!("disabled");
+        debugee.poke_memory(self.address as usize, &self.original_bytes);
 
         self.enabled = false;
     }
@@ -65,13 +150,24 @@ impl Breakpoint for SoftwareBreakpoint {
         false
     }
 
-    fn size(&self) -> usize {
-        self.size
-    }
-
     fn original_bytes<'a>(&'a self) -> Option<&'a [u8]> {
         Some(&self.original_bytes)
     }
+
+    fn overwritten(&mut self, address: u64, data: &[u8]) {
+        for (i, byte) in self.original_bytes.iter_mut().enumerate() {
+            if let Some(new) = (self.address + i as u64)
+                .checked_sub(address)
+                .and_then(|offset| data.get(offset as usize))
+            {
+                *byte = *new;
+            }
+        }
+    }
+
+    fn debug_register(&self) -> Option<usize> {
+        None
+    }
 }
 
 pub struct HardwareBreakpoint {
@@ -108,6 +204,10 @@ impl Breakpoint for HardwareBreakpoint {
     }
 
     fn enable(&mut self, debugee: &Debugee) {
+        if self.enabled {
+            return;
+        }
+
         println!("actual dr7 {:#b}", read_dr(debugee, 7));
 
         //drX = addr
@@ -130,9 +230,15 @@ impl Breakpoint for HardwareBreakpoint {
         write_dr(debugee, 7, new_dr7);
 
         println!("new_dr7 = {new_dr7:#b}");
+
+        self.enabled = true;
     }
 
     fn disable(&mut self, debugee: &Debugee) {
+        if !self.enabled {
+            return;
+        }
+
         println!("actual dr7 {:#b}", read_dr(debugee, 7));
 
         //drX = 0
@@ -149,19 +255,21 @@ impl Breakpoint for HardwareBreakpoint {
         self.enabled = false;
     }
 
-    fn size(&self) -> usize {
-        0
-    }
-
     fn original_bytes<'a>(&'a self) -> Option<&'a [u8]> {
         None
     }
+
+    fn overwritten(&mut self, _address: u64, _data: &[u8]) {}
+
+    fn debug_register(&self) -> Option<usize> {
+        Some(self.register_index)
+    }
 }
 
-fn read_dr(debugee: &Debugee, idx: usize) -> u64 {
+pub(super) fn read_dr(debugee: &Debugee, idx: usize) -> u64 {
     debugee.read_user(std::mem::offset_of!(libc::user, u_debugreg) + idx * 8)
 }
 
-fn write_dr(debugee: &Debugee, idx: usize, data: u64) {
+pub(super) fn write_dr(debugee: &Debugee, idx: usize, data: u64) {
     debugee.write_user(std::mem::offset_of!(libc::user, u_debugreg) + idx * 8, data);
 }
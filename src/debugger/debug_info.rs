@@ -0,0 +1,134 @@
+use std::path::{Path, PathBuf};
+
+use object::Object;
+
+/// Where to look for the debug info of stripped modules
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct DebugInfoSettings {
+    /// Global debug directories, searched by build-id (`.build-id/xx/yyyy.debug`) and debuglink
+    pub directories: Vec<String>,
+    /// Cache of a debuginfod client, laid out as `<build-id>/debuginfo`
+    pub debuginfod_cache: Option<String>,
+}
+
+impl Default for DebugInfoSettings {
+    fn default() -> Self {
+        Self {
+            directories: vec![String::from("/usr/lib/debug")],
+            debuginfod_cache: default_debuginfod_cache(),
+        }
+    }
+}
+
+/// Same lookup order as the debuginfod client library
+fn default_debuginfod_cache() -> Option<String> {
+    if let Ok(path) = std::env::var("DEBUGINFOD_CACHE_PATH") {
+        return Some(path);
+    }
+
+    if let Ok(cache) = std::env::var("XDG_CACHE_HOME") {
+        return Some(format!("{cache}/debuginfod_client"));
+    }
+
+    std::env::var("HOME")
+        .ok()
+        .map(|home| format!("{home}/.cache/debuginfod_client"))
+}
+
+/// File holding the DWARF of the module at `path`: the module itself if it wasn't stripped,
+/// otherwise a separate debug file found by build-id, debuglink or in the debuginfod cache
+pub fn find_debug_file(
+    path: &str,
+    file: &object::File,
+    settings: &DebugInfoSettings,
+) -> Option<String> {
+    if file.section_by_name(".debug_info").is_some() {
+        return Some(path.to_owned());
+    }
+
+    let build_id = file.build_id().ok().flatten().filter(|id| id.len() > 1);
+
+    if let Some(build_id) = build_id {
+        let hex = to_hex(build_id);
+
+        for directory in &settings.directories {
+            let candidate = format!("{directory}/.build-id/{}/{}.debug", &hex[..2], &hex[2..]);
+
+            if has_build_id(&candidate, build_id) {
+                return Some(candidate);
+            }
+        }
+    }
+
+    if let Ok(Some((name, crc))) = file.gnu_debuglink() {
+        let name = String::from_utf8_lossy(name);
+        let directory = Path::new(path).parent().unwrap_or(Path::new("/"));
+
+        let mut candidates = vec![
+            directory.join(&*name),
+            directory.join(".debug").join(&*name),
+        ];
+        for global in &settings.directories {
+            //the module's directory gets mirrored under the global one
+            let relative = directory.strip_prefix("/").unwrap_or(directory);
+            candidates.push(Path::new(global).join(relative).join(&*name));
+        }
+
+        //the debuglink can name the stripped file itself
+        let original = std::fs::canonicalize(path).ok();
+
+        for candidate in candidates {
+            if std::fs::canonicalize(&candidate).ok() == original {
+                continue;
+            }
+
+            if has_crc(&candidate, crc) {
+                return Some(candidate.to_string_lossy().into_owned());
+            }
+        }
+    }
+
+    if let (Some(build_id), Some(cache)) = (build_id, &settings.debuginfod_cache) {
+        let candidate = PathBuf::from(cache)
+            .join(to_hex(build_id))
+            .join("debuginfo");
+
+        if candidate.is_file() {
+            return Some(candidate.to_string_lossy().into_owned());
+        }
+    }
+
+    None
+}
+
+/// GNU build-id of the ELF file at `path` as hex
+pub fn read_build_id(path: &str) -> Option<String> {
+    let data = std::fs::read(path).ok()?;
+    let file = object::File::parse(&*data).ok()?;
+
+    file.build_id()
+        .ok()
+        .flatten()
+        .filter(|id| id.len() > 1)
+        .map(to_hex)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn has_build_id(path: &str, build_id: &[u8]) -> bool {
+    let Ok(data) = std::fs::read(path) else {
+        return false;
+    };
+
+    object::File::parse(&*data)
+        .ok()
+        .and_then(|file| file.build_id().ok().flatten().map(|id| id == build_id))
+        .unwrap_or(false)
+}
+
+/// `.gnu_debuglink` carries the CRC32 of the whole debug file, so stale copies get rejected
+fn has_crc(path: &Path, crc: u32) -> bool {
+    std::fs::read(path).is_ok_and(|data| crc32fast::hash(&data) == crc)
+}
@@ -1,23 +1,105 @@
+use std::error::Error;
+use std::ffi::CString;
+use std::ops::Range;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::FileExt;
+use std::path::Path;
 use std::sync::mpsc::{self, Receiver, Sender};
 use std::thread::JoinHandle;
 
-use super::breakpoint::{Breakpoint, HardwareBreakpoint, SoftwareBreakpoint};
+use super::annotation::{self, Telescope};
+use super::breakpoint::{
+    self, Breakpoint, BreakpointEntry, BreakpointId, HardwareBreakpoint, SoftwareBreakpoint,
+    SymbolicBreakpoint,
+};
+use super::debug_info::DebugInfoSettings;
+use super::expression;
+use super::memory_map::MemoryMap;
+use super::notes::{ModuleAddress, Note, Notes};
+use super::patch::{self, Patch};
+use super::rendezvous::{ModuleEvent, Rendezvous};
+use super::symbols::{DemangleSettings, SymbolTable};
+use super::unwind::{self, FrameRegisters, StackFrame};
+use super::variables::{self, Variable};
+
+//si_code values of a SIGTRAP, see man 2 sigaction
+const TRAP_BRKPT: i32 = 1;
+const TRAP_HWBKPT: i32 = 4;
+const SI_KERNEL: i32 = 0x80;
+
+//instructions a source step single steps through before the ui gets to draw a frame
+const STEP_BATCH_INSTRUCTIONS: usize = 2_000;
+//and before giving up on a line that never ends
+pub const MAX_STEP_INSTRUCTIONS: usize = 100_000;
+
+/// A stop caused by one of our breakpoints
+pub struct BreakpointHit {
+    pub address: u64,
+    /// Hit one of the debugger's own breakpoints (step over, run to cursor)
+    pub internal: bool,
+    /// False when the breakpoint's condition evaluated to zero, execution should resume
+    pub condition_met: bool,
+    pub condition_error: Option<String>,
+    /// Libraries ld.so (un)loaded, only ever filled in when the hit was the rendezvous breakpoint
+    pub module_events: Vec<ModuleEvent>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StepKind {
+    Into,
+    Over,
+    Out,
+}
+
+/// Where a source step stands after the stop it reported
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StepProgress {
+    /// Ran a batch of instructions without getting anywhere yet, `continue_source_step` runs the next
+    Paused,
+    /// Single stepped `MAX_STEP_INSTRUCTIONS` without reaching another line
+    GaveUp,
+}
+
+/// A source level step in progress, survives the continues used to run over calls
+struct SourceStep {
+    kind: StepKind,
+    /// Addresses of the line being stepped, leaving them means a new line might've been reached
+    range: Range<u64>,
+    /// Stack pointer when the step started, anything below it is a callee
+    frame: u64,
+    /// Single stepped so far
+    instructions: usize,
+    progress: Option<StepProgress>,
+}
 
 pub struct Debugee {
     pub stopped: bool,
     pid: u32,
     _waitpid_thread: JoinHandle<()>,
     pub waitpid_communication: (Sender<i32>, Receiver<i32>),
-    breakpoints: Vec<Box<dyn Breakpoint>>,
+    breakpoints: Vec<BreakpointEntry>,
+    next_breakpoint_id: BreakpointId,
+    symbolic_breakpoints: Vec<SymbolicBreakpoint>,
     context: libc::user_regs_struct,
-    hardware_breakpoints: usize,
+    memory_map: MemoryMap,
+    symbols: SymbolTable,
+    rendezvous: Option<Rendezvous>,
+    source_step: Option<SourceStep>,
+    memory_writes: Vec<Range<u64>>,
+    patches: Vec<Patch>,
+    notes: Notes,
+}
+
+/// Whether `status` is the stop ptrace reports right after the tracee called execve
+pub fn is_exec_event(status: i32) -> bool {
+    status >> 8 == (libc::SIGTRAP | (libc::PTRACE_EVENT_EXEC << 8))
 }
 
 impl Debugee {
-    pub fn new(pid: u32) -> Result<Self, Box<dyn std::error::Error>> {
+    pub fn new(pid: u32) -> Result<Self, Box<dyn Error>> {
         //TODO: error check probably
         unsafe {
-            libc::ptrace(libc::PTRACE_SEIZE, pid, 0, 0);
+            libc::ptrace(libc::PTRACE_SEIZE, pid, 0, libc::PTRACE_O_TRACEEXEC);
         }
 
         let (tx, rx) = mpsc::channel::<i32>();
@@ -26,15 +108,65 @@ impl Debugee {
 
         let _waitpid_thread = std::thread::spawn(move || waitpid_thread(pid, sender));
 
-        Ok(Self {
+        let mut debugee = Self {
             stopped: false,
             pid,
             _waitpid_thread,
             waitpid_communication: (tx, rx),
             breakpoints: Vec::new(),
+            next_breakpoint_id: 1,
+            symbolic_breakpoints: Vec::new(),
             context: unsafe { std::mem::zeroed() }, //this is safe trust me :)
-            hardware_breakpoints: 0,
-        })
+            memory_map: MemoryMap::default(),
+            symbols: SymbolTable::default(),
+            rendezvous: None,
+            source_step: None,
+            memory_writes: Vec::new(),
+            patches: Vec::new(),
+            notes: Notes::default(),
+        };
+
+        debugee.refresh_memory_map();
+
+        Ok(debugee)
+    }
+
+    /// Starts `path` under the debugger. The first stop is the exec event, before ld.so
+    /// had a chance to run, so breakpoints can be placed before anything executes
+    pub fn launch(path: &Path) -> Result<Self, Box<dyn Error>> {
+        //allocate before forking, only async signal safe calls are allowed in the child
+        let program = CString::new(path.as_os_str().as_bytes())?;
+        let argv = [program.as_ptr(), std::ptr::null()];
+
+        let pid = unsafe { libc::fork() };
+
+        if pid == -1 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+
+        if pid == 0 {
+            unsafe {
+                //wait for the debugger to seize us
+                libc::raise(libc::SIGSTOP);
+                libc::execv(program.as_ptr(), argv.as_ptr());
+                libc::_exit(127);
+            }
+        }
+
+        let mut status = 0;
+        unsafe {
+            libc::waitpid(pid, &mut status, libc::WUNTRACED);
+        }
+
+        let debugee = Self::new(pid as u32)?;
+
+        //seizing a stopped process reports the stop again, swallow it and let it exec
+        debugee.waitpid_communication.1.recv()?;
+        unsafe {
+            libc::ptrace(libc::PTRACE_CONT, pid, 0, 0);
+        }
+
+        Ok(debugee)
     }
 
     pub fn detach(&mut self) {
@@ -43,49 +175,161 @@ impl Debugee {
         }
     }
 
-    /// Kills the process if it's already stopped (default ptrace behaviour)
+    /// Asks the process to stop. It isn't stopped until the stop comes through waitpid,
+    /// `stopped` gets set when that's handled like any other stop
     pub fn stop(&mut self) {
         unsafe {
             libc::ptrace(libc::PTRACE_INTERRUPT, self.pid, 0, 0);
         }
-        self.update_context();
-        self.stopped = true;
     }
 
-    pub fn r#continue(&mut self) {
+    pub fn r#continue(&mut self) -> Result<(), String> {
         self.update_context();
-        unsafe {
-            //yes, two calls are required
-            libc::ptrace(libc::PTRACE_CONT, self.pid, 0, 0);
-            libc::ptrace(libc::PTRACE_CONT, self.pid, 0, 0);
+
+        if let Some(status) = self.step_over_breakpoint()
+            && !libc::WIFSTOPPED(status)
+        {
+            //process died while stepping, let the ui know
+            let _ = self.waitpid_communication.0.send(status);
+            return Ok(());
+        }
+
+        if unsafe { libc::ptrace(libc::PTRACE_CONT, self.pid, 0, 0) } == -1 {
+            return Err(format!(
+                "Failed to continue: {}",
+                std::io::Error::last_os_error()
+            ));
         }
+
         self.stopped = false;
+        Ok(())
     }
 
     pub fn single_step(&mut self) {
+        if let Some(status) = self.step_over_breakpoint() {
+            let _ = self.waitpid_communication.0.send(status);
+            return;
+        }
+
         unsafe {
             libc::ptrace(libc::PTRACE_SINGLESTEP, self.pid);
         }
         self.update_context();
     }
 
+    /// If rip sits on an enabled breakpoint, lifts it, executes the original instruction and puts it back.
+    /// Returns the wait status of the step if one happened
+    fn step_over_breakpoint(&mut self) -> Option<i32> {
+        let rip = self.context.rip;
+        let index = self
+            .breakpoints
+            .iter()
+            .position(|bp| bp.breakpoint.address() == rip && bp.breakpoint.enabled())?;
+
+        let mut breakpoints = std::mem::take(&mut self.breakpoints);
+        breakpoints[index].breakpoint.disable(self);
+
+        unsafe {
+            libc::ptrace(libc::PTRACE_SINGLESTEP, self.pid, 0, 0);
+        }
+        let status = self.waitpid_communication.1.recv().ok();
+
+        if status.is_some_and(|s| libc::WIFSTOPPED(s)) {
+            breakpoints[index].breakpoint.enable(self);
+        }
+
+        self.breakpoints = breakpoints;
+        self.update_context();
+
+        status
+    }
+
     //TODO: use /proc/<pid>/mem for io!!!
 
-    pub fn write_memory(&self, address: usize, data: &[u8]) {
+    /// Writes to the process on behalf of the user and records it as a patch, caches of its memory
+    /// get told through `memory_writes`
+    pub fn write_memory(&mut self, address: usize, data: &[u8]) {
+        let original = self.read_code(address, data.len());
+        patch::record(
+            &mut self.patches,
+            Patch {
+                address: address as u64,
+                original,
+                patched: data.to_vec(),
+            },
+        );
+
+        self.overwrite(address, data);
+    }
+
+    /// Breakpoints in the way stay, the bytes under them are what they restore once removed
+    fn overwrite(&mut self, address: usize, data: &[u8]) {
+        let mut patched = data.to_vec();
+
+        for bp in self.breakpoints.iter_mut().map(|bp| &mut bp.breakpoint) {
+            if !bp.enabled() || bp.hardware() {
+                continue;
+            }
+
+            bp.overwritten(address as u64, data);
+            if let Some(byte) = (bp.address() as usize)
+                .checked_sub(address)
+                .and_then(|offset| patched.get_mut(offset))
+            {
+                *byte = 0xCC;
+            }
+        }
+
+        self.memory_writes
+            .push(address as u64..(address + data.len()) as u64);
+        self.poke_memory(address, &patched);
+    }
+
+    /// Every range `write_memory` wrote to since the process started, oldest first
+    pub fn memory_writes(&self) -> &[Range<u64>] {
+        &self.memory_writes
+    }
+
+    /// What `write_memory` changed and is still different from the original bytes, sorted by address
+    pub fn patches(&self) -> &[Patch] {
+        &self.patches
+    }
+
+    /// Puts the original bytes of a patch back
+    pub fn revert_patch(&mut self, index: usize) {
+        if index < self.patches.len() {
+            let patch = self.patches.remove(index);
+            self.overwrite(patch.address as usize, &patch.original);
+        }
+    }
+
+    pub fn revert_all_patches(&mut self) {
+        for patch in std::mem::take(&mut self.patches) {
+            self.overwrite(patch.address as usize, &patch.original);
+        }
+    }
+
+    /// Writes without recording it, for int3s which nothing reading memory should see anyway
+    pub(super) fn poke_memory(&self, address: usize, data: &[u8]) {
         for i in 0..(data.len() as f32 / 8.0).floor() as usize {
             unsafe {
                 libc::ptrace(
                     libc::PTRACE_POKEDATA,
                     self.pid,
                     address + i * 8,
-                    u64::from_le_bytes(data[i..i + 8].try_into().unwrap()),
+                    u64::from_le_bytes(data[i * 8..i * 8 + 8].try_into().unwrap()),
                 )
             };
         }
 
         let left_over = data.len() % 8;
+        if left_over == 0 {
+            return;
+        }
 
-        let mut original = self.read_memory(address - left_over, 8);
+        //the tail goes in with the bytes after it, read back first so they stay as they were
+        let tail = address + data.len() - left_over;
+        let mut original = self.read_memory(tail, 8);
         original
             .iter_mut()
             .take(left_over)
@@ -96,7 +340,7 @@ impl Debugee {
             libc::ptrace(
                 libc::PTRACE_POKEDATA,
                 self.pid,
-                address - left_over,
+                tail,
                 u64::from_le_bytes(original.try_into().unwrap()),
             )
         };
@@ -117,6 +361,29 @@ impl Debugee {
         read.into_iter().take(size).collect()
     }
 
+    pub fn read_u64(&self, address: u64) -> u64 {
+        u64::from_le_bytes(self.read_memory(address as usize, 8).try_into().unwrap())
+    }
+
+    /// NUL terminated string of at most `max_len` bytes
+    pub fn read_c_string(&self, address: u64, max_len: usize) -> String {
+        let mut bytes = Vec::new();
+
+        while bytes.len() < max_len {
+            let chunk = self.read_memory(address as usize + bytes.len(), 8);
+
+            if let Some(end) = chunk.iter().position(|b| *b == 0) {
+                bytes.extend_from_slice(&chunk[..end]);
+                break;
+            }
+
+            bytes.extend_from_slice(&chunk);
+        }
+
+        bytes.truncate(max_len);
+        String::from_utf8_lossy(&bytes).into_owned()
+    }
+
     pub fn kill(&mut self) {
         unsafe {
             libc::kill(self.pid as i32, libc::SIGKILL);
@@ -150,45 +417,925 @@ impl Debugee {
         unsafe { libc::ptrace(libc::PTRACE_PEEKUSER, self.pid, offset, 0) as u64 }
     }
 
-    pub fn breakpoints(&self) -> &Vec<Box<dyn Breakpoint>> {
+    pub fn breakpoints(&self) -> &[BreakpointEntry] {
         &self.breakpoints
     }
 
-    pub fn breakpoint_at_address(&mut self, addr: u64) -> Option<&mut Box<dyn Breakpoint>> {
-        self.breakpoints.iter_mut().find(|bp| bp.address() == addr)
+    pub fn breakpoint(&mut self, id: BreakpointId) -> Option<&mut BreakpointEntry> {
+        self.breakpoints.iter_mut().find(|bp| bp.id == id)
+    }
+
+    /// An internal breakpoint can share its address with one of the user's, an enabled one is what
+    /// gets hit and the user's wins over ours
+    pub fn breakpoint_at_address(&mut self, addr: u64) -> Option<&mut BreakpointEntry> {
+        let at = |bp: &BreakpointEntry| bp.breakpoint.address() == addr;
+        let enabled = |bp: &BreakpointEntry| at(bp) && bp.breakpoint.enabled();
+        let index = self
+            .breakpoints
+            .iter()
+            .position(|bp| enabled(bp) && !bp.internal)
+            .or_else(|| self.breakpoints.iter().position(enabled))
+            .or_else(|| self.breakpoints.iter().position(at))?;
+
+        self.breakpoints.get_mut(index)
+    }
+
+    /// The breakpoint the user placed at `addr`, ignoring the ones the debugger placed itself
+    pub fn user_breakpoint_at_address(&mut self, addr: u64) -> Option<&mut BreakpointEntry> {
+        self.breakpoints
+            .iter_mut()
+            .find(|bp| bp.breakpoint.address() == addr && !bp.internal)
+    }
+
+    fn push_breakpoint(&mut self, breakpoint: Box<dyn Breakpoint>) -> BreakpointId {
+        let id = self.next_breakpoint_id;
+        self.next_breakpoint_id += 1;
+
+        self.breakpoints.push(BreakpointEntry::new(id, breakpoint));
+        id
+    }
+
+    pub fn add_software_breakpoint(&mut self, addr: u64) -> BreakpointId {
+        let mut breakpoint = SoftwareBreakpoint::new(addr);
+        breakpoint.enable(self);
+        self.push_breakpoint(Box::new(breakpoint))
     }
 
-    pub fn add_software_breakpoint(&mut self, addr: u64 /*hardware: bool*/, size: u64) {
-        let mut breakpoint = SoftwareBreakpoint::new(addr, size);
+    pub fn add_hardware_breakpoint(&mut self, addr: u64) -> Result<BreakpointId, String> {
+        //disabled breakpoints keep their register so they can be re-enabled later
+        let register_index = (0..4)
+            .find(|i| {
+                !self
+                    .breakpoints
+                    .iter()
+                    .any(|bp| bp.breakpoint.debug_register() == Some(*i))
+            })
+            .ok_or_else(|| String::from("All 4 debug registers are in use"))?;
+
+        let mut breakpoint = HardwareBreakpoint::new(addr, register_index).unwrap();
         breakpoint.enable(self);
-        self.breakpoints.push(Box::new(breakpoint));
+        Ok(self.push_breakpoint(Box::new(breakpoint)))
     }
 
-    pub fn add_hardware_breakpoint(&mut self, addr: u64) {
-        if self.hardware_breakpoints >= 4 {
+    /// Places a breakpoint the user never sees, it's gone after the first hit or the next stop
+    fn add_internal_breakpoint(&mut self, addr: u64, condition: Option<String>) {
+        if self
+            .breakpoint_at_address(addr)
+            .is_some_and(|bp| bp.breakpoint.enabled())
+        {
+            //we're stopping there anyway
             return;
         }
 
-        let mut breakpoint = HardwareBreakpoint::new(addr, self.hardware_breakpoints).unwrap();
-        breakpoint.enable(self);
-        self.breakpoints.push(Box::new(breakpoint));
-        self.hardware_breakpoints += 1;
+        let id = self.add_software_breakpoint(addr);
+        let bp = self.breakpoint(id).unwrap();
+        bp.one_shot = true;
+        bp.internal = true;
+        bp.condition = condition;
+    }
+
+    /// Drops the leftovers of step over and run to, the rendezvous breakpoint stays
+    pub fn remove_internal_breakpoints(&mut self) {
+        let ids = self
+            .breakpoints
+            .iter()
+            .filter(|bp| bp.internal && bp.one_shot)
+            .map(|bp| bp.id)
+            .collect::<Vec<_>>();
+
+        for id in ids {
+            self.remove_breakpoint(id);
+        }
+    }
+
+    pub fn remove_breakpoint(&mut self, id: BreakpointId) {
+        if let Some(index) = self.breakpoints.iter().position(|bp| bp.id == id) {
+            let mut removed = self.breakpoints.remove(index);
+            removed.breakpoint.disable(self);
+            self.restore_int3(removed.breakpoint.address());
+            self.forget_symbolic_location(&removed);
+        }
+    }
+
+    /// Another enabled breakpoint on the same byte still needs its int3 after one got disabled
+    fn restore_int3(&self, address: u64) {
+        if self.breakpoints.iter().any(|bp| {
+            bp.breakpoint.address() == address
+                && bp.breakpoint.enabled()
+                && !bp.breakpoint.hardware()
+        }) {
+            self.poke_memory(address as usize, &[0xCC]);
+        }
     }
 
+    /// Keeps the symbolic breakpoint `bp` was resolved from from placing it again
+    fn forget_symbolic_location(&mut self, bp: &BreakpointEntry) {
+        let Some(location) = &bp.location else {
+            return;
+        };
+
+        if let Some(symbolic) = self
+            .symbolic_breakpoints
+            .iter_mut()
+            .find(|symbolic| symbolic.description() == *location)
+        {
+            symbolic.removed.push(bp.breakpoint.address());
+        }
+    }
+
+    /// Removes the user's breakpoint at `addr`, ours stay
     pub fn try_remove_breakpoint(&mut self, addr: u64) {
-        let mut breakpoints = std::mem::replace(&mut self.breakpoints, Vec::new());
+        if let Some(id) = self.user_breakpoint_at_address(addr).map(|bp| bp.id) {
+            self.remove_breakpoint(id);
+        }
+    }
+
+    pub fn remove_all_breakpoints(&mut self) {
+        let ids = self
+            .breakpoints
+            .iter()
+            .filter(|bp| !bp.internal)
+            .map(|bp| bp.id)
+            .collect::<Vec<_>>();
+
+        for id in ids {
+            self.remove_breakpoint(id);
+        }
 
-        if let Some(breakpoint_index) = breakpoints.iter().position(|bp| bp.address() == addr) {
-            breakpoints[breakpoint_index].disable(self);
+        //they'd be back on the next stop otherwise
+        self.symbolic_breakpoints.clear();
+    }
+
+    /// Only this one is taken out of the list meanwhile, so it sees what's under the others
+    pub fn set_breakpoint_enabled(&mut self, id: BreakpointId, enabled: bool) {
+        let Some(index) = self.breakpoints.iter().position(|bp| bp.id == id) else {
+            return;
+        };
+
+        let mut bp = self.breakpoints.remove(index);
+        if enabled {
+            bp.breakpoint.enable(self);
+        } else {
+            bp.breakpoint.disable(self);
+            self.restore_int3(bp.breakpoint.address());
+        }
+
+        self.breakpoints.insert(index, bp);
+    }
+
+    pub fn set_all_breakpoints_enabled(&mut self, enabled: bool) {
+        let ids = self
+            .breakpoints
+            .iter()
+            .filter(|bp| !bp.internal)
+            .map(|bp| bp.id)
+            .collect::<Vec<_>>();
+
+        for id in ids {
+            self.set_breakpoint_enabled(id, enabled);
+        }
+    }
+
+    /// Sorted, deduplicated names of all breakpoint groups
+    pub fn breakpoint_groups(&self) -> Vec<String> {
+        let mut groups = self
+            .breakpoints
+            .iter()
+            .filter_map(|bp| bp.group.clone())
+            .collect::<Vec<_>>();
 
-            if breakpoints[breakpoint_index].hardware() {
-                self.hardware_breakpoints -= 1;
+        groups.sort();
+        groups.dedup();
+        groups
+    }
+
+    pub fn set_breakpoint_group(&mut self, id: BreakpointId, group: Option<String>) {
+        if let Some(bp) = self.breakpoint(id) {
+            bp.group = group.map(|g| g.trim().to_owned()).filter(|g| !g.is_empty());
+        }
+    }
+
+    pub fn set_group_enabled(&mut self, group: &str, enabled: bool) {
+        let ids = self
+            .breakpoints
+            .iter()
+            .filter(|bp| bp.group.as_deref() == Some(group))
+            .map(|bp| bp.id)
+            .collect::<Vec<_>>();
+
+        for id in ids {
+            self.set_breakpoint_enabled(id, enabled);
+        }
+    }
+
+    /// Moves a breakpoint to `new_addr`, keeping its kind, condition and enabled state
+    pub fn move_breakpoint(&mut self, id: BreakpointId, new_addr: u64) -> Result<(), String> {
+        let Some(index) = self.breakpoints.iter().position(|bp| bp.id == id) else {
+            return Err(format!("No breakpoint with id {id}"));
+        };
+
+        if self.breakpoints[index].breakpoint.address() == new_addr {
+            return Ok(());
+        }
+
+        if self.user_breakpoint_at_address(new_addr).is_some() {
+            return Err(format!("There already is a breakpoint at {new_addr:#x}"));
+        }
+
+        let mut old = self.breakpoints.remove(index);
+        let was_enabled = old.breakpoint.enabled();
+        old.breakpoint.disable(self);
+        self.restore_int3(old.breakpoint.address());
+        self.forget_symbolic_location(&old);
+
+        let mut breakpoint: Box<dyn Breakpoint> =
+            if let Some(register_index) = old.breakpoint.debug_register() {
+                Box::new(HardwareBreakpoint::new(new_addr, register_index).unwrap())
+            } else {
+                Box::new(SoftwareBreakpoint::new(new_addr))
+            };
+
+        if was_enabled {
+            breakpoint.enable(self);
+        }
+
+        self.breakpoints
+            .insert(index, BreakpointEntry { breakpoint, ..old });
+
+        Ok(())
+    }
+
+    pub fn set_breakpoint_condition(&mut self, id: BreakpointId, condition: Option<String>) {
+        if let Some(bp) = self.breakpoint(id) {
+            bp.condition = condition.filter(|c| !c.trim().is_empty());
+        }
+    }
+
+    pub fn set_breakpoint_one_shot(&mut self, id: BreakpointId, one_shot: bool) {
+        if let Some(bp) = self.breakpoint(id) {
+            bp.one_shot = one_shot;
+        }
+    }
+
+    /// Continues until `addr` is reached (or something else stops the process first)
+    pub fn run_to(&mut self, addr: u64) {
+        self.add_internal_breakpoint(addr, None);
+        //only fails if the process is gone, waitpid reports that
+        let _ = self.r#continue();
+    }
+
+    fn decode_at(&self, address: u64) -> iced_x86::Instruction {
+        let code = self.read_code(address as usize, 16);
+        let mut decoder =
+            iced_x86::Decoder::with_ip(64, &code, address, iced_x86::DecoderOptions::NONE);
+        decoder.decode()
+    }
+
+    /// Single steps, unless rip is on a call, in which case it runs until the call returns
+    pub fn step_over(&mut self) {
+        self.update_context();
+
+        let instruction = self.decode_at(self.context.rip);
+
+        if is_call(&instruction) || instruction.has_rep_prefix() {
+            self.run_until_return(instruction.next_ip(), self.context.rsp);
+        } else {
+            self.single_step();
+        }
+    }
+
+    /// Continues until execution is back at `address` with the stack pointer at `rsp`,
+    /// which is how a call (or rep string op) gets run over
+    fn run_until_return(&mut self, address: u64, rsp: u64) {
+        //the stack pointer check keeps recursive calls from stopping early
+        self.add_internal_breakpoint(address, Some(format!("rsp == {rsp:#x}")));
+        //only fails if the process is gone, waitpid reports that
+        let _ = self.r#continue();
+    }
+
+    /// Steps a whole source line, or out of the current function. Without line info
+    /// at rip, into and over fall back to their instruction level counterparts.
+    /// Stepping out runs to the return address when the stack can be unwound
+    pub fn step_source(&mut self, kind: StepKind) {
+        self.update_context();
+
+        if kind == StepKind::Out
+            && let Some((address, rsp)) = self.return_address()
+        {
+            self.run_until_return(address, rsp);
+            return;
+        }
+
+        let rip = self.context.rip;
+        let range = self.symbols.line_range(rip);
+
+        match (kind, range) {
+            (StepKind::Into, None) => self.single_step(),
+            (StepKind::Over, None) => self.step_over(),
+            (kind, range) => {
+                self.source_step = Some(SourceStep {
+                    kind,
+                    range: range.unwrap_or(rip..rip),
+                    frame: self.context.rsp,
+                    instructions: 0,
+                    progress: None,
+                });
+
+                self.advance_source_step();
             }
+        }
+    }
+
+    /// Where the current function returns to and the stack pointer once it has
+    fn return_address(&self) -> Option<(u64, u64)> {
+        let caller = self.backtrace().into_iter().nth(1)?;
+        let address = caller.registers.pc();
+
+        (address != 0).then_some((address, caller.registers.sp()?))
+    }
+
+    /// Abandons the source step, something else stopped the process or the user gave up on it
+    pub fn cancel_source_step(&mut self) {
+        self.source_step = None;
+    }
 
-            breakpoints.remove(breakpoint_index);
+    pub fn source_step_progress(&self) -> Option<StepProgress> {
+        self.source_step.as_ref().and_then(|step| step.progress)
+    }
+
+    /// Runs the next batch of a paused source step
+    pub fn continue_source_step(&mut self) {
+        if let Some(step) = self.source_step.as_mut() {
+            step.progress = None;
+            self.advance_source_step();
         }
+    }
 
-        self.breakpoints = breakpoints;
+    /// Called when the return address breakpoint of a call the source step ran over got hit.
+    /// Returns false if the step ends right there, the stop is then reported like any other
+    pub fn resume_source_step(&mut self) -> bool {
+        let Some(kind) = self.source_step.as_ref().map(|step| step.kind) else {
+            return false;
+        };
+
+        //the return address can already be the start of the next line
+        if kind != StepKind::Out && self.reached_new_line() {
+            self.source_step = None;
+            return false;
+        }
+
+        self.advance_source_step();
+        true
+    }
+
+    /// Whether rip is on a line the source step should stop at. Landing in the middle of a line
+    /// (back from a call, line 0 code) makes that line the one being stepped instead
+    fn reached_new_line(&mut self) -> bool {
+        let rip = self.context.rip;
+        let Some(step) = self.source_step.as_mut() else {
+            return true;
+        };
+
+        if step.range.contains(&rip) {
+            return false;
+        }
+
+        match self.symbols.line_range(rip) {
+            //somewhere without line info, nothing better to do than stop
+            None => true,
+            Some(_) if self.symbols.is_statement_start(rip) => true,
+            Some(range) => {
+                step.range = range;
+                false
+            }
+        }
+    }
+
+    /// Single steps until the source step is done, then reports the final stop like any other.
+    /// Calls that shouldn't be stepped into are run over with a breakpoint on their return address.
+    /// Every `STEP_BATCH_INSTRUCTIONS` the step pauses and reports a stop so the ui stays responsive
+    /// and can cancel it, the step is marked as paused (or given up on) to tell that stop apart
+    fn advance_source_step(&mut self) {
+        let mut status = None;
+        let mut progress = None;
+
+        for i in 0.. {
+            let Some(step) = self.source_step.as_ref() else {
+                return;
+            };
+            let kind = step.kind;
+            let frame = step.frame;
+
+            if step.instructions >= MAX_STEP_INSTRUCTIONS {
+                progress = Some(StepProgress::GaveUp);
+                break;
+            }
+            if i == STEP_BATCH_INSTRUCTIONS {
+                progress = Some(StepProgress::Paused);
+                break;
+            }
+
+            let instruction = self.decode_at(self.context.rip);
+            let rsp = self.context.rsp;
+
+            if (is_call(&instruction) && kind != StepKind::Into) || instruction.has_rep_prefix() {
+                self.run_until_return(instruction.next_ip(), rsp);
+                return;
+            }
+
+            status = self.step_instruction();
+            if let Some(step) = self.source_step.as_mut() {
+                step.instructions += 1;
+            }
+
+            let Some(stopped) = status.filter(|&s| {
+                libc::WIFSTOPPED(s) && libc::WSTOPSIG(s) == libc::SIGTRAP && !is_exec_event(s)
+            }) else {
+                break;
+            };
+            status = Some(stopped);
+
+            let done = match kind {
+                StepKind::Out => {
+                    instruction.flow_control() == iced_x86::FlowControl::Return && rsp >= frame
+                }
+                //stepped into something without line info (plt stubs, libraries), run it to its return
+                StepKind::Into
+                    if is_call(&instruction)
+                        && self.symbols.source_location(self.context.rip).is_none() =>
+                {
+                    self.run_until_return(instruction.next_ip(), rsp);
+                    return;
+                }
+                _ => self.reached_new_line(),
+            };
+
+            if done {
+                break;
+            }
+        }
+
+        match (progress, self.source_step.as_mut()) {
+            (Some(progress), Some(step)) => {
+                step.progress = Some(progress);
+
+                //busy until the next batch, not up for anything the user asks for
+                if progress == StepProgress::Paused {
+                    self.stopped = false;
+                }
+            }
+            _ => self.source_step = None,
+        }
+
+        if let Some(status) = status {
+            let _ = self.waitpid_communication.0.send(status);
+        }
+    }
+
+    /// Executes the instruction at rip and waits for it, returns the wait status
+    fn step_instruction(&mut self) -> Option<i32> {
+        if let Some(status) = self.step_over_breakpoint() {
+            return Some(status);
+        }
+
+        unsafe {
+            libc::ptrace(libc::PTRACE_SINGLESTEP, self.pid, 0, 0);
+        }
+        let status = self.waitpid_communication.1.recv().ok();
+        self.update_context();
+
+        status
+    }
+
+    /// Like `read_memory`, but with our int3s replaced by the bytes they cover
+    pub fn read_code(&self, address: usize, size: usize) -> Vec<u8> {
+        let mut data = self.read_memory(address, size);
+        self.hide_breakpoints(address, &mut data);
+        data
+    }
+
+    fn hide_breakpoints(&self, address: usize, data: &mut [u8]) {
+        for bp in self.breakpoints.iter().map(|bp| &bp.breakpoint) {
+            if !bp.enabled() || bp.hardware() {
+                continue;
+            }
+
+            let bp_address = bp.address() as usize;
+            for (i, b) in bp.original_bytes().unwrap().iter().enumerate() {
+                if let Some(byte) = (bp_address + i)
+                    .checked_sub(address)
+                    .and_then(|offset| data.get_mut(offset))
+                {
+                    *byte = *b;
+                }
+            }
+        }
+    }
+
+    fn trap_code(&self) -> i32 {
+        let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+        unsafe {
+            libc::ptrace(
+                libc::PTRACE_GETSIGINFO,
+                self.pid,
+                0,
+                &mut info as *mut _ as usize,
+            );
+        }
+        info.si_code
+    }
+
+    /// Figures out whether the current SIGTRAP stop was caused by one of our breakpoints.
+    /// Rewinds rip back onto software breakpoints, bumps the hit counter and drops one-shot breakpoints
+    pub fn process_breakpoint_hit(&mut self) -> Option<BreakpointHit> {
+        let address = match self.trap_code() {
+            SI_KERNEL | TRAP_BRKPT => {
+                let address = self.context.rip - 1;
+                let bp = self.breakpoint_at_address(address)?;
+
+                if bp.breakpoint.hardware() || !bp.breakpoint.enabled() {
+                    return None;
+                }
+
+                self.set_rip(address);
+                address
+            }
+            TRAP_HWBKPT => {
+                let dr6 = breakpoint::read_dr(self, 6);
+                breakpoint::write_dr(self, 6, 0);
+
+                self.breakpoints
+                    .iter()
+                    .find(|bp| {
+                        bp.breakpoint
+                            .debug_register()
+                            .is_some_and(|i| dr6 & (1 << i) != 0)
+                    })?
+                    .breakpoint
+                    .address()
+            }
+            _ => return None,
+        };
+
+        let module_events = match self.rendezvous.take() {
+            Some(mut rendezvous) => {
+                let events = if rendezvous.breakpoint_address == address {
+                    rendezvous.update(self)
+                } else {
+                    Vec::new()
+                };
+                self.rendezvous = Some(rendezvous);
+                events
+            }
+            None => Vec::new(),
+        };
+
+        if !module_events.is_empty() {
+            //load symbols of the new libraries and resolve whatever was pending on them
+            self.refresh_memory_map();
+        }
+
+        let bp = self.breakpoint_at_address(address)?;
+        bp.hit_count += 1;
+
+        //ld.so telling us about a library is no reason to stop on its own
+        if bp.internal && !bp.one_shot {
+            return Some(BreakpointHit {
+                address,
+                internal: true,
+                condition_met: false,
+                condition_error: None,
+                module_events,
+            });
+        }
+
+        let id = bp.id;
+        let internal = bp.internal;
+        let one_shot = bp.one_shot;
+
+        let (condition_met, condition_error) = match bp.condition.clone() {
+            None => (true, None),
+            Some(condition) => match expression::evaluate(&condition, self) {
+                Ok(value) => (value != 0, None),
+                //rather stop than silently run past a broken condition
+                Err(error) => (true, Some(error)),
+            },
+        };
+
+        if condition_met && one_shot {
+            self.remove_breakpoint(id);
+        }
+
+        Some(BreakpointHit {
+            address,
+            internal,
+            condition_met,
+            condition_error,
+            module_events,
+        })
+    }
+
+    /// Re-reads the memory map, loads symbols of new modules and resolves pending breakpoints
+    pub fn refresh_memory_map(&mut self) {
+        if let Ok(memory_map) = MemoryMap::read(self.pid) {
+            self.memory_map = memory_map;
+        }
+
+        //ld.so is in the middle of (un)mapping something, wait until it's done
+        if self.rendezvous.as_ref().is_some_and(|r| !r.consistent) {
+            return;
+        }
+
+        if self.symbols.update(&self.memory_map) || self.has_pending_breakpoints() {
+            //breakpoints in unloaded modules are gone along with the memory they patched
+            let symbols = &self.symbols;
+            self.breakpoints.retain(|bp| {
+                bp.location.is_none() || symbols.module_at(bp.breakpoint.address()).is_some()
+            });
+
+            self.resolve_symbolic_breakpoints();
+        }
+
+        if self.rendezvous.is_none() && self.stopped {
+            self.set_up_rendezvous();
+        }
+    }
+
+    /// Hooks ld.so's debugger interface so we hear about dlopen and dlclose
+    fn set_up_rendezvous(&mut self) {
+        let Some(rendezvous) = Rendezvous::locate(self) else {
+            //statically linked, or ld.so isn't there yet
+            return;
+        };
+
+        //a user breakpoint there may be disabled or removed any time, ours has to stay
+        let placed = self.breakpoints.iter().any(|bp| {
+            bp.internal
+                && !bp.one_shot
+                && bp.breakpoint.enabled()
+                && bp.breakpoint.address() == rendezvous.breakpoint_address
+        });
+        if !placed {
+            let id = self.add_software_breakpoint(rendezvous.breakpoint_address);
+            self.breakpoint(id).unwrap().internal = true;
+        }
+
+        self.rendezvous = Some(rendezvous);
+    }
+
+    /// The user's label for it, otherwise `module!symbol+0x12` or the region it's in
+    pub fn describe_address(&self, address: u64) -> Option<String> {
+        self.note(address)
+            .and_then(|note| note.label.clone())
+            .or_else(|| self.symbols.symbolize(address))
+            .or_else(|| self.memory_map.describe(address))
+    }
+
+    /// `address` relative to the module it's in, None outside of modules
+    pub fn module_address(&self, address: u64) -> Option<ModuleAddress> {
+        let module = self.symbols.module_at(address)?;
+
+        Some(ModuleAddress {
+            module: module.name().to_owned(),
+            offset: address - module.base,
+        })
+    }
+
+    /// Where a module relative address is in this process, None while its module isn't loaded
+    pub fn resolve_module_address(&self, address: &ModuleAddress) -> Option<u64> {
+        self.symbols
+            .modules()
+            .iter()
+            .find(|module| module.name() == address.module)
+            .map(|module| module.base + address.offset)
+    }
+
+    pub fn note(&self, address: u64) -> Option<&Note> {
+        if self.notes.is_empty() {
+            return None;
+        }
+
+        self.notes.get(&self.module_address(address)?)
+    }
+
+    pub const fn notes(&self) -> &Notes {
+        &self.notes
+    }
+
+    /// Labels, comments and bookmarks, they're kept across restarts by handing them over
+    pub fn notes_mut(&mut self) -> &mut Notes {
+        &mut self.notes
+    }
+
+    /// The process replaced its image, every address we knew about is meaningless now
+    pub fn handle_exec(&mut self) {
+        //the old memory (and the int3s in it) is gone and the kernel dropped the debug registers
+        self.breakpoints.clear();
+        for symbolic in &mut self.symbolic_breakpoints {
+            symbolic.removed.clear();
+        }
+        self.symbols.clear();
+        self.rendezvous = None;
+        self.update_context();
+        self.refresh_memory_map();
+    }
+
+    pub const fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    pub const fn symbols(&self) -> &SymbolTable {
+        &self.symbols
+    }
+
+    pub const fn memory_map(&self) -> &MemoryMap {
+        &self.memory_map
+    }
+
+    pub fn set_demangle_settings(&mut self, settings: DemangleSettings) {
+        self.symbols.demangle = settings;
+    }
+
+    /// Reloads every module if the debug directories changed, so they get a chance at matching
+    pub fn set_debug_info_settings(&mut self, settings: DebugInfoSettings) {
+        if self.symbols.debug_info == settings {
+            return;
+        }
+
+        self.symbols.debug_info = settings;
+        self.symbols.clear();
+
+        //pending breakpoints can only be placed while stopped
+        if self.stopped {
+            self.refresh_memory_map();
+        } else {
+            self.symbols.update(&self.memory_map);
+        }
+    }
+
+    pub fn symbolic_breakpoints(&self) -> &[SymbolicBreakpoint] {
+        &self.symbolic_breakpoints
+    }
+
+    /// Adds a breakpoint on every function matching `pattern`, now and whenever a module gets loaded.
+    /// Returns how many locations it resolved to right away
+    pub fn add_symbolic_breakpoint(&mut self, pattern: &str, regex: bool) -> Result<usize, String> {
+        let symbolic = SymbolicBreakpoint::new(pattern, regex)?;
+
+        if self
+            .symbolic_breakpoints
+            .iter()
+            .any(|other| other.same_pattern(&symbolic))
+        {
+            return Err(format!(
+                "{} already is a breakpoint",
+                symbolic.description()
+            ));
+        }
+
+        let location = symbolic.description();
+        self.symbolic_breakpoints.push(symbolic);
+        self.resolve_symbolic_breakpoints();
+
+        Ok(self
+            .breakpoints
+            .iter()
+            .filter(|bp| bp.location.as_ref() == Some(&location))
+            .count())
+    }
+
+    /// Whether any symbolic breakpoint hasn't resolved to a single location yet
+    pub fn has_pending_breakpoints(&self) -> bool {
+        self.symbolic_breakpoints.iter().any(|symbolic| {
+            let location = symbolic.description();
+            !self
+                .breakpoints
+                .iter()
+                .any(|bp| bp.location.as_ref() == Some(&location))
+        })
+    }
+
+    /// Removes a symbolic breakpoint along with every location it resolved to
+    pub fn remove_symbolic_breakpoint(&mut self, index: usize) {
+        if index >= self.symbolic_breakpoints.len() {
+            return;
+        }
+
+        let location = self.symbolic_breakpoints.remove(index).description();
+        let ids = self
+            .breakpoints
+            .iter()
+            .filter(|bp| bp.location.as_ref() == Some(&location))
+            .map(|bp| bp.id)
+            .collect::<Vec<_>>();
+
+        for id in ids {
+            self.remove_breakpoint(id);
+        }
+    }
+
+    /// Places breakpoints on symbols that appeared since the last time
+    pub fn resolve_symbolic_breakpoints(&mut self) {
+        //memory can't be patched while the process runs, this happens again on the next stop
+        if !self.stopped {
+            return;
+        }
+
+        for symbolic in self.symbolic_breakpoints.clone() {
+            let mut addresses = match symbolic.regex() {
+                Some(regex) => self.symbols.resolve_regex(&regex),
+                None => self.symbols.resolve(&symbolic.pattern),
+            }
+            .into_iter()
+            .filter(|s| s.is_function)
+            .map(|s| s.address)
+            .collect::<Vec<_>>();
+
+            addresses.retain(|address| !symbolic.removed.contains(address));
+            addresses.sort();
+            addresses.dedup();
+
+            for address in addresses {
+                if self.user_breakpoint_at_address(address).is_some() {
+                    continue;
+                }
+
+                let id = self.add_software_breakpoint(address);
+                self.breakpoint(id).unwrap().location = Some(symbolic.description());
+            }
+        }
+    }
+
+    /// Breaks on every address `path:line` compiled to. Returns the line the breakpoint ended up on,
+    /// the next one with code if `line` has none, and how many locations it has
+    pub fn add_source_breakpoint(&mut self, path: &str, line: u32) -> Result<(u32, usize), String> {
+        let file_name = path.rsplit('/').next().unwrap_or(path);
+
+        let (line, addresses) = self
+            .symbols
+            .line_addresses(path, line)
+            .ok_or_else(|| format!("No code at or after {file_name}:{line}"))?;
+
+        let location = format!("{file_name}:{line}");
+        let mut count = 0;
+
+        for address in addresses {
+            if self.user_breakpoint_at_address(address).is_some() {
+                continue;
+            }
+
+            let id = self.add_software_breakpoint(address);
+            self.breakpoint(id).unwrap().location = Some(location.clone());
+            count += 1;
+        }
+
+        Ok((line, count))
+    }
+
+    /// Looks up a register by name, 32 bit names give the lower half of their 64 bit register
+    pub fn register(&self, name: &str) -> Option<u64> {
+        let (field, lower_half) = register_field(name)?;
+        let value = field(&self.context);
+
+        Some(if lower_half {
+            value & 0xffff_ffff
+        } else {
+            value
+        })
+    }
+
+    /// `value` followed as a pointer for a few levels, each hop tagged with the region it's in
+    pub fn telescope(&self, value: u64) -> Telescope {
+        annotation::telescope(&self.memory_map, &self.symbols, self, value)
+    }
+
+    /// Frames of the call stack, innermost first
+    pub fn backtrace(&self) -> Vec<StackFrame> {
+        unwind::backtrace(
+            &self.symbols,
+            FrameRegisters::from_context(&self.context),
+            self,
+        )
+    }
+
+    /// Locals and parameters in scope in the frame `registers` belong to,
+    /// their types are described by the DWARF of the module the frame is in
+    pub fn locals(&self, registers: &FrameRegisters) -> Vec<Variable> {
+        let Some(module) = self.symbols.module_at(registers.location()) else {
+            return Vec::new();
+        };
+        let Some(dwarf) = &module.dwarf else {
+            return Vec::new();
+        };
+
+        let cfa = module
+            .call_frames
+            .as_ref()
+            .and_then(|call_frames| call_frames.cfa(registers))
+            //without call frame info the function has to keep a frame pointer to be unwound at all
+            .or_else(|| registers.bp().map(|rbp| rbp.wrapping_add(16)));
+
+        dwarf.locals(registers, cfa, self)
     }
 
     pub fn set_rip(&mut self, rip: u64) {
@@ -201,6 +1348,61 @@ impl Debugee {
     }
 }
 
+impl variables::Memory for Debugee {
+    //unlike ptrace peeks this reports unmapped memory instead of reading garbage.
+    //unwinding reads code too, so it gets to see what's under our int3s
+    fn read_bytes(&self, address: u64, size: usize) -> Option<Vec<u8>> {
+        let file = std::fs::File::open(format!("/proc/{}/mem", self.pid)).ok()?;
+        let mut data = vec![0; size];
+        file.read_exact_at(&mut data, address).ok()?;
+        self.hide_breakpoints(address as usize, &mut data);
+        Some(data)
+    }
+}
+
+/// Whether `name` is a register `Debugee::register` knows
+pub fn is_register_name(name: &str) -> bool {
+    register_field(name).is_some()
+}
+
+type RegisterField = fn(&libc::user_regs_struct) -> u64;
+
+//the user_regs_struct field behind a register name and whether it's the lower half of it
+fn register_field(name: &str) -> Option<(RegisterField, bool)> {
+    let name = name.to_ascii_lowercase();
+
+    Some(match name.as_str() {
+        "rax" | "eax" => (|ctx| ctx.rax, name.starts_with('e')),
+        "rbx" | "ebx" => (|ctx| ctx.rbx, name.starts_with('e')),
+        "rcx" | "ecx" => (|ctx| ctx.rcx, name.starts_with('e')),
+        "rdx" | "edx" => (|ctx| ctx.rdx, name.starts_with('e')),
+        "rsi" | "esi" => (|ctx| ctx.rsi, name.starts_with('e')),
+        "rdi" | "edi" => (|ctx| ctx.rdi, name.starts_with('e')),
+        "rbp" | "ebp" => (|ctx| ctx.rbp, name.starts_with('e')),
+        "rsp" | "esp" => (|ctx| ctx.rsp, name.starts_with('e')),
+        "r8" | "r8d" => (|ctx| ctx.r8, name.ends_with('d')),
+        "r9" | "r9d" => (|ctx| ctx.r9, name.ends_with('d')),
+        "r10" | "r10d" => (|ctx| ctx.r10, name.ends_with('d')),
+        "r11" | "r11d" => (|ctx| ctx.r11, name.ends_with('d')),
+        "r12" | "r12d" => (|ctx| ctx.r12, name.ends_with('d')),
+        "r13" | "r13d" => (|ctx| ctx.r13, name.ends_with('d')),
+        "r14" | "r14d" => (|ctx| ctx.r14, name.ends_with('d')),
+        "r15" | "r15d" => (|ctx| ctx.r15, name.ends_with('d')),
+        "rip" => (|ctx| ctx.rip, false),
+        "eflags" | "rflags" => (|ctx| ctx.eflags, false),
+        "fs_base" => (|ctx| ctx.fs_base, false),
+        "gs_base" => (|ctx| ctx.gs_base, false),
+        _ => return None,
+    })
+}
+
+fn is_call(instruction: &iced_x86::Instruction) -> bool {
+    matches!(
+        instruction.flow_control(),
+        iced_x86::FlowControl::Call | iced_x86::FlowControl::IndirectCall
+    )
+}
+
 fn waitpid_thread(pid: u32, tx: Sender<i32>) {
     let mut status = 0i32;
     while unsafe { libc::waitpid(pid as i32, &mut status as _, libc::__WALL) != -1 } {
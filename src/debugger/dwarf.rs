@@ -0,0 +1,286 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use object::{Object, ObjectSection};
+
+/// Owns its section data, so parsed DWARF can be kept around for as long as the module is loaded
+pub type Reader = gimli::EndianArcSlice<gimli::RunTimeEndian>;
+
+pub fn endianness(file: &object::File) -> gimli::RunTimeEndian {
+    if file.is_little_endian() {
+        gimli::RunTimeEndian::Little
+    } else {
+        gimli::RunTimeEndian::Big
+    }
+}
+
+/// Copy of the section called `name`, empty if it's missing or compressed
+pub fn section_reader(file: &object::File, name: &str, endian: gimli::RunTimeEndian) -> Reader {
+    let data = file
+        .section_by_name(name)
+        .and_then(|section| section.uncompressed_data().ok())
+        .unwrap_or_default();
+
+    gimli::EndianArcSlice::new(Arc::from(&*data), endian)
+}
+
+/// Debugging information entries and line table of a module
+pub struct DwarfInfo {
+    pub(super) dwarf: gimli::Dwarf<Reader>,
+    pub(super) units: Vec<gimli::Unit<Reader>>,
+    /// Difference between the addresses in the DWARF and where the module got loaded
+    pub(super) load_bias: u64,
+    pub lines: LineTable,
+}
+
+impl DwarfInfo {
+    pub fn load(file: &object::File, load_bias: u64) -> Result<Self, Box<dyn Error>> {
+        let endian = endianness(file);
+        let dwarf = gimli::Dwarf::load(|id| {
+            Ok::<_, gimli::Error>(section_reader(file, id.name(), endian))
+        })?;
+
+        let mut units = Vec::new();
+        let mut headers = dwarf.units();
+        while let Some(header) = headers.next()? {
+            units.push(dwarf.unit(header)?);
+        }
+
+        let lines = LineTable::load(&dwarf, &units, load_bias)?;
+
+        Ok(Self {
+            dwarf,
+            units,
+            load_bias,
+            lines,
+        })
+    }
+}
+
+/// Where an address comes from in the source
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SourceLocation<'a> {
+    pub path: &'a str,
+    pub line: u32,
+}
+
+#[derive(Clone, Copy)]
+struct LineRow {
+    address: u64,
+    /// Index into `LineTable::files`
+    file: usize,
+    /// 0 for compiler generated code that doesn't belong to any line
+    line: u32,
+    /// Recommended breakpoint location, the start of a statement
+    is_stmt: bool,
+    /// First address past a sequence, doesn't describe any code itself
+    end_sequence: bool,
+}
+
+impl LineRow {
+    const fn same_line(&self, other: &Self) -> bool {
+        self.file == other.file && self.line == other.line
+    }
+}
+
+/// Every `.debug_line` row of a module, relocated to where it got loaded
+pub struct LineTable {
+    files: Vec<String>,
+    /// Sorted by address, a sequence ending where another starts comes first
+    rows: Vec<LineRow>,
+}
+
+impl LineTable {
+    fn load(
+        dwarf: &gimli::Dwarf<Reader>,
+        units: &[gimli::Unit<Reader>],
+        load_bias: u64,
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut files = Vec::new();
+        let mut file_indices = HashMap::new();
+        let mut rows = Vec::new();
+
+        for unit in units {
+            let Some(program) = unit.line_program.clone() else {
+                continue;
+            };
+
+            let mut sequence = Vec::new();
+            //file entries are per unit, resolving their path on every row is way too slow
+            let mut unit_files = HashMap::new();
+            let mut program_rows = program.rows();
+
+            while let Some((header, row)) = program_rows.next_row()? {
+                let file = *unit_files.entry(row.file_index()).or_insert_with(|| {
+                    let path = row
+                        .file(header)
+                        .and_then(|entry| file_path(dwarf, unit, header, entry))
+                        .unwrap_or_else(|| String::from("<unknown>"));
+
+                    *file_indices.entry(path.clone()).or_insert_with(|| {
+                        files.push(path);
+                        files.len() - 1
+                    })
+                });
+
+                sequence.push(LineRow {
+                    address: row.address(),
+                    file,
+                    line: row.line().map_or(0, |line| line.get() as u32),
+                    is_stmt: row.is_stmt(),
+                    end_sequence: row.end_sequence(),
+                });
+
+                if row.end_sequence() {
+                    //functions dropped by the linker keep their rows, relocated to 0 (or -1 with lld)
+                    let start = sequence[0].address;
+                    if start != 0 && start < u64::MAX - 1 {
+                        rows.extend(sequence.iter().map(|row| LineRow {
+                            address: row.address.wrapping_add(load_bias),
+                            ..*row
+                        }));
+                    }
+
+                    sequence.clear();
+                }
+            }
+        }
+
+        rows.sort_by_key(|row| (row.address, !row.end_sequence));
+
+        Ok(Self { files, rows })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    pub fn files(&self) -> &[String] {
+        &self.files
+    }
+
+    fn row_index(&self, address: u64) -> Option<usize> {
+        let index = self
+            .rows
+            .partition_point(|row| row.address <= address)
+            .checked_sub(1)?;
+
+        (!self.rows[index].end_sequence).then_some(index)
+    }
+
+    pub fn location(&self, address: u64) -> Option<SourceLocation<'_>> {
+        let row = &self.rows[self.row_index(address)?];
+
+        Some(SourceLocation {
+            path: self.files.get(row.file)?,
+            line: row.line,
+        })
+        .filter(|_| row.line != 0)
+    }
+
+    /// Whether a statement starts exactly at `address`, which is where stepping stops
+    pub fn is_statement_start(&self, address: u64) -> bool {
+        self.row_index(address).is_some_and(|index| {
+            let row = &self.rows[index];
+            row.address == address && row.is_stmt && row.line != 0
+        })
+    }
+
+    /// Contiguous addresses around `address` belonging to the same line
+    pub fn line_range(&self, address: u64) -> Option<Range<u64>> {
+        let index = self.row_index(address)?;
+        let row = &self.rows[index];
+
+        let mut start = index;
+        while start > 0 {
+            let previous = &self.rows[start - 1];
+            if previous.end_sequence || !previous.same_line(row) {
+                break;
+            }
+            start -= 1;
+        }
+
+        let end = self.rows[index + 1..]
+            .iter()
+            .find(|next| next.end_sequence || !next.same_line(row))?;
+
+        Some(self.rows[start].address..end.address)
+    }
+
+    /// Lines of `path` that have code
+    pub fn lines(&self, path: &str) -> impl Iterator<Item = u32> + '_ {
+        let file = self.files.iter().position(|f| f == path);
+
+        self.rows
+            .iter()
+            .filter(move |row| Some(row.file) == file && !row.end_sequence && row.line != 0)
+            .map(|row| row.line)
+    }
+
+    /// Start of every block of code generated for `line`, or for the next line that has code
+    /// when it has none (comments, blank lines), along with which line that was
+    pub fn addresses(&self, path: &str, line: u32) -> Option<(u32, Vec<u64>)> {
+        let file = self.files.iter().position(|f| f == path)?;
+
+        let line = self.lines(path).filter(|&l| l >= line).min()?;
+
+        let mut addresses = self
+            .rows
+            .iter()
+            .enumerate()
+            .filter(|(_, row)| {
+                row.file == file && row.line == line && row.is_stmt && !row.end_sequence
+            })
+            .filter(|&(index, row)| {
+                index == 0 || {
+                    let previous = &self.rows[index - 1];
+                    previous.end_sequence || !previous.same_line(row)
+                }
+            })
+            .map(|(_, row)| row.address)
+            .collect::<Vec<_>>();
+
+        addresses.dedup();
+        Some((line, addresses))
+    }
+}
+
+/// Full path of a line table file entry, relative directories are relative to the compilation directory
+fn file_path(
+    dwarf: &gimli::Dwarf<Reader>,
+    unit: &gimli::Unit<Reader>,
+    header: &gimli::LineProgramHeader<Reader>,
+    entry: &gimli::FileEntry<Reader>,
+) -> Option<String> {
+    use gimli::Reader as _;
+
+    let mut path = PathBuf::new();
+
+    if let Some(comp_dir) = &unit.comp_dir {
+        path.push(&*comp_dir.to_string_lossy().ok()?);
+    }
+
+    //pushing an absolute path replaces what's there
+    if let Some(directory) = entry.directory(header) {
+        path.push(
+            &*dwarf
+                .attr_string(unit, directory)
+                .ok()?
+                .to_string_lossy()
+                .ok()?,
+        );
+    }
+
+    path.push(
+        &*dwarf
+            .attr_string(unit, entry.path_name())
+            .ok()?
+            .to_string_lossy()
+            .ok()?,
+    );
+
+    Some(path.to_string_lossy().into_owned())
+}
@@ -0,0 +1,439 @@
+use super::{is_register_name, Debugee};
+
+//small expression language used for breakpoint conditions, e.g. `rax == 0x10 && [rsp+8] != 0`,
+//and for going to addresses, e.g. `libc.so.6!puts+4` or `[rsp+0x20]`
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(u64),
+    Identifier(String),
+    Operator(&'static str),
+    OpenParen,
+    CloseParen,
+    OpenBracket,
+    CloseBracket,
+}
+
+//longest operators first so `<=` isn't read as `<` `=`
+const OPERATORS: &[&str] = &[
+    "==", "!=", "<=", ">=", "&&", "||", "<<", ">>", "+", "-", "*", "/", "%", "&", "|", "^", "<",
+    ">", "!", "~",
+];
+
+const SIZE_KEYWORDS: [(&str, usize); 4] = [("byte", 1), ("word", 2), ("dword", 4), ("qword", 8)];
+
+/// `modules` are the names of the loaded modules, they can contain `-` and `+`
+/// (`ld-linux-x86-64.so.2`, `libstdc++.so.6`) and are read as a whole where an identifier starts
+fn tokenize(input: &str, modules: &[&str]) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+
+    while let Some(&(i, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        match c {
+            '(' => tokens.push(Token::OpenParen),
+            ')' => tokens.push(Token::CloseParen),
+            '[' => tokens.push(Token::OpenBracket),
+            ']' => tokens.push(Token::CloseBracket),
+            _ if c.is_ascii_digit() => {
+                let mut end = i;
+                while let Some(&(j, c)) = chars.peek() {
+                    if !c.is_ascii_alphanumeric() {
+                        break;
+                    }
+                    end = j + c.len_utf8();
+                    chars.next();
+                }
+
+                tokens.push(Token::Number(parse_number(&input[i..end])?));
+                continue;
+            }
+            _ if c.is_alphabetic() || c == '_' => {
+                let mut end = i;
+                if let Some(module) = module_at(&input[i..], modules) {
+                    end += module.len();
+                    while chars.next_if(|&(j, _)| j < end).is_some() {}
+                }
+
+                while let Some(&(j, c)) = chars.peek() {
+                    //symbol and module names, `libc.so.6!puts@plt`, `core::ptr::drop_in_place`
+                    let rest = &input[j..];
+                    let continues = match c {
+                        '_' | '.' | '@' | '$' => true,
+                        '!' => !rest.starts_with("!="),
+                        ':' => rest.starts_with("::"),
+                        _ => c.is_alphanumeric(),
+                    };
+                    if !continues {
+                        break;
+                    }
+
+                    let len = if c == ':' { 2 } else { c.len_utf8() };
+                    end = j + len;
+                    for _ in 0..len {
+                        chars.next();
+                    }
+                }
+
+                tokens.push(Token::Identifier(input[i..end].to_owned()));
+                continue;
+            }
+            _ => {
+                let operator = OPERATORS
+                    .iter()
+                    .find(|op| input[i..].starts_with(**op))
+                    .ok_or_else(|| format!("Unexpected character '{c}'"))?;
+
+                tokens.push(Token::Operator(operator));
+
+                for _ in 0..operator.len() {
+                    chars.next();
+                }
+                continue;
+            }
+        }
+
+        chars.next();
+    }
+
+    Ok(tokens)
+}
+
+//longest module name `input` starts with, as long as it isn't just the start of a longer identifier
+fn module_at<'a>(input: &str, modules: &[&'a str]) -> Option<&'a str> {
+    modules
+        .iter()
+        .filter(|module| {
+            input.strip_prefix(**module).is_some_and(|rest| {
+                !rest.starts_with(|c: char| c.is_alphanumeric() || "_.@$".contains(c))
+            })
+        })
+        .max_by_key(|module| module.len())
+        .copied()
+}
+
+pub fn parse_number(text: &str) -> Result<u64, String> {
+    let result = if let Some(hex) = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        u64::from_str_radix(hex, 16)
+    } else {
+        text.parse::<u64>()
+    };
+
+    result.map_err(|_| format!("Invalid number '{text}'"))
+}
+
+const fn precedence(operator: &str) -> Option<u8> {
+    Some(match operator.as_bytes() {
+        b"||" => 1,
+        b"&&" => 2,
+        b"|" => 3,
+        b"^" => 4,
+        b"&" => 5,
+        b"==" | b"!=" => 6,
+        b"<" | b">" | b"<=" | b">=" => 7,
+        b"<<" | b">>" => 8,
+        b"+" | b"-" => 9,
+        b"*" | b"/" | b"%" => 10,
+        _ => return None,
+    })
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    position: usize,
+    debugee: &'a Debugee,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), String> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => Err(format!("Expected {expected:?}, found {token:?}")),
+            None => Err(format!("Expected {expected:?}, found end of expression")),
+        }
+    }
+
+    fn expression(&mut self, min_precedence: u8) -> Result<u64, String> {
+        let mut lhs = self.unary()?;
+
+        while let Some(Token::Operator(operator)) = self.peek() {
+            let operator = *operator;
+            let Some(prec) = precedence(operator) else {
+                break;
+            };
+
+            if prec < min_precedence {
+                break;
+            }
+
+            self.next();
+            let rhs = self.expression(prec + 1)?;
+            lhs = apply(operator, lhs, rhs)?;
+        }
+
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<u64, String> {
+        match self.next() {
+            Some(Token::Operator("-")) => Ok(self.unary()?.wrapping_neg()),
+            Some(Token::Operator("!")) => Ok((self.unary()? == 0) as u64),
+            Some(Token::Operator("~")) => Ok(!self.unary()?),
+            Some(Token::Number(number)) => Ok(number),
+            Some(Token::Identifier(name)) => self.identifier(&name),
+            Some(Token::OpenParen) => {
+                let value = self.expression(0)?;
+                self.expect(Token::CloseParen)?;
+                Ok(value)
+            }
+            Some(Token::OpenBracket) => {
+                let address = self.expression(0)?;
+                self.expect(Token::CloseBracket)?;
+                self.dereference(address, 8)
+            }
+            Some(token) => Err(format!("Unexpected {token:?}")),
+            None => Err(String::from("Unexpected end of expression")),
+        }
+    }
+
+    fn identifier(&mut self, name: &str) -> Result<u64, String> {
+        //size prefixed dereference, `dword [rsp]`
+        let size = SIZE_KEYWORDS
+            .iter()
+            .find(|(keyword, _)| *keyword == name)
+            .map(|(_, size)| *size);
+
+        if let Some(size) = size
+            && self.peek() == Some(&Token::OpenBracket)
+        {
+            self.next();
+            let address = self.expression(0)?;
+            self.expect(Token::CloseBracket)?;
+            return self.dereference(address, size);
+        }
+
+        if let Some(value) = self.debugee.register(name) {
+            return Ok(value);
+        }
+
+        if let Some(address) = self
+            .debugee
+            .notes()
+            .find_label(name)
+            .and_then(|label| self.debugee.resolve_module_address(label))
+        {
+            return Ok(address);
+        }
+
+        let symbols = self.debugee.symbols();
+        if let Some(symbol) = symbols.resolve(name).first() {
+            return Ok(symbol.address);
+        }
+
+        //a module on its own is its base, `libc.so.6+0x1234`
+        symbols
+            .modules()
+            .iter()
+            .find(|module| module.matches(name))
+            .map(|module| module.base)
+            .ok_or_else(|| format!("Unknown identifier '{name}'"))
+    }
+
+    /// Unmapped memory is an error rather than whatever ptrace makes of it
+    fn dereference(&self, address: u64, size: usize) -> Result<u64, String> {
+        if self.debugee.memory_map().region_at(address).is_none() {
+            return Err(format!("{address:#x} isn't mapped"));
+        }
+
+        let mut bytes = self.debugee.read_memory(address as usize, size);
+        bytes.resize(8, 0);
+        Ok(u64::from_le_bytes(bytes.try_into().unwrap()))
+    }
+}
+
+fn apply(operator: &str, lhs: u64, rhs: u64) -> Result<u64, String> {
+    Ok(match operator {
+        "+" => lhs.wrapping_add(rhs),
+        "-" => lhs.wrapping_sub(rhs),
+        "*" => lhs.wrapping_mul(rhs),
+        "/" => lhs.checked_div(rhs).ok_or("Division by zero")?,
+        "%" => lhs.checked_rem(rhs).ok_or("Division by zero")?,
+        "&" => lhs & rhs,
+        "|" => lhs | rhs,
+        "^" => lhs ^ rhs,
+        "<<" => lhs.wrapping_shl(rhs as u32),
+        ">>" => lhs.wrapping_shr(rhs as u32),
+        "==" => (lhs == rhs) as u64,
+        "!=" => (lhs != rhs) as u64,
+        "<" => (lhs < rhs) as u64,
+        ">" => (lhs > rhs) as u64,
+        "<=" => (lhs <= rhs) as u64,
+        ">=" => (lhs >= rhs) as u64,
+        "&&" => (lhs != 0 && rhs != 0) as u64,
+        "||" => (lhs != 0 || rhs != 0) as u64,
+        _ => return Err(format!("Unknown operator '{operator}'")),
+    })
+}
+
+/// Names resolved before labels, a label named like that couldn't be referred to
+pub fn is_reserved(name: &str) -> bool {
+    SIZE_KEYWORDS.iter().any(|(keyword, _)| *keyword == name) || is_register_name(name)
+}
+
+/// Evaluates `expression` against the current register context, memory and symbols of `debugee`.
+/// Identifiers are registers first, then labels, symbols (optionally `module!symbol`) and module bases
+pub fn evaluate(expression: &str, debugee: &Debugee) -> Result<u64, String> {
+    let modules = debugee
+        .symbols()
+        .modules()
+        .iter()
+        .map(|module| module.name())
+        .collect::<Vec<_>>();
+
+    let mut parser = Parser {
+        tokens: tokenize(expression, &modules)?,
+        position: 0,
+        debugee,
+    };
+
+    let value = parser.expression(0)?;
+
+    if let Some(token) = parser.peek() {
+        return Err(format!("Unexpected {token:?}"));
+    }
+
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn identifier(name: &str) -> Token {
+        Token::Identifier(name.to_owned())
+    }
+
+    #[test]
+    fn module_qualified_symbol() {
+        assert_eq!(
+            tokenize("libc.so.6!puts+4", &[]).unwrap(),
+            [
+                identifier("libc.so.6!puts"),
+                Token::Operator("+"),
+                Token::Number(4)
+            ]
+        );
+        assert_eq!(
+            tokenize("libc.so.6!puts@plt", &[]).unwrap(),
+            [identifier("libc.so.6!puts@plt")]
+        );
+        assert_eq!(
+            tokenize("core::ptr::drop_in_place+0x10", &[]).unwrap(),
+            [
+                identifier("core::ptr::drop_in_place"),
+                Token::Operator("+"),
+                Token::Number(0x10)
+            ]
+        );
+    }
+
+    #[test]
+    fn not_equal_ends_an_identifier() {
+        assert_eq!(
+            tokenize("rax!=0x10", &[]).unwrap(),
+            [
+                identifier("rax"),
+                Token::Operator("!="),
+                Token::Number(0x10)
+            ]
+        );
+    }
+
+    #[test]
+    fn labels() {
+        assert_eq!(
+            tokenize("[after_prologue.1 + 8]", &[]).unwrap(),
+            [
+                Token::OpenBracket,
+                identifier("after_prologue.1"),
+                Token::Operator("+"),
+                Token::Number(8),
+                Token::CloseBracket
+            ]
+        );
+        assert_eq!(
+            tokenize("_loop$2@x-rbx", &[]).unwrap(),
+            [
+                identifier("_loop$2@x"),
+                Token::Operator("-"),
+                identifier("rbx")
+            ]
+        );
+    }
+
+    #[test]
+    fn operators_longest_first() {
+        assert_eq!(
+            tokenize("1<<2<=3", &[]).unwrap(),
+            [
+                Token::Number(1),
+                Token::Operator("<<"),
+                Token::Number(2),
+                Token::Operator("<="),
+                Token::Number(3)
+            ]
+        );
+        assert!(tokenize("rax # 1", &[]).is_err());
+    }
+
+    #[test]
+    fn module_names_with_dashes_and_pluses() {
+        let modules = ["ld-linux-x86-64.so.2", "libstdc++.so.6", "libc.so.6"];
+
+        assert_eq!(
+            tokenize("ld-linux-x86-64.so.2!_dl_debug_state", &modules).unwrap(),
+            [identifier("ld-linux-x86-64.so.2!_dl_debug_state")]
+        );
+        assert_eq!(
+            tokenize("libstdc++.so.6+0x10", &modules).unwrap(),
+            [
+                identifier("libstdc++.so.6"),
+                Token::Operator("+"),
+                Token::Number(0x10)
+            ]
+        );
+        //without knowing the module it's arithmetic
+        assert_eq!(
+            tokenize("ld-linux", &[]).unwrap(),
+            [identifier("ld"), Token::Operator("-"), identifier("linux")]
+        );
+        //a module name only counts as a whole identifier
+        assert_eq!(
+            tokenize("libc.so.6x", &modules).unwrap(),
+            [identifier("libc.so.6x")]
+        );
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(parse_number("0x1F").unwrap(), 0x1f);
+        assert_eq!(parse_number("0X10").unwrap(), 0x10);
+        assert_eq!(parse_number("42").unwrap(), 42);
+        assert!(parse_number("0xg").is_err());
+        assert!(tokenize("12ab", &[]).is_err());
+    }
+}
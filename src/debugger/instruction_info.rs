@@ -0,0 +1,473 @@
+use iced_x86::{
+    Code, ConditionCode, FlowControl, Instruction, Mnemonic, OpAccess, OpKind, Register, RflagsBits,
+};
+
+use super::variables::Memory;
+use super::Debugee;
+
+//eflags bits
+const CF: u64 = 1 << 0;
+const PF: u64 = 1 << 2;
+const ZF: u64 = 1 << 6;
+const SF: u64 = 1 << 7;
+const OF: u64 = 1 << 11;
+//memory operands bigger than this are shown as their address only
+const MAX_VALUE_SIZE: usize = 8;
+const FLAG_NAMES: [(u32, &str); 14] = [
+    (RflagsBits::CF, "CF"),
+    (RflagsBits::PF, "PF"),
+    (RflagsBits::AF, "AF"),
+    (RflagsBits::ZF, "ZF"),
+    (RflagsBits::SF, "SF"),
+    (RflagsBits::IF, "IF"),
+    (RflagsBits::DF, "DF"),
+    (RflagsBits::OF, "OF"),
+    (RflagsBits::AC, "AC"),
+    (RflagsBits::UIF, "UIF"),
+    (RflagsBits::C0, "C0"),
+    (RflagsBits::C1, "C1"),
+    (RflagsBits::C2, "C2"),
+    (RflagsBits::C3, "C3"),
+];
+
+/// Value of a general purpose (or segment base) register, sub registers included.
+/// None for the ones ptrace's user_regs_struct doesn't have, like vector registers
+pub fn register_value(context: &libc::user_regs_struct, register: Register) -> Option<u64> {
+    let full = match register.full_register() {
+        Register::RAX => context.rax,
+        Register::RBX => context.rbx,
+        Register::RCX => context.rcx,
+        Register::RDX => context.rdx,
+        Register::RSI => context.rsi,
+        Register::RDI => context.rdi,
+        Register::RBP => context.rbp,
+        Register::RSP => context.rsp,
+        Register::R8 => context.r8,
+        Register::R9 => context.r9,
+        Register::R10 => context.r10,
+        Register::R11 => context.r11,
+        Register::R12 => context.r12,
+        Register::R13 => context.r13,
+        Register::R14 => context.r14,
+        Register::R15 => context.r15,
+        Register::RIP => context.rip,
+        //flat in long mode, only fs and gs have a base
+        Register::ES | Register::CS | Register::SS | Register::DS => 0,
+        Register::FS => context.fs_base,
+        Register::GS => context.gs_base,
+        _ => return None,
+    };
+
+    if register.is_segment_register() {
+        return Some(full);
+    }
+
+    Some(match register {
+        Register::AH | Register::BH | Register::CH | Register::DH => (full >> 8) & 0xff,
+        _ => match register.size() {
+            1 => full & 0xff,
+            2 => full & 0xffff,
+            4 => full & 0xffff_ffff,
+            _ => full,
+        },
+    })
+}
+
+/// Whether the conditional branch (jcc, jrcxz, loop) would be taken with the registers in `context`.
+/// None for anything else
+pub fn branch_taken(instruction: &Instruction, context: &libc::user_regs_struct) -> Option<bool> {
+    if instruction.flow_control() != FlowControl::ConditionalBranch {
+        return None;
+    }
+
+    let flags = context.eflags;
+    let set = |flag: u64| flags & flag != 0;
+
+    let condition = match instruction.condition_code() {
+        ConditionCode::None => true,
+        ConditionCode::o => set(OF),
+        ConditionCode::no => !set(OF),
+        ConditionCode::b => set(CF),
+        ConditionCode::ae => !set(CF),
+        ConditionCode::e => set(ZF),
+        ConditionCode::ne => !set(ZF),
+        ConditionCode::be => set(CF) || set(ZF),
+        ConditionCode::a => !set(CF) && !set(ZF),
+        ConditionCode::s => set(SF),
+        ConditionCode::ns => !set(SF),
+        ConditionCode::p => set(PF),
+        ConditionCode::np => !set(PF),
+        ConditionCode::l => set(SF) != set(OF),
+        ConditionCode::ge => set(SF) == set(OF),
+        ConditionCode::le => set(ZF) || set(SF) != set(OF),
+        ConditionCode::g => !set(ZF) && set(SF) == set(OF),
+    };
+
+    //the counter the instruction looks at is as wide as its address size, which a 0x67 prefix
+    //changes without changing the mnemonic of loop
+    let mask = match instruction.code() {
+        Code::Jcxz_rel8_16
+        | Code::Jcxz_rel8_32
+        | Code::Loop_rel8_16_CX
+        | Code::Loop_rel8_32_CX
+        | Code::Loope_rel8_16_CX
+        | Code::Loope_rel8_32_CX
+        | Code::Loopne_rel8_16_CX
+        | Code::Loopne_rel8_32_CX => 0xffff,
+        Code::Jecxz_rel8_16
+        | Code::Jecxz_rel8_32
+        | Code::Jecxz_rel8_64
+        | Code::Loop_rel8_16_ECX
+        | Code::Loop_rel8_32_ECX
+        | Code::Loop_rel8_64_ECX
+        | Code::Loope_rel8_16_ECX
+        | Code::Loope_rel8_32_ECX
+        | Code::Loope_rel8_64_ECX
+        | Code::Loopne_rel8_16_ECX
+        | Code::Loopne_rel8_32_ECX
+        | Code::Loopne_rel8_64_ECX => 0xffff_ffff,
+        _ => u64::MAX,
+    };
+    let counter = context.rcx & mask;
+
+    Some(match instruction.mnemonic() {
+        Mnemonic::Jcxz | Mnemonic::Jecxz | Mnemonic::Jrcxz => counter == 0,
+        //decrements first
+        Mnemonic::Loop | Mnemonic::Loope | Mnemonic::Loopne => counter != 1 && condition,
+        _ => condition,
+    })
+}
+
+/// Current values of what the instruction reads, `["[rbp-0x14]=0x2a", "eax=0x1"]`,
+/// `??` for memory that isn't mapped.
+/// Only meaningful for the instruction at rip, the registers are the current ones
+pub fn operand_values(debugee: &Debugee, instruction: &Instruction) -> Vec<String> {
+    let context = debugee.context();
+    let mut factory = iced_x86::InstructionInfoFactory::new();
+    let info = factory.info(instruction);
+
+    let mut values = Vec::new();
+    for operand in 0..instruction.op_count() {
+        let access = info.op_access(operand);
+
+        match instruction.op_kind(operand) {
+            OpKind::Register => {
+                let register = instruction.op_register(operand);
+                if access_kind(access).0
+                    && !register.is_ip()
+                    && let Some(value) = register_value(context, register)
+                {
+                    values.push(format!("{}={value:#x}", register_name(register)));
+                }
+            }
+            OpKind::Memory => {
+                let Some(address) = instruction.virtual_address(operand, 0, |register, _, _| {
+                    if register.is_ip() {
+                        Some(instruction.next_ip())
+                    } else {
+                        register_value(context, register)
+                    }
+                }) else {
+                    continue;
+                };
+
+                let text = memory_operand(debugee, instruction, address);
+                let size = instruction.memory_size().size();
+
+                //lea and the like only compute the address
+                if access == OpAccess::NoMemAccess || size == 0 || size > MAX_VALUE_SIZE {
+                    values.push(format!("{text}={address:#x}"));
+                    continue;
+                }
+
+                match debugee.read_bytes(address, size) {
+                    Some(mut bytes) => {
+                        bytes.resize(8, 0);
+                        let value = u64::from_le_bytes(bytes.try_into().unwrap());
+                        values.push(format!("{text}={value:#x}"));
+                    }
+                    None => values.push(format!("{text}=??")),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    values
+}
+
+/// `[rbp-0x14]`, `[rax+rcx*8+0x10]`, or the symbol a rip relative operand points to
+fn memory_operand(debugee: &Debugee, instruction: &Instruction, address: u64) -> String {
+    if instruction.is_ip_rel_memory_operand() {
+        return format!(
+            "[{}]",
+            debugee
+                .describe_address(address)
+                .unwrap_or_else(|| format!("{address:#x}"))
+        );
+    }
+
+    address_expression(
+        instruction.memory_segment(),
+        instruction.memory_base(),
+        instruction.memory_index(),
+        instruction.memory_index_scale(),
+        instruction.memory_displacement64(),
+    )
+}
+
+/// `[fs:rax+rcx*8-0x10]`, segments only when they have a base
+fn address_expression(
+    segment: Register,
+    base: Register,
+    index: Register,
+    scale: u32,
+    displacement: u64,
+) -> String {
+    let mut text = String::from("[");
+    if segment == Register::FS || segment == Register::GS {
+        text.push_str(&register_name(segment));
+        text.push(':');
+    }
+
+    if base != Register::None {
+        text.push_str(&register_name(base));
+    }
+
+    if index != Register::None {
+        if base != Register::None {
+            text.push('+');
+        }
+        text.push_str(&register_name(index));
+        if scale != 1 {
+            text.push_str(&format!("*{scale}"));
+        }
+    }
+
+    let displacement = displacement as i64;
+    let registers = base != Register::None || index != Register::None;
+    if !registers {
+        text.push_str(&format!("{displacement:#x}"));
+    } else if displacement > 0 {
+        text.push_str(&format!("+{displacement:#x}"));
+    } else if displacement < 0 {
+        text.push_str(&format!("-{:#x}", displacement.unsigned_abs()));
+    }
+
+    text.push(']');
+    text
+}
+
+/// What iced knows about the instruction as (what, description) rows: encoding, required CPU
+/// features, operand sizes and the registers, memory and flags it touches
+pub fn details(instruction: &Instruction) -> Vec<(&'static str, String)> {
+    let mut factory = iced_x86::InstructionInfoFactory::new();
+    let info = factory.info(instruction);
+    let op_code = instruction.op_code();
+
+    let operands = (0..instruction.op_count())
+        .map(|operand| match instruction.op_kind(operand) {
+            OpKind::Register => {
+                let register = instruction.op_register(operand);
+                format!("{} ({} bits)", register_name(register), register.size() * 8)
+            }
+            OpKind::Memory => format!(
+                "{:?} ({} bytes)",
+                instruction.memory_size(),
+                instruction.memory_size().size()
+            ),
+            kind => format!("{kind:?}"),
+        })
+        .collect::<Vec<_>>();
+
+    let mut reads = Vec::new();
+    let mut writes = Vec::new();
+    for used in info.used_registers() {
+        let name = register_name(used.register());
+        let (read, write) = access_kind(used.access());
+        if read {
+            reads.push(name.clone());
+        }
+        if write {
+            writes.push(name);
+        }
+    }
+
+    for used in info.used_memory() {
+        let text = format!(
+            "{:?} {}",
+            used.memory_size(),
+            address_expression(
+                used.segment(),
+                used.base(),
+                used.index(),
+                used.scale(),
+                used.displacement(),
+            )
+        );
+        let (read, write) = access_kind(used.access());
+        if read {
+            reads.push(text.clone());
+        }
+        if write {
+            writes.push(text);
+        }
+    }
+
+    let cpuid = instruction
+        .cpuid_features()
+        .iter()
+        .map(|feature| format!("{feature:?}"))
+        .collect::<Vec<_>>();
+
+    let list = |items: Vec<String>| {
+        if items.is_empty() {
+            String::from("-")
+        } else {
+            items.join(", ")
+        }
+    };
+
+    vec![
+        ("Instruction", op_code.instruction_string().to_owned()),
+        ("Opcode", op_code.op_code_string().to_owned()),
+        ("Encoding", format!("{:?}", instruction.encoding())),
+        ("CPUID", list(cpuid)),
+        ("Operands", list(operands)),
+        ("Reads", list(reads)),
+        ("Writes", list(writes)),
+        ("Flags read", flag_names(instruction.rflags_read())),
+        ("Flags written", flag_names(instruction.rflags_written())),
+        ("Flags cleared", flag_names(instruction.rflags_cleared())),
+        ("Flags set", flag_names(instruction.rflags_set())),
+        (
+            "Flags undefined",
+            flag_names(instruction.rflags_undefined()),
+        ),
+        ("Flags modified", flag_names(instruction.rflags_modified())),
+    ]
+}
+
+/// Whether an access (maybe) reads and (maybe) writes
+const fn access_kind(access: OpAccess) -> (bool, bool) {
+    match access {
+        OpAccess::Read | OpAccess::CondRead => (true, false),
+        OpAccess::Write | OpAccess::CondWrite => (false, true),
+        OpAccess::ReadWrite | OpAccess::ReadCondWrite => (true, true),
+        _ => (false, false),
+    }
+}
+
+fn flag_names(bits: u32) -> String {
+    let names = FLAG_NAMES
+        .iter()
+        .filter(|(bit, _)| bits & bit != 0)
+        .map(|(_, name)| *name)
+        .collect::<Vec<_>>();
+
+    if names.is_empty() {
+        String::from("-")
+    } else {
+        names.join(" ")
+    }
+}
+
+fn register_name(register: Register) -> String {
+    format!("{register:?}").to_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(bytes: &[u8]) -> Instruction {
+        iced_x86::Decoder::with_ip(64, bytes, 0x1000, iced_x86::DecoderOptions::NONE).decode()
+    }
+
+    fn context(eflags: u64, rcx: u64) -> libc::user_regs_struct {
+        let mut context: libc::user_regs_struct = unsafe { std::mem::zeroed() };
+        context.eflags = eflags;
+        context.rcx = rcx;
+        context
+    }
+
+    #[test]
+    fn condition_codes() {
+        //jcc rel8 opcodes are 0x70 + condition
+        let table: [(u8, u64, bool); 24] = [
+            (0x70, OF, true),       //jo
+            (0x70, 0, false),       //jo
+            (0x71, 0, true),        //jno
+            (0x72, CF, true),       //jb
+            (0x73, CF, false),      //jae
+            (0x74, ZF, true),       //je
+            (0x75, ZF, false),      //jne
+            (0x76, CF, true),       //jbe
+            (0x76, ZF, true),       //jbe
+            (0x76, 0, false),       //jbe
+            (0x77, 0, true),        //ja
+            (0x77, CF, false),      //ja
+            (0x78, SF, true),       //js
+            (0x79, SF, false),      //jns
+            (0x7a, PF, true),       //jp
+            (0x7b, PF, false),      //jnp
+            (0x7c, SF, true),       //jl
+            (0x7c, SF | OF, false), //jl
+            (0x7d, SF | OF, true),  //jge
+            (0x7d, OF, false),      //jge
+            (0x7e, ZF, true),       //jle
+            (0x7e, SF, true),       //jle
+            (0x7f, 0, true),        //jg
+            (0x7f, ZF, false),      //jg
+        ];
+
+        for (opcode, eflags, taken) in table {
+            let instruction = decode(&[opcode, 0x10]);
+            assert_eq!(
+                branch_taken(&instruction, &context(eflags, 0)),
+                Some(taken),
+                "{instruction} with eflags {eflags:#x}"
+            );
+        }
+    }
+
+    #[test]
+    fn counter_branches() {
+        let table: [(&[u8], u64, u64, bool); 12] = [
+            //jrcxz
+            (&[0xe3, 0x10], 0, 0, true),
+            (&[0xe3, 0x10], 0, 1 << 32, false),
+            //jecxz looks at the low 32 bits
+            (&[0x67, 0xe3, 0x10], 0, 1 << 32, true),
+            //loop decrements rcx first
+            (&[0xe2, 0x10], 0, 2, true),
+            (&[0xe2, 0x10], 0, 1, false),
+            (&[0xe2, 0x10], 0, 0, true),
+            (&[0xe2, 0x10], 0, (1 << 32) + 1, true),
+            //addr32 loop decrements ecx
+            (&[0x67, 0xe2, 0x10], 0, (1 << 32) + 1, false),
+            //loope and loopne also need ZF
+            (&[0xe1, 0x10], ZF, 2, true),
+            (&[0xe1, 0x10], 0, 2, false),
+            (&[0xe0, 0x10], 0, 2, true),
+            (&[0x67, 0xe0, 0x10], 0, (1 << 32) + 1, false),
+        ];
+
+        for (bytes, eflags, rcx, taken) in table {
+            let instruction = decode(bytes);
+            assert_eq!(
+                branch_taken(&instruction, &context(eflags, rcx)),
+                Some(taken),
+                "{instruction} with rcx {rcx:#x}"
+            );
+        }
+    }
+
+    #[test]
+    fn not_conditional() {
+        //jmp, call, nop
+        for bytes in [&[0xeb, 0x10][..], &[0xe8, 0, 0, 0, 0], &[0x90]] {
+            assert_eq!(branch_taken(&decode(bytes), &context(0, 0)), None);
+        }
+    }
+}
@@ -0,0 +1,287 @@
+/// What a region is used for, as far as its permissions and name tell
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RegionKind {
+    Code,
+    /// Non executable parts of a mapped file, like .data and .rodata
+    Data,
+    Heap,
+    Stack,
+    /// Anonymous mappings, which includes big allocations and the stacks of other threads
+    Anonymous,
+}
+
+impl RegionKind {
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Code => "code",
+            Self::Data => "data",
+            Self::Heap => "heap",
+            Self::Stack => "stack",
+            Self::Anonymous => "anon",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MemoryRegion {
+    pub start: u64,
+    pub end: u64,
+    /// `r-xp` style, p(rivate) or s(hared) last
+    pub permissions: String,
+    /// Offset into the backing file
+    pub offset: u64,
+    pub path: String,
+    /// Resident and dirty bytes, only known when read from smaps
+    pub rss: Option<u64>,
+    pub dirty: Option<u64>,
+}
+
+impl MemoryRegion {
+    pub const fn contains(&self, address: u64) -> bool {
+        address >= self.start && address < self.end
+    }
+
+    pub fn file_name(&self) -> &str {
+        self.path.rsplit('/').next().unwrap_or(&self.path)
+    }
+
+    pub fn is_file_backed(&self) -> bool {
+        self.path.starts_with('/')
+    }
+
+    /// [heap], [stack], [vdso], [vvar], [vsyscall]...
+    pub fn is_special(&self) -> bool {
+        self.path.starts_with('[')
+    }
+
+    pub fn is_executable(&self) -> bool {
+        self.permissions.as_bytes().get(2) == Some(&b'x')
+    }
+
+    pub fn is_readable(&self) -> bool {
+        self.permissions.as_bytes().first() == Some(&b'r')
+    }
+
+    pub fn kind(&self) -> RegionKind {
+        if self.is_executable() {
+            RegionKind::Code
+        } else if self.path == "[heap]" {
+            RegionKind::Heap
+        } else if self.path.starts_with("[stack") {
+            RegionKind::Stack
+        } else if self.is_file_backed() {
+            RegionKind::Data
+        } else {
+            RegionKind::Anonymous
+        }
+    }
+
+    pub const fn size(&self) -> u64 {
+        self.end - self.start
+    }
+}
+
+#[derive(Default, Clone)]
+pub struct MemoryMap {
+    regions: Vec<MemoryRegion>,
+}
+
+impl MemoryMap {
+    pub fn read(pid: u32) -> std::io::Result<Self> {
+        let maps = std::fs::read_to_string(format!("/proc/{pid}/maps"))?;
+
+        Ok(Self {
+            regions: maps.lines().filter_map(parse_maps_line).collect(),
+        })
+    }
+
+    /// Same as `read` plus RSS and dirty sizes from smaps, which is a lot slower for the kernel to produce
+    pub fn read_smaps(pid: u32) -> std::io::Result<Self> {
+        let smaps = std::fs::read_to_string(format!("/proc/{pid}/smaps"))?;
+
+        Ok(Self {
+            regions: parse_smaps(&smaps),
+        })
+    }
+
+    pub fn regions(&self) -> &[MemoryRegion] {
+        &self.regions
+    }
+
+    pub fn region_at(&self, address: u64) -> Option<&MemoryRegion> {
+        self.regions.iter().find(|r| r.contains(address))
+    }
+
+    /// Lowest mapped address of the file backing `region`, i.e. where its ELF header lives
+    pub fn module_base(&self, region: &MemoryRegion) -> Option<u64> {
+        self.regions
+            .iter()
+            .filter(|r| r.path == region.path)
+            .map(|r| r.start)
+            .min()
+    }
+
+    /// Every mapped file along with its base address and mapped size, in mapping order
+    pub fn modules(&self) -> Vec<(&str, u64, u64)> {
+        let mut modules: Vec<(&str, u64, u64)> = Vec::new();
+
+        for region in self.regions.iter().filter(|r| r.is_file_backed()) {
+            match modules.iter_mut().find(|(path, ..)| *path == region.path) {
+                Some((_, base, size)) => *size = region.end.max(*base + *size) - *base,
+                None => modules.push((&region.path, region.start, region.end - region.start)),
+            }
+        }
+
+        modules
+    }
+
+    /// `libfoo.so+0x1234` style description, relative to the module base
+    pub fn describe(&self, address: u64) -> Option<String> {
+        let region = self.region_at(address)?;
+
+        if !region.is_file_backed() {
+            return None;
+        }
+
+        let base = self.module_base(region)?;
+        Some(format!("{}+{:#x}", region.file_name(), address - base))
+    }
+}
+
+//00400000-00452000 r-xp 00000000 08:02 173521      /usr/bin/dbus-daemon
+fn parse_maps_line(line: &str) -> Option<MemoryRegion> {
+    //the path is padded to a column and may contain spaces itself
+    let mut fields = line.splitn(6, ' ');
+
+    let (start, end) = fields.next()?.split_once('-')?;
+    let permissions = fields.next()?;
+    let offset = fields.next()?;
+    let _device = fields.next()?;
+    let _inode = fields.next()?;
+    let path = fields.next().unwrap_or_default().trim_start();
+    //the file got replaced or unlinked, the mapping still holds the old one
+    let path = path.strip_suffix(" (deleted)").unwrap_or(path).to_owned();
+
+    Some(MemoryRegion {
+        start: u64::from_str_radix(start, 16).ok()?,
+        end: u64::from_str_radix(end, 16).ok()?,
+        permissions: permissions.to_owned(),
+        offset: u64::from_str_radix(offset, 16).ok()?,
+        path,
+        rss: None,
+        dirty: None,
+    })
+}
+
+//a maps line followed by `Key: value` lines for each region
+fn parse_smaps(smaps: &str) -> Vec<MemoryRegion> {
+    let mut regions: Vec<MemoryRegion> = Vec::new();
+
+    for line in smaps.lines() {
+        //Rss:                 123 kB
+        if let Some((key, value)) = line.split_once(':')
+            && !key.contains(' ')
+            && let Some(region) = regions.last_mut()
+        {
+            let Some(kb) = value
+                .trim()
+                .strip_suffix(" kB")
+                .and_then(|kb| kb.trim().parse::<u64>().ok())
+            else {
+                continue;
+            };
+
+            match key {
+                "Rss" => region.rss = Some(kb * 1024),
+                "Shared_Dirty" | "Private_Dirty" => {
+                    region.dirty = Some(region.dirty.unwrap_or(0) + kb * 1024)
+                }
+                _ => (),
+            }
+        } else if let Some(region) = parse_maps_line(line) {
+            regions.push(region);
+        }
+    }
+
+    regions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_line_with_file() {
+        let region = parse_maps_line(
+            "7f3c8a200000-7f3c8a228000 r-xp 00002000 08:02 173521                     /usr/lib/libc.so.6",
+        )
+        .unwrap();
+
+        assert_eq!(region.start, 0x7f3c8a200000);
+        assert_eq!(region.end, 0x7f3c8a228000);
+        assert_eq!(region.permissions, "r-xp");
+        assert_eq!(region.offset, 0x2000);
+        assert_eq!(region.path, "/usr/lib/libc.so.6");
+        assert_eq!(region.kind(), RegionKind::Code);
+    }
+
+    #[test]
+    fn maps_line_keeps_spaces_in_the_path() {
+        let region = parse_maps_line(
+            "00400000-00401000 r--p 00000000 08:02 42                         /home/me/my  app/a b",
+        )
+        .unwrap();
+
+        assert_eq!(region.path, "/home/me/my  app/a b");
+        assert_eq!(region.file_name(), "a b");
+    }
+
+    #[test]
+    fn maps_line_strips_deleted() {
+        let region = parse_maps_line(
+            "00400000-00401000 r-xp 00000000 08:02 42                         /tmp/a.out (deleted)",
+        )
+        .unwrap();
+
+        assert_eq!(region.path, "/tmp/a.out");
+    }
+
+    #[test]
+    fn maps_line_without_path() {
+        let region = parse_maps_line("7ffd1c000000-7ffd1c021000 rw-p 00000000 00:00 0 ").unwrap();
+
+        assert_eq!(region.path, "");
+        assert_eq!(region.kind(), RegionKind::Anonymous);
+
+        let region = parse_maps_line(
+            "01d4e000-01d6f000 rw-p 00000000 00:00 0                          [heap]",
+        )
+        .unwrap();
+        assert_eq!(region.kind(), RegionKind::Heap);
+    }
+
+    #[test]
+    fn smaps_sizes() {
+        let smaps = "\
+00400000-00401000 r-xp 00000000 08:02 42                         /tmp/my app
+Size:                  4 kB
+Rss:                   4 kB
+Shared_Dirty:          0 kB
+Private_Dirty:         8 kB
+VmFlags: rd ex mr mw me
+7ffd1c000000-7ffd1c021000 rw-p 00000000 00:00 0                          [stack]
+Rss:                  12 kB
+Shared_Dirty:          4 kB
+Private_Dirty:         4 kB
+";
+        let regions = parse_smaps(smaps);
+
+        assert_eq!(regions.len(), 2);
+        assert_eq!(regions[0].path, "/tmp/my app");
+        assert_eq!(regions[0].rss, Some(4096));
+        assert_eq!(regions[0].dirty, Some(8192));
+        assert_eq!(regions[1].path, "[stack]");
+        assert_eq!(regions[1].rss, Some(12 * 1024));
+        assert_eq!(regions[1].dirty, Some(8192));
+    }
+}
@@ -1,5 +1,19 @@
+pub mod annotation;
+pub mod assembler;
 pub mod breakpoint;
+pub mod debug_info;
 mod debugee;
+pub mod dwarf;
+pub mod expression;
+pub mod instruction_info;
+pub mod memory_map;
+pub mod notes;
+pub mod patch;
+pub mod pretty;
+pub mod rendezvous;
+pub mod symbols;
+pub mod unwind;
+pub mod variables;
 
 //man 7 signal
 #[allow(dead_code, clippy::upper_case_acronyms)]
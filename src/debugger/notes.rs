@@ -0,0 +1,242 @@
+use std::collections::BTreeMap;
+
+use super::expression;
+
+/// An address as module file name and offset from its base, stays meaningful wherever ASLR puts
+/// the module
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct ModuleAddress {
+    pub module: String,
+    pub offset: u64,
+}
+
+impl ModuleAddress {
+    /// Parses what `Display` writes, `libc.so.6+0x29d90`
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let invalid = || format!("Invalid module address '{text}'");
+
+        //module names can have a '+' in them (libstdc++)
+        let (module, offset) = text.trim().rsplit_once('+').ok_or_else(invalid)?;
+        let offset = offset.strip_prefix("0x").ok_or_else(invalid)?;
+
+        if module.is_empty() {
+            return Err(invalid());
+        }
+
+        Ok(Self {
+            module: module.to_owned(),
+            offset: u64::from_str_radix(offset, 16).map_err(|_| invalid())?,
+        })
+    }
+}
+
+impl std::fmt::Display for ModuleAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}+{:#x}", self.module, self.offset)
+    }
+}
+
+/// What the user wrote down about an address
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct Note {
+    /// Shown instead of the address, usable in expressions
+    pub label: Option<String>,
+    pub comment: Option<String>,
+    pub bookmark: bool,
+}
+
+impl Note {
+    fn is_empty(&self) -> bool {
+        self.label.is_none() && self.comment.is_none() && !self.bookmark
+    }
+}
+
+/// Labels, comments and bookmarks by module relative address
+#[derive(Clone, Default)]
+pub struct Notes {
+    notes: BTreeMap<ModuleAddress, Note>,
+    /// Bumped whenever a label changes, for whatever has labels baked in
+    generation: u64,
+}
+
+impl Notes {
+    pub fn get(&self, address: &ModuleAddress) -> Option<&Note> {
+        self.notes.get(address)
+    }
+
+    /// Sorted by module, then offset
+    pub fn iter(&self) -> impl Iterator<Item = (&ModuleAddress, &Note)> {
+        self.notes.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.notes.is_empty()
+    }
+
+    pub const fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn find_label(&self, label: &str) -> Option<&ModuleAddress> {
+        self.notes
+            .iter()
+            .find(|(_, note)| note.label.as_deref() == Some(label))
+            .map(|(address, _)| address)
+    }
+
+    /// An empty `label` removes it. Labels are unique and have to look like identifiers that
+    /// aren't registers or size keywords so expressions can refer to them
+    pub fn set_label(&mut self, address: ModuleAddress, label: &str) -> Result<(), String> {
+        let label = label.trim();
+
+        if !label.is_empty() {
+            let valid = label
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                && label
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '@' | '$'));
+            if !valid {
+                return Err(format!("Invalid label '{label}'"));
+            }
+
+            if expression::is_reserved(label) {
+                return Err(format!("'{label}' is a register or keyword"));
+            }
+
+            if let Some(other) = self.find_label(label)
+                && *other != address
+            {
+                return Err(format!("'{label}' already labels {other}"));
+            }
+        }
+
+        let label = (!label.is_empty()).then(|| label.to_owned());
+        self.update(address, |note| note.label = label);
+        Ok(())
+    }
+
+    /// An empty `comment` removes it
+    pub fn set_comment(&mut self, address: ModuleAddress, comment: &str) {
+        let comment = comment.trim();
+        let comment = (!comment.is_empty()).then(|| comment.to_owned());
+        self.update(address, |note| note.comment = comment);
+    }
+
+    pub fn set_bookmark(&mut self, address: ModuleAddress, bookmark: bool) {
+        self.update(address, |note| note.bookmark = bookmark);
+    }
+
+    pub fn remove(&mut self, address: &ModuleAddress) {
+        if self
+            .notes
+            .remove(address)
+            .is_some_and(|note| note.label.is_some())
+        {
+            self.generation += 1;
+        }
+    }
+
+    /// Notes left empty are dropped
+    fn update(&mut self, address: ModuleAddress, change: impl FnOnce(&mut Note)) {
+        let note = self.notes.entry(address.clone()).or_default();
+        let label = note.label.clone();
+        change(note);
+
+        if note.label != label {
+            self.generation += 1;
+        }
+
+        if note.is_empty() {
+            self.notes.remove(&address);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn address(offset: u64) -> ModuleAddress {
+        ModuleAddress {
+            module: String::from("app"),
+            offset,
+        }
+    }
+
+    #[test]
+    fn parse_module_address() {
+        let parsed = ModuleAddress::parse("libstdc++.so.6+0x10").unwrap();
+        assert_eq!(parsed.module, "libstdc++.so.6");
+        assert_eq!(parsed.offset, 0x10);
+        assert_eq!(parsed.to_string(), "libstdc++.so.6+0x10");
+
+        assert_eq!(
+            ModuleAddress::parse(" libc.so.6+0x29d90 ").unwrap(),
+            ModuleAddress {
+                module: String::from("libc.so.6"),
+                offset: 0x29d90,
+            }
+        );
+    }
+
+    #[test]
+    fn parse_invalid_module_address() {
+        assert!(ModuleAddress::parse("libc.so.6").is_err());
+        assert!(ModuleAddress::parse("libc.so.6+16").is_err());
+        assert!(ModuleAddress::parse("+0x10").is_err());
+        assert!(ModuleAddress::parse("app+0xzz").is_err());
+    }
+
+    #[test]
+    fn labels_are_unique_identifiers() {
+        let mut notes = Notes::default();
+
+        notes.set_label(address(0x10), "main_loop").unwrap();
+        assert_eq!(notes.find_label("main_loop"), Some(&address(0x10)));
+        assert!(notes.set_label(address(0x20), "main_loop").is_err());
+        assert!(notes.set_label(address(0x20), "1st").is_err());
+        assert!(notes.set_label(address(0x20), "a b").is_err());
+
+        //expressions would read these as registers and sizes
+        for reserved in ["rax", "RIP", "eax", "r8d", "eflags", "byte", "qword"] {
+            assert!(notes.set_label(address(0x20), reserved).is_err());
+        }
+        notes.set_label(address(0x20), "rax_saved").unwrap();
+
+        //relabeling the same address is fine
+        notes.set_label(address(0x10), "main_loop").unwrap();
+    }
+
+    #[test]
+    fn empty_notes_are_dropped() {
+        let mut notes = Notes::default();
+
+        notes.set_comment(address(0x10), "checks argc");
+        notes.set_bookmark(address(0x10), true);
+        notes.set_comment(address(0x10), "  ");
+        assert!(!notes.is_empty());
+
+        notes.set_bookmark(address(0x10), false);
+        assert!(notes.is_empty());
+    }
+
+    #[test]
+    fn generation_follows_labels_only() {
+        let mut notes = Notes::default();
+
+        notes.set_comment(address(0x10), "checks argc");
+        notes.set_bookmark(address(0x10), true);
+        notes.remove(&address(0x10));
+        assert_eq!(notes.generation(), 0);
+
+        notes.set_label(address(0x10), "start").unwrap();
+        assert_eq!(notes.generation(), 1);
+        notes.set_label(address(0x10), "start").unwrap();
+        assert_eq!(notes.generation(), 1);
+
+        notes.remove(&address(0x10));
+        assert_eq!(notes.generation(), 2);
+    }
+}
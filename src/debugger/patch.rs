@@ -0,0 +1,168 @@
+use std::path::Path;
+
+use super::symbols::Module;
+
+/// Bytes changed through `Debugee::write_memory`, overlapping and adjacent writes end up in one patch
+#[derive(Clone, Debug)]
+pub struct Patch {
+    pub address: u64,
+    /// What was there before the first write, our int3s left out
+    pub original: Vec<u8>,
+    pub patched: Vec<u8>,
+}
+
+impl Patch {
+    pub const fn end(&self) -> u64 {
+        self.address + self.patched.len() as u64
+    }
+
+    /// Overlapping or right next to `start..end`
+    const fn touches(&self, start: u64, end: u64) -> bool {
+        start <= self.end() && end >= self.address
+    }
+
+    /// `other` written on top of this one, the original bytes are the oldest known ones
+    fn merge(self, other: Self) -> Self {
+        let start = self.address.min(other.address);
+        let end = self.end().max(other.end());
+        let mut original = vec![0; (end - start) as usize];
+        let mut patched = original.clone();
+
+        for patch in [&other, &self] {
+            let offset = (patch.address - start) as usize;
+            original[offset..offset + patch.original.len()].copy_from_slice(&patch.original);
+        }
+
+        for patch in [&self, &other] {
+            let offset = (patch.address - start) as usize;
+            patched[offset..offset + patch.patched.len()].copy_from_slice(&patch.patched);
+        }
+
+        Self {
+            address: start,
+            original,
+            patched,
+        }
+    }
+}
+
+/// Adds `patch` to `patches` (sorted by address), merging it with the ones it touches.
+/// Writing the original bytes back by hand drops the patch
+pub fn record(patches: &mut Vec<Patch>, mut patch: Patch) {
+    let mut i = 0;
+    while i < patches.len() {
+        if patches[i].touches(patch.address, patch.end()) {
+            patch = patches.remove(i).merge(patch);
+        } else {
+            i += 1;
+        }
+    }
+
+    if patch.original != patch.patched {
+        let index = patches.partition_point(|p| p.address < patch.address);
+        patches.insert(index, patch);
+    }
+}
+
+/// Writes a copy of `module`'s file with the patches inside it applied to `destination`, file offsets
+/// come from the program headers. Returns how many patched bytes the file has no room for (.bss)
+pub fn export(module: &Module, patches: &[Patch], destination: &Path) -> Result<usize, String> {
+    let mut data = std::fs::read(&module.path).map_err(|e| format!("{}: {e}", module.path))?;
+    let mut skipped = 0;
+
+    for patch in patches
+        .iter()
+        .filter(|patch| module.contains(patch.address))
+    {
+        for (address, byte) in (patch.address..).zip(&patch.patched) {
+            match module
+                .file_offset(address)
+                .and_then(|offset| data.get_mut(offset as usize))
+            {
+                Some(old) => *old = *byte,
+                None => skipped += 1,
+            }
+        }
+    }
+
+    std::fs::write(destination, &data).map_err(|e| format!("{}: {e}", destination.display()))?;
+
+    //keep it executable
+    if let Ok(metadata) = std::fs::metadata(&module.path) {
+        std::fs::set_permissions(destination, metadata.permissions())
+            .map_err(|e| format!("{}: {e}", destination.display()))?;
+    }
+
+    Ok(skipped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patch(address: u64, original: &[u8], patched: &[u8]) -> Patch {
+        Patch {
+            address,
+            original: original.to_vec(),
+            patched: patched.to_vec(),
+        }
+    }
+
+    #[test]
+    fn separate_patches_stay_sorted() {
+        let mut patches = Vec::new();
+        record(&mut patches, patch(0x20, &[1], &[2]));
+        record(&mut patches, patch(0x10, &[3], &[4]));
+
+        assert_eq!(
+            patches.iter().map(|p| p.address).collect::<Vec<_>>(),
+            [0x10, 0x20]
+        );
+    }
+
+    #[test]
+    fn touching_patches_merge() {
+        let mut patches = Vec::new();
+        record(&mut patches, patch(0x10, &[1, 2], &[0x90, 0x90]));
+        record(&mut patches, patch(0x12, &[3], &[0xcc]));
+
+        assert_eq!(patches.len(), 1);
+        assert_eq!(patches[0].address, 0x10);
+        assert_eq!(patches[0].original, [1, 2, 3]);
+        assert_eq!(patches[0].patched, [0x90, 0x90, 0xcc]);
+    }
+
+    #[test]
+    fn overlapping_patches_keep_the_oldest_original() {
+        let mut patches = Vec::new();
+        record(&mut patches, patch(0x10, &[1, 2], &[0x90, 0x90]));
+        //read back over the first patch, so its "original" is the patched bytes
+        record(&mut patches, patch(0x0f, &[0, 0x90], &[0xeb, 0xfe]));
+
+        assert_eq!(patches.len(), 1);
+        assert_eq!(patches[0].address, 0x0f);
+        assert_eq!(patches[0].original, [0, 1, 2]);
+        assert_eq!(patches[0].patched, [0xeb, 0xfe, 0x90]);
+    }
+
+    #[test]
+    fn bridging_patch_merges_both_neighbours() {
+        let mut patches = Vec::new();
+        record(&mut patches, patch(0x10, &[1], &[0x90]));
+        record(&mut patches, patch(0x13, &[4], &[0x90]));
+        record(&mut patches, patch(0x11, &[2, 3], &[0xcc, 0xcc]));
+
+        assert_eq!(patches.len(), 1);
+        assert_eq!(patches[0].original, [1, 2, 3, 4]);
+        assert_eq!(patches[0].patched, [0x90, 0xcc, 0xcc, 0x90]);
+    }
+
+    #[test]
+    fn writing_the_original_back_drops_the_patch() {
+        let mut patches = Vec::new();
+        record(&mut patches, patch(0x10, &[1, 2], &[0x90, 0x90]));
+        record(&mut patches, patch(0x10, &[0x90, 0x90], &[1, 2]));
+
+        assert!(patches.is_empty());
+    }
+}
@@ -0,0 +1,864 @@
+use regex::Regex;
+
+use super::dwarf::DwarfInfo;
+use super::variables::{Child, Memory, Place, TypeInfo, TypeKind, TypeRef, MAX_ELEMENTS};
+
+//summaries nested deeper than this get cut off with "..."
+const MAX_SUMMARY_DEPTH: usize = 3;
+//elements previewed in the summary of a collection
+const MAX_PREVIEW: usize = 5;
+//longest string shown in a summary
+const MAX_STRING: u64 = 256;
+//wrapper structs looked through when searching for a member or pointer
+const MAX_SEARCH_DEPTH: usize = 6;
+//the control bytes of a hash table get read at once, bigger tables aren't expanded
+const MAX_BUCKETS: u64 = 1 << 20;
+
+/// Shows values of a type by what they hold instead of how they're laid out.
+/// Types of your own get one by implementing this and adding it to `PrettyPrinters::default`,
+/// or without rebuilding through a `CustomPrinter`
+pub trait PrettyPrinter {
+    fn matches(&self, info: &TypeInfo) -> bool;
+
+    /// `None` when the value doesn't look like expected, it's shown plainly then
+    fn summary(
+        &self,
+        formatter: &Formatter,
+        info: &TypeInfo,
+        place: &Place,
+        depth: usize,
+    ) -> Option<String>;
+
+    /// What expanding the value shows, `None` for its plain members
+    fn children(&self, formatter: &Formatter, info: &TypeInfo, place: &Place)
+        -> Option<Vec<Child>>;
+}
+
+/// Built-in printers for the Rust standard library, plus the user's own
+pub struct PrettyPrinters {
+    printers: Vec<Box<dyn PrettyPrinter>>,
+    custom: Vec<CustomPrinter>,
+    /// What `custom` was parsed from
+    source: String,
+}
+
+impl Default for PrettyPrinters {
+    fn default() -> Self {
+        Self {
+            printers: vec![
+                Box::new(StringPrinter),
+                Box::new(SlicePrinter),
+                Box::new(VecPrinter),
+                Box::new(VecDequePrinter),
+                Box::new(HashMapPrinter),
+                Box::new(RcPrinter),
+                Box::new(BoxPrinter),
+            ],
+            custom: Vec::new(),
+            source: String::new(),
+        }
+    }
+}
+
+impl PrettyPrinters {
+    /// Replaces the user's printers, one per line as described on `CustomPrinter::parse`.
+    /// Empty lines and ones starting with `#` are skipped
+    pub fn set_custom(&mut self, source: &str) -> Result<(), String> {
+        self.custom = source
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty() && !line.trim().starts_with('#'))
+            .map(|(index, line)| {
+                CustomPrinter::parse(line).map_err(|error| format!("Line {}: {error}", index + 1))
+            })
+            .collect::<Result<_, _>>()?;
+        self.source = source.to_owned();
+
+        Ok(())
+    }
+
+    pub fn custom_source(&self) -> &str {
+        &self.source
+    }
+
+    /// User printers take precedence, they can override the built-in ones
+    fn find(&self, info: &TypeInfo) -> Option<&dyn PrettyPrinter> {
+        self.custom
+            .iter()
+            .map(|printer| printer as &dyn PrettyPrinter)
+            .chain(self.printers.iter().map(Box::as_ref))
+            .find(|printer| printer.matches(info))
+    }
+}
+
+/// Formats values, going through the pretty-printers before falling back to their DWARF layout
+pub struct Formatter<'a> {
+    pub dwarf: &'a DwarfInfo,
+    pub memory: &'a dyn Memory,
+    pub printers: &'a PrettyPrinters,
+}
+
+impl Formatter<'_> {
+    pub fn type_info(&self, ty: Option<TypeRef>) -> Option<TypeInfo> {
+        ty.map(|ty| self.dwarf.type_info(ty))
+    }
+
+    pub fn summary(&self, info: &TypeInfo, place: &Place) -> String {
+        self.summary_at(info, place, 0)
+    }
+
+    /// `depth` is how deep in another summary this one is
+    pub fn summary_at(&self, info: &TypeInfo, place: &Place, depth: usize) -> String {
+        if let Place::Unavailable(reason) = place {
+            return format!("<{reason}>");
+        }
+
+        if depth > MAX_SUMMARY_DEPTH {
+            return String::from("...");
+        }
+
+        if let Some(summary) = self
+            .printers
+            .find(info)
+            .and_then(|printer| printer.summary(self, info, place, depth))
+        {
+            return summary;
+        }
+
+        match &info.kind {
+            TypeKind::Variants {
+                discriminant,
+                variants,
+            } => {
+                let Some(variant) =
+                    self.dwarf
+                        .active_variant(discriminant.as_ref(), variants, place, self.memory)
+                else {
+                    return String::from("<invalid discriminant>");
+                };
+
+                let fields = self.dwarf.children(info, place, self.memory);
+                let (is_tuple, list) = self.field_list(&fields, depth);
+
+                match (fields.is_empty(), is_tuple) {
+                    (true, _) => variant.member.name.clone(),
+                    (false, true) => format!("{}({list})", variant.member.name),
+                    (false, false) => format!("{} {{ {list} }}", variant.member.name),
+                }
+            }
+            //tuples
+            TypeKind::Struct(_) if info.name.starts_with('(') => {
+                let fields = self.dwarf.children(info, place, self.memory);
+                format!("({})", self.field_list(&fields, depth).1)
+            }
+            //newtypes like `NonZero` are shown as what they wrap
+            TypeKind::Struct(members) if members.len() == 1 && members[0].name == "__0" => self
+                .dwarf
+                .children(info, place, self.memory)
+                .first()
+                .map_or_else(
+                    || String::from("?"),
+                    |inner| self.child_summary(inner, depth),
+                ),
+            TypeKind::Array {
+                element: Some(element),
+                count: Some(count),
+            } if !self.dwarf.is_char(*element) => {
+                let elements = self.dwarf.children(info, place, self.memory);
+                self.preview(&elements, *count, depth)
+            }
+            _ => self.dwarf.format_value(info, place, self.memory),
+        }
+    }
+
+    /// Fields of a variant or tuple, along with whether they're positional (`__0`, `__1`, ...)
+    fn field_list(&self, fields: &[Child], depth: usize) -> (bool, String) {
+        let is_tuple = fields.iter().all(|field| field.name.starts_with("__"));
+
+        let list = fields
+            .iter()
+            .map(|field| {
+                let value = self.child_summary(field, depth + 1);
+                if is_tuple {
+                    value
+                } else {
+                    format!("{}: {value}", field.name)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        (is_tuple, list)
+    }
+
+    pub fn child_summary(&self, child: &Child, depth: usize) -> String {
+        match self.type_info(child.ty) {
+            Some(info) => self.summary_at(&info, &child.place, depth),
+            None => String::from("?"),
+        }
+    }
+
+    pub fn children(&self, info: &TypeInfo, place: &Place) -> Vec<Child> {
+        if let Place::Unavailable(_) = place {
+            return Vec::new();
+        }
+
+        self.printers
+            .find(info)
+            .and_then(|printer| printer.children(self, info, place))
+            .unwrap_or_else(|| self.dwarf.children(info, place, self.memory))
+    }
+
+    pub fn has_children(&self, info: &TypeInfo, place: &Place) -> bool {
+        match &info.kind {
+            TypeKind::Variants { .. } => !self.children(info, place).is_empty(),
+            _ if self.printers.find(info).is_some() => !self.children(info, place).is_empty(),
+            _ => info.has_children(),
+        }
+    }
+
+    /// `[a, b, ...]` out of the first few elements
+    pub fn preview(&self, elements: &[Child], count: u64, depth: usize) -> String {
+        let mut preview = elements
+            .iter()
+            .take(MAX_PREVIEW)
+            .map(|element| self.child_summary(element, depth + 1))
+            .collect::<Vec<_>>();
+
+        if count > preview.len() as u64 {
+            preview.push(String::from("..."));
+        }
+
+        format!("[{}]", preview.join(", "))
+    }
+
+    /// Member called `name`, looking through the structs it's nested in (`buf.inner.cap`)
+    pub fn find_member(&self, info: &TypeInfo, place: &Place, name: &str) -> Option<Child> {
+        self.find_member_at(info, place, name, 0)
+    }
+
+    fn find_member_at(
+        &self,
+        info: &TypeInfo,
+        place: &Place,
+        name: &str,
+        depth: usize,
+    ) -> Option<Child> {
+        if depth > MAX_SEARCH_DEPTH || !matches!(info.kind, TypeKind::Struct(_)) {
+            return None;
+        }
+
+        let children = self.dwarf.children(info, place, self.memory);
+
+        if let Some(index) = children.iter().position(|child| child.name == name) {
+            return children.into_iter().nth(index);
+        }
+
+        children.iter().find_map(|child| {
+            self.find_member_at(&self.type_info(child.ty)?, &child.place, name, depth + 1)
+        })
+    }
+
+    /// Member at a dotted path of member names, without looking through anything
+    pub fn member_path(&self, info: &TypeInfo, place: &Place, path: &str) -> Option<Child> {
+        let mut current: Option<Child> = None;
+
+        for name in path.split('.').map(str::trim) {
+            let children = match &current {
+                Some(child) => {
+                    self.dwarf
+                        .children(&self.type_info(child.ty)?, &child.place, self.memory)
+                }
+                None => self.dwarf.children(info, place, self.memory),
+            };
+
+            current = Some(children.into_iter().find(|child| child.name == name)?);
+        }
+
+        current
+    }
+
+    /// Value of an integer or pointer, looking through wrappers like `Cell<usize>` or `NonNull<T>`
+    pub fn unsigned(&self, info: &TypeInfo, place: &Place) -> Option<u64> {
+        self.unsigned_at(info, place, 0)
+    }
+
+    fn unsigned_at(&self, info: &TypeInfo, place: &Place, depth: usize) -> Option<u64> {
+        match &info.kind {
+            TypeKind::Base(_) | TypeKind::Pointer(_) | TypeKind::Enum(_) => {
+                let bytes = place.read(info.size.min(8), self.memory)?;
+                let mut value = [0; 8];
+                value[..bytes.len()].copy_from_slice(&bytes);
+                Some(u64::from_le_bytes(value))
+            }
+            TypeKind::Struct(_) if depth <= MAX_SEARCH_DEPTH => {
+                //wrappers can have zero sized markers next to the value
+                self.dwarf
+                    .children(info, place, self.memory)
+                    .iter()
+                    .filter_map(|child| Some((self.type_info(child.ty)?, &child.place)))
+                    .find(|(info, _)| info.size > 0)
+                    .and_then(|(info, place)| self.unsigned_at(&info, place, depth + 1))
+            }
+            _ => None,
+        }
+    }
+
+    /// First pointer in a value, looking through wrappers like `Unique<T>`, along with what it points to
+    pub fn find_pointer(&self, info: &TypeInfo, place: &Place) -> Option<(u64, Option<TypeRef>)> {
+        self.find_pointer_at(info, place, 0)
+    }
+
+    fn find_pointer_at(
+        &self,
+        info: &TypeInfo,
+        place: &Place,
+        depth: usize,
+    ) -> Option<(u64, Option<TypeRef>)> {
+        match &info.kind {
+            TypeKind::Pointer(target) => Some((self.unsigned(info, place)?, *target)),
+            TypeKind::Struct(_) if depth <= MAX_SEARCH_DEPTH => self
+                .dwarf
+                .children(info, place, self.memory)
+                .iter()
+                .find_map(|child| {
+                    self.find_pointer_at(&self.type_info(child.ty)?, &child.place, depth + 1)
+                }),
+            _ => None,
+        }
+    }
+
+    /// `count` values of type `element` laid out from `address`, named by index
+    pub fn elements(&self, element: TypeRef, address: u64, count: u64) -> Vec<Child> {
+        let size = self.dwarf.type_size(element);
+
+        (0..count.min(MAX_ELEMENTS))
+            .map(|i| Child {
+                name: format!("[{i}]"),
+                ty: Some(element),
+                place: Place::Memory(address.wrapping_add(i * size)),
+            })
+            .collect()
+    }
+
+    /// Quoted UTF-8 text of `length` bytes at `address`
+    pub fn string(&self, address: u64, length: u64) -> Option<String> {
+        let bytes = self
+            .memory
+            .read_bytes(address, length.min(MAX_STRING) as usize)?;
+        let mut string = format!("{:?}", String::from_utf8_lossy(&bytes));
+
+        if length > MAX_STRING {
+            string.push_str("...");
+        }
+
+        Some(string)
+    }
+}
+
+fn parameter(info: &TypeInfo, name: &str) -> Option<TypeRef> {
+    info.parameters
+        .iter()
+        .find(|(parameter, _)| parameter == name)
+        .map(|(_, ty)| *ty)
+}
+
+/// Name without its module path, `alloc::boxed::Box<T>` becomes `Box<T>`
+fn base_name(name: &str) -> &str {
+    let generics = name.find('<').unwrap_or(name.len());
+    let start = name[..generics].rfind("::").map_or(0, |i| i + 2);
+    &name[start..]
+}
+
+fn is_struct_named(info: &TypeInfo, prefix: &str) -> bool {
+    matches!(info.kind, TypeKind::Struct(_)) && base_name(&info.name).starts_with(prefix)
+}
+
+/// `String`, `OsString` and `PathBuf`, all of them a `Vec<u8>` underneath
+struct StringPrinter;
+
+impl PrettyPrinter for StringPrinter {
+    fn matches(&self, info: &TypeInfo) -> bool {
+        matches!(info.kind, TypeKind::Struct(_))
+            && matches!(base_name(&info.name), "String" | "OsString" | "PathBuf")
+    }
+
+    fn summary(&self, f: &Formatter, info: &TypeInfo, place: &Place, _: usize) -> Option<String> {
+        let (address, _) = f.find_pointer(info, place)?;
+        let length = f.find_member(info, place, "len")?;
+        f.string(
+            address,
+            f.unsigned(&f.type_info(length.ty)?, &length.place)?,
+        )
+    }
+
+    fn children(&self, _: &Formatter, _: &TypeInfo, _: &Place) -> Option<Vec<Child>> {
+        Some(Vec::new())
+    }
+}
+
+/// Fat pointers to slices and `str` (`&[T]`, `&str`, `Box<[T]>`, ...)
+struct SlicePrinter;
+
+impl SlicePrinter {
+    fn parts(f: &Formatter, info: &TypeInfo, place: &Place) -> Option<(u64, Option<TypeRef>, u64)> {
+        let pointer = f.member_path(info, place, "data_ptr")?;
+        let length = f.member_path(info, place, "length")?;
+
+        let (address, element) = f.find_pointer(&f.type_info(pointer.ty)?, &pointer.place)?;
+        let length = f.unsigned(&f.type_info(length.ty)?, &length.place)?;
+
+        Some((address, element, length))
+    }
+
+    fn is_str(info: &TypeInfo) -> bool {
+        info.name.ends_with("str") || info.name.contains("<str")
+    }
+}
+
+impl PrettyPrinter for SlicePrinter {
+    fn matches(&self, info: &TypeInfo) -> bool {
+        match &info.kind {
+            TypeKind::Struct(members) => {
+                members.len() == 2 && members[0].name == "data_ptr" && members[1].name == "length"
+            }
+            _ => false,
+        }
+    }
+
+    fn summary(
+        &self,
+        f: &Formatter,
+        info: &TypeInfo,
+        place: &Place,
+        depth: usize,
+    ) -> Option<String> {
+        let (address, element, length) = Self::parts(f, info, place)?;
+
+        if Self::is_str(info) {
+            return f.string(address, length);
+        }
+
+        let elements = f.elements(element?, address, length.min(MAX_PREVIEW as u64));
+        Some(format!(
+            "len={length} {}",
+            f.preview(&elements, length, depth)
+        ))
+    }
+
+    fn children(&self, f: &Formatter, info: &TypeInfo, place: &Place) -> Option<Vec<Child>> {
+        let (address, element, length) = Self::parts(f, info, place)?;
+
+        if Self::is_str(info) {
+            return Some(Vec::new());
+        }
+
+        Some(f.elements(element?, address, length))
+    }
+}
+
+struct VecPrinter;
+
+impl VecPrinter {
+    /// The buffer is type erased (`RawVecInner` holds a `u8` pointer), the element type comes from `T`
+    fn parts(f: &Formatter, info: &TypeInfo, place: &Place) -> Option<(TypeRef, u64, u64)> {
+        let element = parameter(info, "T")?;
+
+        let buffer = f.member_path(info, place, "buf")?;
+        let (address, _) = f.find_pointer(&f.type_info(buffer.ty)?, &buffer.place)?;
+
+        let length = f.member_path(info, place, "len")?;
+        let length = f.unsigned(&f.type_info(length.ty)?, &length.place)?;
+
+        Some((element, address, length))
+    }
+}
+
+impl PrettyPrinter for VecPrinter {
+    fn matches(&self, info: &TypeInfo) -> bool {
+        is_struct_named(info, "Vec<")
+    }
+
+    fn summary(
+        &self,
+        f: &Formatter,
+        info: &TypeInfo,
+        place: &Place,
+        depth: usize,
+    ) -> Option<String> {
+        let (element, address, length) = Self::parts(f, info, place)?;
+        let elements = f.elements(element, address, length.min(MAX_PREVIEW as u64));
+
+        Some(format!(
+            "len={length} {}",
+            f.preview(&elements, length, depth)
+        ))
+    }
+
+    fn children(&self, f: &Formatter, info: &TypeInfo, place: &Place) -> Option<Vec<Child>> {
+        let (element, address, length) = Self::parts(f, info, place)?;
+        Some(f.elements(element, address, length))
+    }
+}
+
+/// Ring buffer, element `i` lives at `(head + i) % capacity`
+struct VecDequePrinter;
+
+impl VecDequePrinter {
+    fn elements(
+        f: &Formatter,
+        info: &TypeInfo,
+        place: &Place,
+        limit: u64,
+    ) -> Option<(Vec<Child>, u64)> {
+        let element = parameter(info, "T")?;
+        let size = f.dwarf.type_size(element);
+
+        let field = |name| {
+            let child = f.find_member(info, place, name)?;
+            f.unsigned(&f.type_info(child.ty)?, &child.place)
+        };
+
+        let buffer = f.member_path(info, place, "buf")?;
+        let (address, _) = f.find_pointer(&f.type_info(buffer.ty)?, &buffer.place)?;
+        let (head, length, capacity) = (field("head")?, field("len")?, field("cap")?);
+
+        if capacity == 0 {
+            return Some((Vec::new(), 0));
+        }
+
+        let elements = (0..length.min(limit))
+            .map(|i| Child {
+                name: format!("[{i}]"),
+                ty: Some(element),
+                place: Place::Memory(address.wrapping_add((head + i) % capacity * size)),
+            })
+            .collect();
+
+        Some((elements, length))
+    }
+}
+
+impl PrettyPrinter for VecDequePrinter {
+    fn matches(&self, info: &TypeInfo) -> bool {
+        is_struct_named(info, "VecDeque<")
+    }
+
+    fn summary(
+        &self,
+        f: &Formatter,
+        info: &TypeInfo,
+        place: &Place,
+        depth: usize,
+    ) -> Option<String> {
+        let (elements, length) = Self::elements(f, info, place, MAX_PREVIEW as u64)?;
+        Some(format!(
+            "len={length} {}",
+            f.preview(&elements, length, depth)
+        ))
+    }
+
+    fn children(&self, f: &Formatter, info: &TypeInfo, place: &Place) -> Option<Vec<Child>> {
+        Some(Self::elements(f, info, place, MAX_ELEMENTS)?.0)
+    }
+}
+
+/// Key and value of a full bucket, sets have no values
+type Bucket = (Child, Option<Child>);
+
+/// hashbrown tables, buckets are laid out backwards from the control bytes
+/// and a control byte with the top bit clear marks a full one
+struct HashMapPrinter;
+
+impl HashMapPrinter {
+    /// Full buckets, up to `limit` of them, along with the item count
+    fn entries(
+        f: &Formatter,
+        info: &TypeInfo,
+        place: &Place,
+        limit: usize,
+    ) -> Option<(Vec<Bucket>, u64)> {
+        let table = f.find_member(info, place, "table")?;
+        let table_info = f.type_info(table.ty)?;
+        let bucket = parameter(&table_info, "T")?;
+        let bucket_info = f.dwarf.type_info(bucket);
+        let bucket_size = bucket_info.size;
+
+        let field = |name| {
+            let child = f.find_member(&table_info, &table.place, name)?;
+            f.unsigned(&f.type_info(child.ty)?, &child.place)
+        };
+
+        let items = field("items")?;
+        let buckets = field("bucket_mask")? + 1;
+        let control = f.find_member(&table_info, &table.place, "ctrl")?;
+        let (control, _) = f.find_pointer(&f.type_info(control.ty)?, &control.place)?;
+
+        if items == 0 || buckets > MAX_BUCKETS {
+            return Some((Vec::new(), items));
+        }
+
+        let control_bytes = f.memory.read_bytes(control, buckets as usize)?;
+
+        let entries = control_bytes
+            .iter()
+            .enumerate()
+            .filter(|(_, byte)| *byte & 0x80 == 0)
+            .take(limit)
+            .map(|(i, _)| {
+                let address = control.wrapping_sub((i as u64 + 1) * bucket_size);
+                let mut fields = f
+                    .dwarf
+                    .children(&bucket_info, &Place::Memory(address), f.memory)
+                    .into_iter();
+
+                (fields.next(), fields.next())
+            })
+            .filter_map(|(key, value)| Some((key?, value)))
+            .collect();
+
+        Some((entries, items))
+    }
+
+    fn is_set(info: &TypeInfo) -> bool {
+        base_name(&info.name).starts_with("HashSet<")
+    }
+}
+
+impl PrettyPrinter for HashMapPrinter {
+    fn matches(&self, info: &TypeInfo) -> bool {
+        is_struct_named(info, "HashMap<") || is_struct_named(info, "HashSet<")
+    }
+
+    fn summary(
+        &self,
+        f: &Formatter,
+        info: &TypeInfo,
+        place: &Place,
+        depth: usize,
+    ) -> Option<String> {
+        let (entries, items) = Self::entries(f, info, place, MAX_PREVIEW)?;
+
+        let mut preview = entries
+            .iter()
+            .map(|(key, value)| {
+                let key = f.child_summary(key, depth + 1);
+                match value.as_ref().filter(|_| !Self::is_set(info)) {
+                    Some(value) => format!("{key}: {}", f.child_summary(value, depth + 1)),
+                    None => key,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        if items > preview.len() as u64 {
+            preview.push(String::from("..."));
+        }
+
+        Some(format!("len={items} {{{}}}", preview.join(", ")))
+    }
+
+    /// Maps are shown as `[key]: value`, sets by index
+    fn children(&self, f: &Formatter, info: &TypeInfo, place: &Place) -> Option<Vec<Child>> {
+        let (entries, _) = Self::entries(f, info, place, MAX_ELEMENTS as usize)?;
+
+        Some(
+            entries
+                .into_iter()
+                .enumerate()
+                .map(
+                    |(i, (key, value))| match value.filter(|_| !Self::is_set(info)) {
+                        Some(value) => Child {
+                            name: format!("[{}]", f.child_summary(&key, 1)),
+                            ..value
+                        },
+                        None => Child {
+                            name: format!("[{i}]"),
+                            ..key
+                        },
+                    },
+                )
+                .collect(),
+        )
+    }
+}
+
+/// `Rc<T>` and `Arc<T>`, pointing to the value along with its reference counts
+struct RcPrinter;
+
+impl RcPrinter {
+    /// Strong count, weak count and the value
+    fn parts(f: &Formatter, info: &TypeInfo, place: &Place) -> Option<(u64, u64, Child)> {
+        let (address, Some(inner)) = f.find_pointer(info, place)? else {
+            return None;
+        };
+        let inner_info = f.dwarf.type_info(inner);
+        let inner_place = Place::Memory(address);
+
+        let count = |name| {
+            let child = f.member_path(&inner_info, &inner_place, name)?;
+            f.unsigned(&f.type_info(child.ty)?, &child.place)
+        };
+
+        let value = f
+            .member_path(&inner_info, &inner_place, "value")
+            .or_else(|| f.member_path(&inner_info, &inner_place, "data"))?;
+
+        Some((count("strong")?, count("weak")?, value))
+    }
+}
+
+impl PrettyPrinter for RcPrinter {
+    fn matches(&self, info: &TypeInfo) -> bool {
+        is_struct_named(info, "Rc<") || is_struct_named(info, "Arc<")
+    }
+
+    fn summary(
+        &self,
+        f: &Formatter,
+        info: &TypeInfo,
+        place: &Place,
+        depth: usize,
+    ) -> Option<String> {
+        let (strong, weak, value) = Self::parts(f, info, place)?;
+
+        //the strong references together hold one weak one
+        Some(format!(
+            "strong={strong} weak={} {}",
+            weak.saturating_sub(1),
+            f.child_summary(&value, depth + 1)
+        ))
+    }
+
+    fn children(&self, f: &Formatter, info: &TypeInfo, place: &Place) -> Option<Vec<Child>> {
+        let (_, _, value) = Self::parts(f, info, place)?;
+        Some(vec![value])
+    }
+}
+
+/// `Box<T>` is a plain pointer in the DWARF, shown as what it points to
+struct BoxPrinter;
+
+impl BoxPrinter {
+    fn pointee(f: &Formatter, info: &TypeInfo, place: &Place) -> Option<(TypeInfo, Place)> {
+        let TypeKind::Pointer(Some(target)) = info.kind else {
+            return None;
+        };
+
+        let address = f.unsigned(info, place).filter(|&address| address != 0)?;
+        Some((f.dwarf.type_info(target), Place::Memory(address)))
+    }
+}
+
+impl PrettyPrinter for BoxPrinter {
+    fn matches(&self, info: &TypeInfo) -> bool {
+        matches!(info.kind, TypeKind::Pointer(_)) && base_name(&info.name).starts_with("Box<")
+    }
+
+    fn summary(
+        &self,
+        f: &Formatter,
+        info: &TypeInfo,
+        place: &Place,
+        depth: usize,
+    ) -> Option<String> {
+        let (info, place) = Self::pointee(f, info, place)?;
+        Some(f.summary_at(&info, &place, depth + 1))
+    }
+
+    fn children(&self, f: &Formatter, info: &TypeInfo, place: &Place) -> Option<Vec<Child>> {
+        let (info, place) = Self::pointee(f, info, place)?;
+        Some(f.children(&info, &place))
+    }
+}
+
+/// A printer written by the user, for types of their own
+pub struct CustomPrinter {
+    /// Matched against the whole type name
+    pattern: Regex,
+    /// Text with `{member.path}` placeholders, replaced by the summaries of those members
+    summary: String,
+    /// Members holding a pointer to the elements and their count, to show them when expanded
+    elements: Option<(String, String)>,
+}
+
+impl CustomPrinter {
+    /// `<type name regex> => <summary>`, optionally followed by `; <pointer>[<length>]`, like
+    /// `^MyVec<.*>$ => {len} items ; data[len]`
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let (pattern, rest) = line
+            .split_once("=>")
+            .ok_or_else(|| String::from("expected `<type regex> => <summary>`"))?;
+
+        let pattern = Regex::new(&format!("^(?:{})$", pattern.trim()))
+            .map_err(|error| format!("invalid regex: {error}"))?;
+
+        let (summary, elements) = match rest.split_once(';') {
+            Some((summary, elements)) => {
+                let (pointer, length) = elements
+                    .trim()
+                    .strip_suffix(']')
+                    .and_then(|elements| elements.split_once('['))
+                    .ok_or_else(|| String::from("expected `<pointer>[<length>]` after `;`"))?;
+
+                (
+                    summary,
+                    Some((pointer.trim().to_owned(), length.trim().to_owned())),
+                )
+            }
+            None => (rest, None),
+        };
+
+        Ok(Self {
+            pattern,
+            summary: summary.trim().to_owned(),
+            elements,
+        })
+    }
+}
+
+impl PrettyPrinter for CustomPrinter {
+    fn matches(&self, info: &TypeInfo) -> bool {
+        self.pattern.is_match(&info.name)
+    }
+
+    fn summary(
+        &self,
+        f: &Formatter,
+        info: &TypeInfo,
+        place: &Place,
+        depth: usize,
+    ) -> Option<String> {
+        let mut summary = String::new();
+        let mut rest = self.summary.as_str();
+
+        while let Some(start) = rest.find('{') {
+            let Some(end) = rest[start..].find('}') else {
+                break;
+            };
+
+            let path = &rest[start + 1..start + end];
+            summary.push_str(&rest[..start]);
+            summary.push_str(&match f.member_path(info, place, path) {
+                Some(member) => f.child_summary(&member, depth + 1),
+                None => format!("<no {path}>"),
+            });
+
+            rest = &rest[start + end + 1..];
+        }
+
+        summary.push_str(rest);
+        Some(summary)
+    }
+
+    fn children(&self, f: &Formatter, info: &TypeInfo, place: &Place) -> Option<Vec<Child>> {
+        let (pointer, length) = self.elements.as_ref()?;
+
+        let pointer = f.member_path(info, place, pointer)?;
+        let length = f.member_path(info, place, length)?;
+
+        let (address, element) = f.find_pointer(&f.type_info(pointer.ty)?, &pointer.place)?;
+        let length = f.unsigned(&f.type_info(length.ty)?, &length.place)?;
+
+        Some(f.elements(element?, address, length))
+    }
+}
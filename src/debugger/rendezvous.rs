@@ -0,0 +1,209 @@
+use super::Debugee;
+
+//see <elf.h> and <link.h>
+const AT_NULL: u64 = 0;
+const AT_PHDR: u64 = 3;
+const AT_PHNUM: u64 = 5;
+const AT_BASE: u64 = 7;
+
+const PT_DYNAMIC: u32 = 2;
+const PT_PHDR: u32 = 6;
+
+const DT_NULL: u64 = 0;
+const DT_DEBUG: u64 = 21;
+
+//enum r_state
+const RT_CONSISTENT: u64 = 0;
+
+#[derive(Clone, Debug)]
+pub enum ModuleEvent {
+    Loaded { path: String, load_bias: u64 },
+    Unloaded { path: String, load_bias: u64 },
+}
+
+/// ld.so's debugger interface. It keeps the list of loaded objects in `_r_debug.r_map` and calls
+/// `r_brk` (`_dl_debug_state`) every time it's about to change and once it's consistent again
+pub struct Rendezvous {
+    /// Address of `_r_debug`, unknown until ld.so initialized it when we started the process ourselves
+    r_debug: Option<u64>,
+    dynamic_section: u64,
+    /// Where the internal breakpoint goes
+    pub breakpoint_address: u64,
+    /// (path, l_addr) of every object in the link map as of the last consistent state
+    loaded: Vec<(String, u64)>,
+    /// False between ld.so announcing a change and finishing it, half mapped libraries
+    /// shouldn't get symbols loaded or breakpoints placed in them
+    pub consistent: bool,
+}
+
+impl Rendezvous {
+    /// Finds the rendezvous of a dynamically linked process, through `DT_DEBUG` if ld.so already
+    /// filled it in, otherwise (right after exec) through the `_dl_debug_state` symbol of ld.so
+    pub fn locate(debugee: &Debugee) -> Option<Self> {
+        let auxv = read_auxv(debugee.pid())?;
+        let dynamic_section = find_dynamic_section(debugee, &auxv)?;
+
+        let mut rendezvous = Self {
+            r_debug: None,
+            dynamic_section,
+            breakpoint_address: 0,
+            loaded: Vec::new(),
+            consistent: true,
+        };
+
+        if let Some(r_debug) = rendezvous.find_r_debug(debugee)
+            && debugee.read_u64(r_debug + 16) != 0
+        {
+            rendezvous.r_debug = Some(r_debug);
+            rendezvous.breakpoint_address = debugee.read_u64(r_debug + 16);
+            //whatever is loaded already doesn't count as an event
+            rendezvous.loaded = rendezvous.read_link_map(debugee);
+            return Some(rendezvous);
+        }
+
+        let interpreter_base = auxv
+            .iter()
+            .find(|(key, _)| *key == AT_BASE)
+            .map(|(_, value)| *value)
+            .filter(|base| *base != 0)?;
+
+        rendezvous.breakpoint_address = debugee
+            .symbols()
+            .module_at(interpreter_base)?
+            .symbols
+            .iter()
+            .find(|s| s.name == "_dl_debug_state")?
+            .address;
+        Some(rendezvous)
+    }
+
+    fn find_r_debug(&self, debugee: &Debugee) -> Option<u64> {
+        //the dynamic section is never anywhere near this long, unreadable memory reads as all ones
+        for i in 0..1024 {
+            let address = self.dynamic_section + i * 16;
+            let tag = debugee.read_u64(address);
+            let value = debugee.read_u64(address + 8);
+
+            match tag {
+                DT_NULL => return None,
+                DT_DEBUG if value != 0 => return Some(value),
+                DT_DEBUG => return None,
+                _ => (),
+            }
+        }
+
+        None
+    }
+
+    /// (l_name, l_addr) of every named object in the link map
+    fn read_link_map(&self, debugee: &Debugee) -> Vec<(String, u64)> {
+        let Some(r_debug) = self.r_debug else {
+            return Vec::new();
+        };
+
+        let mut objects = Vec::new();
+        let mut link_map = debugee.read_u64(r_debug + 8);
+
+        //cap it so a corrupted list can't hang us
+        while link_map != 0 && objects.len() < 4096 {
+            let l_addr = debugee.read_u64(link_map);
+            let l_name = debugee.read_u64(link_map + 8);
+
+            //the main executable has an empty name
+            let name = debugee.read_c_string(l_name, 4096);
+            if !name.is_empty() {
+                objects.push((name, l_addr));
+            }
+
+            link_map = debugee.read_u64(link_map + 24);
+        }
+
+        objects
+    }
+
+    /// Called when the rendezvous breakpoint got hit, returns what changed since the last consistent state
+    pub fn update(&mut self, debugee: &Debugee) -> Vec<ModuleEvent> {
+        if self.r_debug.is_none() {
+            self.r_debug = self.find_r_debug(debugee);
+        }
+
+        let Some(r_debug) = self.r_debug else {
+            return Vec::new();
+        };
+
+        //RT_ADD and RT_DELETE announce a change that hasn't happened yet
+        self.consistent = debugee.read_u64(r_debug + 24) & 0xffff_ffff == RT_CONSISTENT;
+        if !self.consistent {
+            return Vec::new();
+        }
+
+        let current = self.read_link_map(debugee);
+
+        let unloaded = self
+            .loaded
+            .iter()
+            .filter(|object| !current.contains(object))
+            .map(|(path, load_bias)| ModuleEvent::Unloaded {
+                path: path.clone(),
+                load_bias: *load_bias,
+            });
+
+        let loaded = current
+            .iter()
+            .filter(|object| !self.loaded.contains(object))
+            .map(|(path, load_bias)| ModuleEvent::Loaded {
+                path: path.clone(),
+                load_bias: *load_bias,
+            });
+
+        let events = unloaded.chain(loaded).collect();
+        self.loaded = current;
+        events
+    }
+}
+
+fn read_auxv(pid: u32) -> Option<Vec<(u64, u64)>> {
+    let data = std::fs::read(format!("/proc/{pid}/auxv")).ok()?;
+
+    Some(
+        data.chunks_exact(16)
+            .map(|pair| {
+                (
+                    u64::from_le_bytes(pair[..8].try_into().unwrap()),
+                    u64::from_le_bytes(pair[8..].try_into().unwrap()),
+                )
+            })
+            .take_while(|(key, _)| *key != AT_NULL)
+            .collect(),
+    )
+}
+
+/// Runtime address of the executable's `PT_DYNAMIC`, found through the program headers in memory
+fn find_dynamic_section(debugee: &Debugee, auxv: &[(u64, u64)]) -> Option<u64> {
+    let aux = |key| auxv.iter().find(|(k, _)| *k == key).map(|(_, v)| *v);
+
+    let phdr = aux(AT_PHDR)?;
+    let phnum = aux(AT_PHNUM)?;
+
+    //Elf64_Phdr is 56 bytes: p_type (4), p_flags (4), p_offset, p_vaddr, ...
+    let headers = debugee.read_memory(phdr as usize, phnum as usize * 56);
+    let headers = headers
+        .chunks_exact(56)
+        .map(|header| {
+            (
+                u32::from_le_bytes(header[..4].try_into().unwrap()),
+                u64::from_le_bytes(header[16..24].try_into().unwrap()),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let load_bias = match headers.iter().find(|(p_type, _)| *p_type == PT_PHDR) {
+        Some((_, p_vaddr)) => phdr.wrapping_sub(*p_vaddr),
+        None => 0,
+    };
+
+    headers
+        .iter()
+        .find(|(p_type, _)| *p_type == PT_DYNAMIC)
+        .map(|(_, p_vaddr)| p_vaddr.wrapping_add(load_bias))
+}
@@ -0,0 +1,712 @@
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::error::Error;
+use std::io::Read;
+use std::ops::Range;
+
+use object::{
+    Object, ObjectSection, ObjectSegment, ObjectSymbol, ObjectSymbolTable, RelocationKind,
+    RelocationTarget, SymbolKind,
+};
+
+use super::debug_info::{self, DebugInfoSettings};
+use super::dwarf::{DwarfInfo, LineTable, SourceLocation};
+use super::memory_map::MemoryMap;
+use super::unwind::CallFrameInfo;
+
+/// How symbol names get displayed
+#[derive(Clone, Copy, PartialEq)]
+pub struct DemangleSettings {
+    /// Show `alloc::vec::Vec<T>::push` instead of `_ZN5alloc3vec16Vec$LT$T$GT$4push17h...E`
+    pub demangle: bool,
+    /// Drop the `::h0123456789abcdef` suffix of legacy Rust symbols (and crate disambiguators of v0 ones)
+    pub hide_hash: bool,
+}
+
+impl Default for DemangleSettings {
+    fn default() -> Self {
+        Self {
+            demangle: true,
+            hide_hash: true,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Symbol {
+    /// As found in the ELF, possibly mangled
+    pub name: String,
+    /// Full demangled name, None if `name` isn't mangled
+    pub demangled: Option<String>,
+    /// Runtime address, load bias already applied
+    pub address: u64,
+    pub size: u64,
+    pub is_function: bool,
+}
+
+impl Symbol {
+    pub fn new(name: String, address: u64, size: u64, is_function: bool) -> Self {
+        Self {
+            demangled: demangle(&name),
+            name,
+            address,
+            size,
+            is_function,
+        }
+    }
+
+    pub fn display_name(&self, settings: DemangleSettings) -> Cow<'_, str> {
+        display_name(&self.name, self.demangled.as_deref(), settings)
+    }
+
+    /// Whether `name` refers to this symbol: verbatim, demangled, demangled without the hash
+    /// or, for C++, demangled without the parameter list
+    pub fn matches(&self, name: &str) -> bool {
+        if self.name == name {
+            return true;
+        }
+
+        self.demangled.as_ref().is_some_and(|demangled| {
+            demangled == name
+                || strip_hash(demangled) == name
+                || demangled
+                    .split_once('(')
+                    .is_some_and(|(path, _)| path == name)
+        })
+    }
+}
+
+fn display_name<'a>(
+    name: &'a str,
+    demangled: Option<&'a str>,
+    settings: DemangleSettings,
+) -> Cow<'a, str> {
+    match demangled {
+        Some(demangled) if settings.demangle && settings.hide_hash => {
+            Cow::Owned(strip_hash(demangled))
+        }
+        Some(demangled) if settings.demangle => Cow::Borrowed(demangled),
+        _ => Cow::Borrowed(name),
+    }
+}
+
+/// Rust (legacy and v0) or Itanium C++ demangling, `@plt`-style suffixes are kept as they are
+fn demangle(name: &str) -> Option<String> {
+    let (mangled, suffix) = match name.split_once('@') {
+        Some((mangled, suffix)) => (mangled, Some(suffix)),
+        None => (name, None),
+    };
+
+    let demangled = if let Ok(demangled) = rustc_demangle::try_demangle(mangled) {
+        demangled.to_string()
+    } else if mangled.starts_with("_Z") {
+        cpp_demangle::Symbol::new(mangled)
+            .ok()?
+            .demangle(&cpp_demangle::DemangleOptions::default())
+            .ok()?
+    } else {
+        return None;
+    };
+
+    Some(match suffix {
+        Some(suffix) => format!("{demangled}@{suffix}"),
+        None => demangled,
+    })
+}
+
+/// `core::fmt::write::h0123456789abcdef` -> `core::fmt::write`, for v0 symbols
+/// `core[c1f1a4ba060b9bfa]::fmt::write` -> `core::fmt::write`
+fn strip_hash(demangled: &str) -> String {
+    let (path, suffix) = match demangled.split_once('@') {
+        Some((path, suffix)) => (path, Some(suffix)),
+        None => (demangled, None),
+    };
+
+    let path = match path.rsplit_once("::h") {
+        Some((stripped, hash))
+            if hash.len() == 16 && hash.bytes().all(|b| b.is_ascii_hexdigit()) =>
+        {
+            stripped
+        }
+        _ => path,
+    };
+
+    let mut stripped = String::with_capacity(path.len());
+    let mut rest = path;
+
+    while let Some(start) = rest.find('[') {
+        let (before, after) = rest.split_at(start);
+        stripped.push_str(before);
+
+        //a disambiguator sticks to the crate name, slices and arrays never follow an identifier
+        let is_disambiguator = before
+            .chars()
+            .last()
+            .is_some_and(|c| c.is_alphanumeric() || c == '_')
+            && after[1..].find(']').is_some_and(|end| {
+                (8..=16).contains(&end) && after[1..=end].bytes().all(|b| b.is_ascii_hexdigit())
+            });
+
+        if is_disambiguator {
+            rest = &after[after.find(']').unwrap() + 1..];
+        } else {
+            stripped.push('[');
+            rest = &after[1..];
+        }
+    }
+    stripped.push_str(rest);
+
+    match suffix {
+        Some(suffix) => format!("{stripped}@{suffix}"),
+        None => stripped,
+    }
+}
+
+/// A symbol some other module is expected to provide, through a GOT slot
+#[derive(Clone, Debug)]
+pub struct Import {
+    pub name: String,
+    pub demangled: Option<String>,
+    /// Runtime address of the GOT slot the dynamic linker fills in
+    pub got_address: u64,
+    /// Runtime address of the PLT stub jumping through the slot, if calls go through one
+    pub plt_address: Option<u64>,
+    /// R_X86_64_JUMP_SLOT, which ld.so may bind lazily on the first call
+    pub jump_slot: bool,
+}
+
+impl Import {
+    pub fn display_name(&self, settings: DemangleSettings) -> Cow<'_, str> {
+        display_name(&self.name, self.demangled.as_deref(), settings)
+    }
+}
+
+/// A PT_LOAD program header, which part of the file got mapped where
+#[derive(Clone, Copy, Debug)]
+pub struct LoadSegment {
+    /// Runtime address
+    pub address: u64,
+    pub file_offset: u64,
+    /// Bytes that come from the file, the rest of the segment (.bss) is zero filled
+    pub file_size: u64,
+}
+
+pub struct Module {
+    pub path: String,
+    pub base: u64,
+    pub size: u64,
+    /// Sorted by address, includes `name@plt` and `name@got` for imports
+    pub symbols: Vec<Symbol>,
+    /// Symbols defined in the dynamic symbol table, sorted by address
+    pub exports: Vec<Symbol>,
+    pub imports: Vec<Import>,
+    /// Where the DWARF comes from, the module itself unless it was stripped
+    pub debug_file: Option<String>,
+    pub dwarf: Option<DwarfInfo>,
+    pub call_frames: Option<CallFrameInfo>,
+    pub segments: Vec<LoadSegment>,
+}
+
+/// Regular file starting with the ELF magic
+fn is_elf(path: &str) -> std::io::Result<bool> {
+    //opening a fifo would block
+    if !std::fs::metadata(path)?.is_file() {
+        return Ok(false);
+    }
+
+    let mut file = std::fs::File::open(path)?;
+
+    let mut magic = [0; 4];
+    match file.read_exact(&mut magic) {
+        Ok(()) => Ok(&magic == b"\x7fELF"),
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+impl Module {
+    pub fn load(
+        path: &str,
+        base: u64,
+        size: u64,
+        settings: &DebugInfoSettings,
+    ) -> Result<Self, Box<dyn Error>> {
+        //mapped device memory or big data files shouldn't be read whole just to be rejected
+        if path.starts_with("/dev/") || !is_elf(path)? {
+            return Err(format!("{path} isn't an ELF file").into());
+        }
+
+        let data = std::fs::read(path)?;
+        let file = object::File::parse(&*data)?;
+
+        let debug_file = debug_info::find_debug_file(path, &file, settings);
+        let debug_data = match &debug_file {
+            Some(debug_file) if debug_file != path => std::fs::read(debug_file).ok(),
+            _ => None,
+        };
+        let debug = debug_data
+            .as_deref()
+            .and_then(|data| object::File::parse(data).ok());
+
+        //difference between the addresses in the file and where it actually got mapped (PIE, ASLR),
+        //the lowest PT_LOAD is what ends up at the base
+        let first_segment = file
+            .segments()
+            .map(|s| s.address())
+            .min()
+            .ok_or("ELF has no loadable segments")?;
+        let load_bias = base.wrapping_sub(first_segment & !0xfff);
+
+        let segments = file
+            .segments()
+            .map(|segment| {
+                let (file_offset, file_size) = segment.file_range();
+                LoadSegment {
+                    address: segment.address().wrapping_add(load_bias),
+                    file_offset,
+                    file_size,
+                }
+            })
+            .collect();
+
+        let imports = read_imports(&file, load_bias);
+
+        //stripped modules only have their dynamic symbols left, the full table is in the debug file
+        let mut symbols = file
+            .symbols()
+            .chain(file.dynamic_symbols())
+            .chain(debug.iter().flat_map(|debug| debug.symbols()))
+            .filter(|s| s.is_definition() && s.address() != 0)
+            .filter(|s| matches!(s.kind(), SymbolKind::Text | SymbolKind::Data))
+            .filter_map(|s| {
+                Some(Symbol::new(
+                    s.name().ok()?.to_owned(),
+                    s.address().wrapping_add(load_bias),
+                    s.size(),
+                    s.kind() == SymbolKind::Text,
+                ))
+            })
+            .collect::<Vec<_>>();
+
+        for import in &imports {
+            if let Some(plt_address) = import.plt_address {
+                symbols.push(Symbol::new(
+                    format!("{}@plt", import.name),
+                    plt_address,
+                    16,
+                    true,
+                ));
+            }
+
+            symbols.push(Symbol::new(
+                format!("{}@got", import.name),
+                import.got_address,
+                8,
+                false,
+            ));
+        }
+
+        symbols.sort_by_key(|s| s.address);
+        symbols.dedup_by(|a, b| a.address == b.address && a.name == b.name);
+
+        let mut exports = file
+            .dynamic_symbols()
+            .filter(|s| s.is_definition() && s.is_global() && s.address() != 0)
+            .filter_map(|s| {
+                Some(Symbol::new(
+                    s.name().ok()?.to_owned(),
+                    s.address().wrapping_add(load_bias),
+                    s.size(),
+                    s.kind() == SymbolKind::Text,
+                ))
+            })
+            .collect::<Vec<_>>();
+        exports.sort_by_key(|s| s.address);
+
+        let dwarf_file = match &debug_file {
+            Some(debug_file) if debug_file == path => Some(&file),
+            _ => debug.as_ref(),
+        };
+        //types and variables are still there without a line program, only source lookups go
+        let dwarf = dwarf_file
+            .and_then(|dwarf_file| DwarfInfo::load(dwarf_file, load_bias).ok())
+            .filter(|dwarf| !dwarf.units.is_empty());
+        let call_frames = CallFrameInfo::load(&file, debug.as_ref(), load_bias);
+
+        Ok(Self {
+            path: path.to_owned(),
+            base,
+            size,
+            symbols,
+            exports,
+            imports,
+            debug_file,
+            dwarf,
+            call_frames,
+            segments,
+        })
+    }
+
+    /// None without a line program
+    pub fn lines(&self) -> Option<&LineTable> {
+        self.dwarf
+            .as_ref()
+            .map(|dwarf| &dwarf.lines)
+            .filter(|lines| !lines.is_empty())
+    }
+
+    pub fn name(&self) -> &str {
+        self.path.rsplit('/').next().unwrap_or(&self.path)
+    }
+
+    /// Where the byte at `address` is in the file, None for ones that aren't backed by it
+    pub fn file_offset(&self, address: u64) -> Option<u64> {
+        self.segments
+            .iter()
+            .find(|segment| {
+                address >= segment.address && address < segment.address + segment.file_size
+            })
+            .map(|segment| segment.file_offset + (address - segment.address))
+    }
+
+    pub const fn contains(&self, address: u64) -> bool {
+        address >= self.base && address < self.base + self.size
+    }
+
+    /// `libssl.so` matches `libssl.so.3`, version suffixes are optional
+    pub fn matches(&self, name: &str) -> bool {
+        let file_name = self.name();
+        file_name == name
+            || file_name
+                .strip_prefix(name)
+                .is_some_and(|rest| rest.starts_with('.') || rest.starts_with('-'))
+    }
+
+    /// Closest symbol at or before `address`, along with the offset into it
+    pub fn symbol_at(&self, address: u64) -> Option<(&Symbol, u64)> {
+        let index = self
+            .symbols
+            .partition_point(|s| s.address <= address)
+            .checked_sub(1)?;
+        let symbol = &self.symbols[index];
+        let offset = address - symbol.address;
+
+        //sizeless symbols (mostly hand written asm) get the benefit of the doubt
+        if symbol.size != 0 && offset >= symbol.size {
+            return None;
+        }
+
+        Some((symbol, offset))
+    }
+}
+
+/// Imports from the GOT relocations in .rela.plt and .rela.dyn, with their PLT stubs found by
+/// looking for `jmp [rip+slot]` in the PLT sections. Data relocations (R_X86_64_64, COPY)
+/// reference symbols too but aren't imports
+fn read_imports(file: &object::File, load_bias: u64) -> Vec<Import> {
+    let (Some(relocations), Some(dynamic_symbols)) =
+        (file.dynamic_relocations(), file.dynamic_symbol_table())
+    else {
+        return Vec::new();
+    };
+
+    let mut imports = relocations
+        .filter_map(|(offset, relocation)| {
+            let jump_slot = match relocation.kind() {
+                RelocationKind::Elf(object::elf::R_X86_64_JUMP_SLOT) => true,
+                RelocationKind::Elf(object::elf::R_X86_64_GLOB_DAT) => false,
+                _ => return None,
+            };
+            let RelocationTarget::Symbol(index) = relocation.target() else {
+                return None;
+            };
+            let name = dynamic_symbols.symbol_by_index(index).ok()?.name().ok()?;
+
+            (!name.is_empty()).then(|| Import {
+                name: name.to_owned(),
+                demangled: demangle(name),
+                got_address: offset.wrapping_add(load_bias),
+                plt_address: None,
+                jump_slot,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    //.plt.sec holds the actual stubs when IBT is on, .plt.got the ones for eagerly bound slots
+    for section in file
+        .sections()
+        .filter(|s| matches!(s.name(), Ok(".plt" | ".plt.sec" | ".plt.got")))
+    {
+        let Ok(data) = section.data() else {
+            continue;
+        };
+
+        let decoder = iced_x86::Decoder::with_ip(
+            64,
+            data,
+            section.address().wrapping_add(load_bias),
+            iced_x86::DecoderOptions::NONE,
+        );
+
+        for instruction in decoder {
+            if instruction.flow_control() != iced_x86::FlowControl::IndirectBranch
+                || !instruction.is_ip_rel_memory_operand()
+            {
+                continue;
+            }
+
+            let slot = instruction.ip_rel_memory_address();
+            if let Some(import) = imports.iter_mut().find(|i| i.got_address == slot) {
+                //stubs are 16 bytes, the jump sits after an endbr64 with IBT
+                import.plt_address = Some(instruction.ip() & !0xf);
+            }
+        }
+    }
+
+    imports
+}
+
+#[derive(Default)]
+pub struct SymbolTable {
+    modules: Vec<Module>,
+    //(path, base) pairs that aren't ELFs or failed to parse, so we don't retry them on every stop
+    unloadable: HashSet<(String, u64)>,
+    /// Bumped whenever modules come or go, lets views know their copies are stale
+    generation: u64,
+    pub demangle: DemangleSettings,
+    pub debug_info: DebugInfoSettings,
+}
+
+impl SymbolTable {
+    /// Loads symbols of newly mapped files and forgets unmapped ones, returns whether anything changed
+    pub fn update(&mut self, memory_map: &MemoryMap) -> bool {
+        let mapped = memory_map.modules();
+
+        let count = self.modules.len();
+        self.modules.retain(|m| {
+            mapped
+                .iter()
+                .any(|(path, base, _)| *path == m.path && *base == m.base)
+        });
+        let mut changed = count != self.modules.len();
+
+        for (path, base, size) in mapped {
+            //ld.so maps segments one by one, so the size can still grow after the first sighting
+            if let Some(module) = self
+                .modules
+                .iter_mut()
+                .find(|m| m.path == path && m.base == base)
+            {
+                module.size = size;
+                continue;
+            }
+
+            if self.unloadable.contains(&(path.to_owned(), base)) {
+                continue;
+            }
+
+            match Module::load(path, base, size, &self.debug_info) {
+                Ok(module) => {
+                    self.modules.push(module);
+                    changed = true;
+                }
+                Err(_) => {
+                    self.unloadable.insert((path.to_owned(), base));
+                }
+            }
+        }
+
+        if changed {
+            self.generation += 1;
+        }
+
+        changed
+    }
+
+    pub fn clear(&mut self) {
+        self.modules.clear();
+        self.unloadable.clear();
+        self.generation += 1;
+    }
+
+    pub fn modules(&self) -> &[Module] {
+        &self.modules
+    }
+
+    pub const fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn module_at(&self, address: u64) -> Option<&Module> {
+        self.modules.iter().find(|m| m.contains(address))
+    }
+
+    /// `module!symbol+0x12`
+    pub fn symbolize(&self, address: u64) -> Option<String> {
+        let module = self.module_at(address)?;
+        let (symbol, offset) = module.symbol_at(address)?;
+        let name = symbol.display_name(self.demangle);
+
+        Some(if offset == 0 {
+            format!("{}!{name}", module.name())
+        } else {
+            format!("{}!{name}+{offset:#x}", module.name())
+        })
+    }
+
+    pub fn source_location(&self, address: u64) -> Option<SourceLocation<'_>> {
+        self.module_at(address)?.lines()?.location(address)
+    }
+
+    pub fn line_range(&self, address: u64) -> Option<Range<u64>> {
+        self.module_at(address)?.lines()?.line_range(address)
+    }
+
+    pub fn is_statement_start(&self, address: u64) -> bool {
+        self.module_at(address)
+            .and_then(|m| m.lines())
+            .is_some_and(|lines| lines.is_statement_start(address))
+    }
+
+    /// Every source file any module has line info for, sorted
+    pub fn source_files(&self) -> Vec<&str> {
+        let mut files = self
+            .modules
+            .iter()
+            .filter_map(|m| m.lines())
+            .flat_map(|lines| lines.files().iter().map(String::as_str))
+            .collect::<Vec<_>>();
+
+        files.sort_unstable();
+        files.dedup();
+        files
+    }
+
+    /// Lines of `path` that have code in any module
+    pub fn source_lines(&self, path: &str) -> HashSet<u32> {
+        self.modules
+            .iter()
+            .filter_map(|m| m.lines())
+            .flat_map(|lines| lines.lines(path))
+            .collect()
+    }
+
+    /// Addresses a breakpoint on `path:line` goes to, in every module the file was compiled into.
+    /// Lines without code resolve to the next one that has some, which is returned along with them
+    pub fn line_addresses(&self, path: &str, line: u32) -> Option<(u32, Vec<u64>)> {
+        let resolved = self
+            .modules
+            .iter()
+            .filter_map(|m| m.lines()?.addresses(path, line))
+            .collect::<Vec<_>>();
+
+        let line = resolved.iter().map(|(line, _)| *line).min()?;
+        let addresses = resolved
+            .into_iter()
+            .filter(|(l, _)| *l == line)
+            .flat_map(|(_, addresses)| addresses)
+            .collect();
+
+        Some((line, addresses))
+    }
+
+    /// Every symbol called `name`, which can be qualified with a module (`libc.so.6!puts`)
+    pub fn resolve(&self, name: &str) -> Vec<&Symbol> {
+        let (module_name, symbol_name) = match name.split_once('!') {
+            Some((module, symbol)) => (Some(module), symbol),
+            None => (None, name),
+        };
+
+        self.modules
+            .iter()
+            .filter(|m| module_name.is_none_or(|name| m.matches(name)))
+            .flat_map(|m| m.symbols.iter())
+            .filter(|s| s.matches(symbol_name))
+            .collect()
+    }
+
+    /// Every function whose (demangled) name matches `regex`
+    pub fn resolve_regex(&self, regex: &regex::Regex) -> Vec<&Symbol> {
+        self.modules
+            .iter()
+            .flat_map(|m| m.symbols.iter())
+            .filter(|s| s.is_function)
+            .filter(|s| {
+                regex.is_match(&s.name)
+                    || s.demangled.as_ref().is_some_and(|demangled| {
+                        regex.is_match(demangled) || regex.is_match(&strip_hash(demangled))
+                    })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: u64 = 0x5555_5555_4000;
+
+    /// Laid out like a small PIE: headers, text, rodata, then data with .bss after it
+    fn module() -> Module {
+        let segment = |address: u64, file_offset: u64, file_size: u64| LoadSegment {
+            address: BASE + address,
+            file_offset,
+            file_size,
+        };
+
+        Module {
+            path: String::from("/usr/lib/libstdc++.so.6.0.33"),
+            base: BASE,
+            size: 0x5000,
+            symbols: Vec::new(),
+            exports: Vec::new(),
+            imports: Vec::new(),
+            debug_file: None,
+            dwarf: None,
+            call_frames: None,
+            segments: vec![
+                segment(0, 0, 0x628),
+                segment(0x1000, 0x1000, 0x1bd),
+                segment(0x2000, 0x2000, 0x104),
+                segment(0x3db8, 0x2db8, 0x258),
+            ],
+        }
+    }
+
+    #[test]
+    fn file_offset_across_segments() {
+        let module = module();
+
+        assert_eq!(module.file_offset(BASE), Some(0));
+        assert_eq!(module.file_offset(BASE + 0x627), Some(0x627));
+        assert_eq!(module.file_offset(BASE + 0x1139), Some(0x1139));
+        assert_eq!(module.file_offset(BASE + 0x2010), Some(0x2010));
+        //data is mapped a page further than where it is in the file
+        assert_eq!(module.file_offset(BASE + 0x3db8), Some(0x2db8));
+        assert_eq!(module.file_offset(BASE + 0x400f), Some(0x300f));
+    }
+
+    #[test]
+    fn file_offset_outside_of_the_file() {
+        let module = module();
+
+        //padding between segments
+        assert_eq!(module.file_offset(BASE + 0x628), None);
+        //.bss
+        assert_eq!(module.file_offset(BASE + 0x4010), None);
+        assert_eq!(module.file_offset(BASE - 1), None);
+    }
+
+    #[test]
+    fn name_matching() {
+        let module = module();
+
+        assert_eq!(module.name(), "libstdc++.so.6.0.33");
+        assert!(module.matches("libstdc++.so.6"));
+        assert!(module.matches("libstdc++.so"));
+        assert!(!module.matches("libstdc"));
+        assert!(module.contains(BASE + 0x4fff));
+        assert!(!module.contains(BASE + 0x5000));
+    }
+}
@@ -0,0 +1,371 @@
+use gimli::UnwindSection;
+use object::{Object, ObjectSection};
+
+use super::dwarf::{self, Reader};
+use super::symbols::SymbolTable;
+use super::variables::Memory;
+
+/// DWARF number of the return address column, which is rip as far as the current frame goes
+pub const RETURN_ADDRESS: u16 = 16;
+const RBP: u16 = 6;
+const RSP: u16 = 7;
+/// rbx, rbp and r12-r15, which a callee has to give back untouched
+const CALLEE_SAVED: [u16; 6] = [3, 6, 12, 13, 14, 15];
+//unwinding gives up after this many frames, in case of a corrupted stack that loops
+const MAX_FRAMES: usize = 256;
+
+/// `mov rax, 15 (rt_sigreturn); syscall`, what a signal handler returns into
+const SIGRETURN: [u8; 9] = [0x48, 0xc7, 0xc0, 0x0f, 0x00, 0x00, 0x00, 0x0f, 0x05];
+/// Offset of `uc_mcontext` in the `ucontext_t` rsp points at once the handler returned
+const MCONTEXT_OFFSET: u64 = 40;
+/// DWARF register numbers in the order `struct sigcontext` saves them, from r8 to rip
+const SIGCONTEXT_REGISTERS: [u16; 17] = [8, 9, 10, 11, 12, 13, 14, 15, 5, 4, 6, 3, 1, 0, 2, 7, 16];
+
+/// Register values of a stack frame, indexed by DWARF register number
+/// (rax, rdx, rcx, rbx, rsi, rdi, rbp, rsp, r8-r15, return address)
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct FrameRegisters {
+    values: [Option<u64>; 17],
+    /// The pc is a return address, one past the call that's still running
+    is_caller: bool,
+}
+
+impl FrameRegisters {
+    pub fn from_context(ctx: &libc::user_regs_struct) -> Self {
+        Self {
+            values: [
+                ctx.rax, ctx.rdx, ctx.rcx, ctx.rbx, ctx.rsi, ctx.rdi, ctx.rbp, ctx.rsp, ctx.r8,
+                ctx.r9, ctx.r10, ctx.r11, ctx.r12, ctx.r13, ctx.r14, ctx.r15, ctx.rip,
+            ]
+            .map(Some),
+            is_caller: false,
+        }
+    }
+
+    pub fn get(&self, register: u16) -> Option<u64> {
+        self.values.get(register as usize).copied().flatten()
+    }
+
+    pub fn pc(&self) -> u64 {
+        self.values[RETURN_ADDRESS as usize].unwrap_or(0)
+    }
+
+    pub fn sp(&self) -> Option<u64> {
+        self.get(RSP)
+    }
+
+    pub fn bp(&self) -> Option<u64> {
+        self.get(RBP)
+    }
+
+    /// Address to look up symbols, lines and unwind info of the frame with.
+    /// For callers that's inside the call instruction, the return address might already belong to
+    /// the next line or even function
+    pub fn location(&self) -> u64 {
+        if self.is_caller {
+            self.pc().wrapping_sub(1)
+        } else {
+            self.pc()
+        }
+    }
+
+    fn set(&mut self, register: u16, value: Option<u64>) {
+        if let Some(slot) = self.values.get_mut(register as usize) {
+            *slot = value;
+        }
+    }
+}
+
+/// How the registers of a frame were recovered
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum UnwindMethod {
+    /// The innermost frame, registers are the thread's own
+    Context,
+    CallFrameInfo,
+    /// Saved rbp and return address right above where rbp points
+    FramePointer,
+    /// The frame got interrupted by a signal, registers are the ones the kernel saved
+    Signal,
+}
+
+#[derive(Clone, Debug)]
+pub struct StackFrame {
+    pub registers: FrameRegisters,
+    pub method: UnwindMethod,
+}
+
+/// Walks the stack up from `registers`, innermost frame first.
+/// Call frame info is preferred, frames without any are unwound through the rbp chain
+pub fn backtrace(
+    symbols: &SymbolTable,
+    registers: FrameRegisters,
+    memory: &dyn Memory,
+) -> Vec<StackFrame> {
+    let mut frames = vec![StackFrame {
+        registers,
+        method: UnwindMethod::Context,
+    }];
+
+    while frames.len() < MAX_FRAMES {
+        let current = &frames[frames.len() - 1].registers;
+
+        let caller = if is_sigreturn(current, memory) {
+            signal_frame(current, memory).map(|registers| (registers, UnwindMethod::Signal))
+        } else {
+            symbols
+                .module_at(current.location())
+                .and_then(|module| module.call_frames.as_ref())
+                .and_then(|call_frames| call_frames.unwind(current, memory))
+                .map(|registers| (registers, UnwindMethod::CallFrameInfo))
+                .or_else(|| {
+                    frame_pointer(current, memory)
+                        .map(|registers| (registers, UnwindMethod::FramePointer))
+                })
+        };
+
+        let Some((mut registers, method)) = caller else {
+            break;
+        };
+
+        //the stack grows down, a caller that isn't above its callee means garbage.
+        //signal handlers can run on an alternate stack, so those are let through
+        let rsp = current.get(RSP).unwrap_or(0);
+        let progressed = registers
+            .get(RSP)
+            .is_some_and(|caller_rsp| caller_rsp > rsp);
+        if registers.pc() == 0 || (method != UnwindMethod::Signal && !progressed) {
+            break;
+        }
+
+        //returning into the trampoline isn't a call, and neither is getting interrupted
+        registers.is_caller = method != UnwindMethod::Signal && !is_sigreturn(&registers, memory);
+        frames.push(StackFrame { registers, method });
+    }
+
+    frames
+}
+
+fn is_sigreturn(registers: &FrameRegisters, memory: &dyn Memory) -> bool {
+    memory
+        .read_bytes(registers.pc(), SIGRETURN.len())
+        .is_some_and(|code| code == SIGRETURN)
+}
+
+/// Registers the kernel saved in the signal frame when it ran the handler that just returned
+fn signal_frame(registers: &FrameRegisters, memory: &dyn Memory) -> Option<FrameRegisters> {
+    let mcontext = registers.get(RSP)?.wrapping_add(MCONTEXT_OFFSET);
+    let bytes = memory.read_bytes(mcontext, SIGCONTEXT_REGISTERS.len() * 8)?;
+
+    let mut caller = FrameRegisters::default();
+    for (register, value) in SIGCONTEXT_REGISTERS.iter().zip(bytes.chunks_exact(8)) {
+        caller.set(
+            *register,
+            Some(u64::from_le_bytes(value.try_into().unwrap())),
+        );
+    }
+
+    Some(caller)
+}
+
+/// `push rbp; mov rbp, rsp` prologues leave the caller's rbp at [rbp] and the return address above
+fn frame_pointer(registers: &FrameRegisters, memory: &dyn Memory) -> Option<FrameRegisters> {
+    let rsp = registers.sp()?;
+    let rbp = registers.bp()?;
+    let read = |address: u64| {
+        memory
+            .read_bytes(address, 8)
+            .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+    };
+
+    //until the prologue ran rbp still belongs to the caller, only the innermost frame can be in it
+    let (saved_rbp, return_address, caller_rsp) = match prologue(registers, memory) {
+        Prologue::Entry => (rbp, read(rsp)?, rsp + 8),
+        Prologue::Pushed => (read(rsp)?, read(rsp + 8)?, rsp + 16),
+        Prologue::Done => {
+            if rbp == 0 || rbp % 8 != 0 || rbp < rsp {
+                return None;
+            }
+            (read(rbp)?, read(rbp + 8)?, rbp + 16)
+        }
+    };
+
+    let mut caller = *registers;
+    for register in 0..RETURN_ADDRESS {
+        if !CALLEE_SAVED.contains(&register) {
+            caller.set(register, None);
+        }
+    }
+    caller.set(RBP, Some(saved_rbp));
+    caller.set(RETURN_ADDRESS, Some(return_address));
+    caller.set(RSP, Some(caller_rsp));
+
+    Some(caller)
+}
+
+enum Prologue {
+    /// Nothing ran yet, the return address is at [rsp]
+    Entry,
+    /// Past `push rbp`, but rbp wasn't set up yet
+    Pushed,
+    Done,
+}
+
+fn prologue(registers: &FrameRegisters, memory: &dyn Memory) -> Prologue {
+    const ENDBR64: [u8; 4] = [0xf3, 0x0f, 0x1e, 0xfa];
+    const PUSH_RBP: u8 = 0x55;
+    const MOV_RBP_RSP: [u8; 3] = [0x48, 0x89, 0xe5];
+
+    if registers.is_caller {
+        return Prologue::Done;
+    }
+
+    let pc = registers.pc();
+    let Some(code) = memory.read_bytes(pc, 8) else {
+        return Prologue::Done;
+    };
+    let code = code.strip_prefix(&ENDBR64).unwrap_or(&code);
+
+    if code.starts_with(&[PUSH_RBP, MOV_RBP_RSP[0], MOV_RBP_RSP[1], MOV_RBP_RSP[2]]) {
+        Prologue::Entry
+    } else if code.starts_with(&MOV_RBP_RSP)
+        && memory.read_bytes(pc.wrapping_sub(1), 1) == Some(vec![PUSH_RBP])
+    {
+        Prologue::Pushed
+    } else {
+        Prologue::Done
+    }
+}
+
+/// `.eh_frame` and `.debug_frame` of a module, describing how each function lays out its frame
+pub struct CallFrameInfo {
+    eh_frame: Option<(gimli::EhFrame<Reader>, gimli::BaseAddresses)>,
+    debug_frame: Option<gimli::DebugFrame<Reader>>,
+    load_bias: u64,
+}
+
+impl CallFrameInfo {
+    /// `.eh_frame` is loaded from the module itself, `.debug_frame` from wherever it's found,
+    /// stripped modules usually only have it in their debug file
+    pub fn load(file: &object::File, debug: Option<&object::File>, load_bias: u64) -> Option<Self> {
+        let endian = dwarf::endianness(file);
+
+        let eh_frame = file.section_by_name(".eh_frame").map(|section| {
+            let mut bases = gimli::BaseAddresses::default().set_eh_frame(section.address());
+            if let Some(text) = file.section_by_name(".text") {
+                bases = bases.set_text(text.address());
+            }
+            if let Some(got) = file.section_by_name(".got") {
+                bases = bases.set_got(got.address());
+            }
+
+            let mut eh_frame =
+                gimli::EhFrame::from(dwarf::section_reader(file, ".eh_frame", endian));
+            eh_frame.set_address_size(8);
+            (eh_frame, bases)
+        });
+
+        let debug_frame = [Some(file), debug]
+            .into_iter()
+            .flatten()
+            .map(|file| dwarf::section_reader(file, ".debug_frame", endian))
+            .find(|reader| !reader.is_empty())
+            .map(|reader| {
+                let mut debug_frame = gimli::DebugFrame::from(reader);
+                debug_frame.set_address_size(8);
+                debug_frame
+            });
+
+        (eh_frame.is_some() || debug_frame.is_some()).then_some(Self {
+            eh_frame,
+            debug_frame,
+            load_bias,
+        })
+    }
+
+    /// Canonical frame address (rsp right before the call) of the frame `registers` belong to
+    pub fn cfa(&self, registers: &FrameRegisters) -> Option<u64> {
+        let row = self.row(registers)?;
+        Self::cfa_of(&row, registers)
+    }
+
+    /// Registers of the frame that called the one `registers` belong to, the ones the callee
+    /// clobbered without saving are unknown
+    pub fn unwind(
+        &self,
+        registers: &FrameRegisters,
+        memory: &dyn Memory,
+    ) -> Option<FrameRegisters> {
+        let row = self.row(registers)?;
+        let cfa = Self::cfa_of(&row, registers)?;
+
+        let mut caller = *registers;
+        for register in 0..=RETURN_ADDRESS {
+            if !CALLEE_SAVED.contains(&register) {
+                caller.set(register, None);
+            }
+        }
+        caller.set(RSP, Some(cfa));
+
+        let read = |address: u64| {
+            memory
+                .read_bytes(address, 8)
+                .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+        };
+
+        for (register, rule) in row.registers() {
+            let value = match rule {
+                gimli::RegisterRule::SameValue => registers.get(register.0),
+                gimli::RegisterRule::Offset(offset) => read(cfa.wrapping_add_signed(*offset)),
+                gimli::RegisterRule::ValOffset(offset) => Some(cfa.wrapping_add_signed(*offset)),
+                gimli::RegisterRule::Register(other) => registers.get(other.0),
+                gimli::RegisterRule::Constant(value) => Some(*value),
+                //expressions only show up in signal trampolines, which get handled separately
+                _ => None,
+            };
+            caller.set(register.0, value);
+        }
+
+        Some(caller)
+    }
+
+    fn row(&self, registers: &FrameRegisters) -> Option<gimli::UnwindTableRow<Reader>> {
+        let address = registers.location().wrapping_sub(self.load_bias);
+        let mut context = gimli::UnwindContext::new();
+
+        match &self.eh_frame {
+            Some((eh_frame, bases)) => eh_frame
+                .unwind_info_for_address(
+                    bases,
+                    &mut context,
+                    address,
+                    gimli::EhFrame::cie_from_offset,
+                )
+                .ok()
+                .cloned(),
+            None => None,
+        }
+        .or_else(|| {
+            let bases = gimli::BaseAddresses::default();
+            self.debug_frame
+                .as_ref()?
+                .unwind_info_for_address(
+                    &bases,
+                    &mut context,
+                    address,
+                    gimli::DebugFrame::cie_from_offset,
+                )
+                .ok()
+                .cloned()
+        })
+    }
+
+    fn cfa_of(row: &gimli::UnwindTableRow<Reader>, registers: &FrameRegisters) -> Option<u64> {
+        match row.cfa() {
+            gimli::CfaRule::RegisterAndOffset { register, offset } => {
+                Some(registers.get(register.0)?.wrapping_add_signed(*offset))
+            }
+            //never seen outside of signal trampolines
+            gimli::CfaRule::Expression(_) => None,
+        }
+    }
+}
@@ -0,0 +1,1263 @@
+use gimli::{AttributeValue, Location, Reader as _};
+
+use super::dwarf::{DwarfInfo, Reader};
+use super::unwind::FrameRegisters;
+
+//array elements listed when expanding, the rest gets elided
+pub const MAX_ELEMENTS: u64 = 256;
+//longest string previewed for char pointers and arrays
+const MAX_STRING: usize = 64;
+//typedef chains and pointer names nested deeper than this are almost certainly cycles
+const MAX_TYPE_DEPTH: usize = 16;
+
+type Entry<'a, 'b> = gimli::DebuggingInformationEntry<'a, 'b, Reader>;
+
+/// Where variable values get read from
+pub trait Memory {
+    /// `None` when any of it isn't mapped
+    fn read_bytes(&self, address: u64, size: usize) -> Option<Vec<u8>>;
+}
+
+/// A type DIE, resolved into a `TypeInfo` when needed
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct TypeRef {
+    unit: usize,
+    offset: gimli::UnitOffset,
+}
+
+/// Where a value lives
+#[derive(Clone, Debug)]
+pub enum Place {
+    Memory(u64),
+    /// Held in registers or computed by the location expression, there's no address to it
+    Bytes(Vec<u8>),
+    Unavailable(&'static str),
+}
+
+impl Place {
+    /// Part of the value `offset` bytes in, like a struct member or array element
+    pub fn offset(&self, offset: u64, size: u64) -> Self {
+        match self {
+            Self::Memory(address) => Self::Memory(address.wrapping_add(offset)),
+            Self::Bytes(bytes) => bytes
+                .get(offset as usize..(offset + size) as usize)
+                .map_or(Self::Unavailable("out of range"), |bytes| {
+                    Self::Bytes(bytes.to_vec())
+                }),
+            Self::Unavailable(reason) => Self::Unavailable(reason),
+        }
+    }
+
+    pub fn read(&self, size: u64, memory: &dyn Memory) -> Option<Vec<u8>> {
+        match self {
+            Self::Memory(address) => memory.read_bytes(*address, size as usize),
+            Self::Bytes(bytes) => {
+                let mut bytes = bytes.clone();
+                bytes.resize(size as usize, 0);
+                Some(bytes)
+            }
+            Self::Unavailable(_) => None,
+        }
+    }
+}
+
+/// A local variable or parameter in scope at some address
+pub struct Variable {
+    pub name: String,
+    pub is_parameter: bool,
+    pub ty: Option<TypeRef>,
+    pub place: Place,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BaseKind {
+    Signed,
+    Unsigned,
+    Float,
+    Bool,
+    Char,
+}
+
+pub enum TypeKind {
+    Void,
+    Base(BaseKind),
+    /// Pointers and references
+    Pointer(Option<TypeRef>),
+    Struct(Vec<Member>),
+    Union(Vec<Member>),
+    Enum(Vec<(String, i64)>),
+    /// Rust enums, which variant is active depends on the discriminant
+    Variants {
+        discriminant: Option<Member>,
+        variants: Vec<Variant>,
+    },
+    Array {
+        element: Option<TypeRef>,
+        count: Option<u64>,
+    },
+    Function,
+    Unknown,
+}
+
+/// A type with typedefs and qualifiers looked through, they only show up in the name
+pub struct TypeInfo {
+    pub name: String,
+    pub size: u64,
+    pub kind: TypeKind,
+    /// Template type parameters of structs, like `T` of a `Vec<T>`
+    pub parameters: Vec<(String, TypeRef)>,
+}
+
+impl TypeInfo {
+    fn unknown() -> Self {
+        Self {
+            name: String::from("?"),
+            size: 0,
+            kind: TypeKind::Unknown,
+            parameters: Vec::new(),
+        }
+    }
+
+    /// Whether expanding it shows anything
+    pub fn has_children(&self) -> bool {
+        match &self.kind {
+            TypeKind::Struct(members) | TypeKind::Union(members) => !members.is_empty(),
+            TypeKind::Array { count, .. } => count.is_some_and(|count| count > 0),
+            TypeKind::Pointer(target) => target.is_some(),
+            //depends on the active variant, which needs the value
+            TypeKind::Variants { .. } => true,
+            _ => false,
+        }
+    }
+}
+
+pub struct Member {
+    pub name: String,
+    pub ty: Option<TypeRef>,
+    pub offset: u64,
+    /// Bit offset from `offset` and width of bit fields
+    pub bits: Option<(u64, u64)>,
+}
+
+pub struct Variant {
+    /// Discriminant value selecting it, `None` for the variant taking every other value
+    /// (the one holding the niche in niche layouts)
+    pub value: Option<u64>,
+    /// Named after the variant, its type holds the fields
+    pub member: Member,
+}
+
+/// Struct member, array element or pointee of an expanded value
+pub struct Child {
+    pub name: String,
+    pub ty: Option<TypeRef>,
+    pub place: Place,
+}
+
+/// Everything location expressions can refer to
+struct Frame<'a> {
+    registers: &'a FrameRegisters,
+    cfa: Option<u64>,
+    frame_base: Option<u64>,
+    memory: &'a dyn Memory,
+    /// pc as it appears in the DWARF, without the load bias
+    address: u64,
+}
+
+impl DwarfInfo {
+    /// Parameters and variables of the innermost function (and blocks in it) containing pc.
+    /// `cfa` is needed by functions whose frame base is DW_OP_call_frame_cfa, which is what gcc emits
+    pub fn locals(
+        &self,
+        registers: &FrameRegisters,
+        cfa: Option<u64>,
+        memory: &dyn Memory,
+    ) -> Vec<Variable> {
+        let address = registers.location().wrapping_sub(self.load_bias);
+
+        for (index, unit) in self.units.iter().enumerate() {
+            if !self.unit_contains(unit, address) {
+                continue;
+            }
+
+            let mut scopes = Vec::new();
+            let found = unit
+                .entries_tree(None)
+                .and_then(|mut tree| self.find_scopes(unit, tree.root()?, address, &mut scopes));
+
+            if found.is_err() || scopes.is_empty() {
+                continue;
+            }
+
+            let mut frame = Frame {
+                registers,
+                cfa,
+                frame_base: None,
+                memory,
+                address,
+            };
+
+            //the frame base belongs to the concrete function, inlined ones don't have their own
+            frame.frame_base = scopes.iter().rev().find_map(|&offset| {
+                let entry = unit.entry(offset).ok()?;
+                let Ok(Some(AttributeValue::Exprloc(expression))) =
+                    entry.attr_value(gimli::DW_AT_frame_base)
+                else {
+                    return None;
+                };
+
+                match self
+                    .evaluate_pieces(unit, expression, &frame)
+                    .ok()?
+                    .first()?
+                    .location
+                {
+                    Location::Address { address } => Some(address),
+                    Location::Register { register } => registers.get(register.0),
+                    _ => None,
+                }
+            });
+
+            return self.scope_variables(index, &scopes, &frame);
+        }
+
+        Vec::new()
+    }
+
+    fn unit_contains(&self, unit: &gimli::Unit<Reader>, address: u64) -> bool {
+        let Ok(mut ranges) = self.dwarf.unit_ranges(unit) else {
+            return false;
+        };
+
+        while let Ok(Some(range)) = ranges.next() {
+            if range.begin <= address && address < range.end {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn die_contains(
+        &self,
+        unit: &gimli::Unit<Reader>,
+        entry: &Entry,
+        address: u64,
+    ) -> gimli::Result<bool> {
+        let mut ranges = self.dwarf.die_ranges(unit, entry)?;
+
+        while let Some(range) = ranges.next()? {
+            if range.begin <= address && address < range.end {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Functions, inlined functions and blocks containing `address`, outermost first
+    fn find_scopes(
+        &self,
+        unit: &gimli::Unit<Reader>,
+        node: gimli::EntriesTreeNode<Reader>,
+        address: u64,
+        scopes: &mut Vec<gimli::UnitOffset>,
+    ) -> gimli::Result<()> {
+        let mut children = node.children();
+
+        while let Some(child) = children.next()? {
+            let entry = child.entry();
+            let offset = entry.offset();
+
+            match entry.tag() {
+                gimli::DW_TAG_subprogram
+                | gimli::DW_TAG_lexical_block
+                | gimli::DW_TAG_inlined_subroutine
+                    if self.die_contains(unit, entry, address)? =>
+                {
+                    scopes.push(offset);
+                    return self.find_scopes(unit, child, address, scopes);
+                }
+                //rust puts functions in namespaces, c++ sometimes in classes
+                gimli::DW_TAG_namespace
+                | gimli::DW_TAG_structure_type
+                | gimli::DW_TAG_class_type
+                | gimli::DW_TAG_union_type
+                | gimli::DW_TAG_enumeration_type => {
+                    self.find_scopes(unit, child, address, scopes)?;
+
+                    if !scopes.is_empty() {
+                        return Ok(());
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn scope_variables(
+        &self,
+        unit_index: usize,
+        scopes: &[gimli::UnitOffset],
+        frame: &Frame,
+    ) -> Vec<Variable> {
+        let unit = &self.units[unit_index];
+        let mut variables = Vec::new();
+
+        for &offset in scopes {
+            let Ok(mut tree) = unit.entries_tree(Some(offset)) else {
+                continue;
+            };
+            let Ok(root) = tree.root() else {
+                continue;
+            };
+
+            let mut children = root.children();
+            while let Ok(Some(child)) = children.next() {
+                let entry = child.entry();
+
+                let is_parameter = match entry.tag() {
+                    gimli::DW_TAG_formal_parameter => true,
+                    gimli::DW_TAG_variable => false,
+                    _ => continue,
+                };
+
+                variables.push(self.variable(unit_index, entry, is_parameter, frame));
+            }
+        }
+
+        variables
+    }
+
+    fn variable(
+        &self,
+        unit_index: usize,
+        entry: &Entry,
+        is_parameter: bool,
+        frame: &Frame,
+    ) -> Variable {
+        let unit = &self.units[unit_index];
+
+        let name = self
+            .attribute(unit, entry, gimli::DW_AT_name)
+            .and_then(|name| self.string(unit, name))
+            .unwrap_or_else(|| String::from("<unnamed>"));
+
+        let ty = self
+            .attribute(unit, entry, gimli::DW_AT_type)
+            .and_then(|ty| self.type_ref(unit_index, ty));
+        let size = ty.map_or(8, |ty| self.type_size(ty));
+
+        let place = match entry.attr_value(gimli::DW_AT_location) {
+            Ok(Some(AttributeValue::Exprloc(expression))) => {
+                self.evaluate(unit, expression, frame, size)
+            }
+            Ok(Some(value)) => self.evaluate_location_list(unit, value, frame, size),
+            _ => match self.attribute(unit, entry, gimli::DW_AT_const_value) {
+                Some(AttributeValue::Block(block)) => block
+                    .to_slice()
+                    .map_or(Place::Unavailable("optimized out"), |bytes| {
+                        Place::Bytes(bytes.to_vec())
+                    }),
+                Some(AttributeValue::Sdata(value)) => Place::Bytes(value.to_le_bytes().to_vec()),
+                Some(value) => value
+                    .udata_value()
+                    .map_or(Place::Unavailable("optimized out"), |value| {
+                        Place::Bytes(value.to_le_bytes().to_vec())
+                    }),
+                None => Place::Unavailable("optimized out"),
+            },
+        };
+
+        Variable {
+            name,
+            is_parameter,
+            ty,
+            place,
+        }
+    }
+
+    /// Attribute of `entry`, or of the DIE it's an inlined or out of line instance of
+    fn attribute(
+        &self,
+        unit: &gimli::Unit<Reader>,
+        entry: &Entry,
+        name: gimli::DwAt,
+    ) -> Option<AttributeValue<Reader>> {
+        if let Ok(Some(value)) = entry.attr_value(name) {
+            return Some(value);
+        }
+
+        for origin in [gimli::DW_AT_abstract_origin, gimli::DW_AT_specification] {
+            if let Ok(Some(AttributeValue::UnitRef(offset))) = entry.attr_value(origin) {
+                let origin = unit.entry(offset).ok()?;
+                return self.attribute(unit, &origin, name);
+            }
+        }
+
+        None
+    }
+
+    fn string(&self, unit: &gimli::Unit<Reader>, value: AttributeValue<Reader>) -> Option<String> {
+        Some(
+            self.dwarf
+                .attr_string(unit, value)
+                .ok()?
+                .to_string_lossy()
+                .ok()?
+                .into_owned(),
+        )
+    }
+
+    fn name(&self, unit: &gimli::Unit<Reader>, entry: &Entry) -> Option<String> {
+        self.string(unit, entry.attr_value(gimli::DW_AT_name).ok()??)
+    }
+
+    fn type_ref(&self, unit_index: usize, value: AttributeValue<Reader>) -> Option<TypeRef> {
+        match value {
+            AttributeValue::UnitRef(offset) => Some(TypeRef {
+                unit: unit_index,
+                offset,
+            }),
+            //lto and dwz point into other units
+            AttributeValue::DebugInfoRef(offset) => {
+                self.units.iter().enumerate().find_map(|(unit, u)| {
+                    Some(TypeRef {
+                        unit,
+                        offset: offset.to_unit_offset(&u.header)?,
+                    })
+                })
+            }
+            _ => None,
+        }
+    }
+
+    fn evaluate_location_list(
+        &self,
+        unit: &gimli::Unit<Reader>,
+        value: AttributeValue<Reader>,
+        frame: &Frame,
+        size: u64,
+    ) -> Place {
+        let Ok(Some(mut locations)) = self.dwarf.attr_locations(unit, value) else {
+            return Place::Unavailable("optimized out");
+        };
+
+        while let Ok(Some(location)) = locations.next() {
+            if location.range.begin <= frame.address && frame.address < location.range.end {
+                return self.evaluate(unit, location.data, frame, size);
+            }
+        }
+
+        Place::Unavailable("optimized out")
+    }
+
+    fn evaluate(
+        &self,
+        unit: &gimli::Unit<Reader>,
+        expression: gimli::Expression<Reader>,
+        frame: &Frame,
+        size: u64,
+    ) -> Place {
+        let pieces = match self.evaluate_pieces(unit, expression, frame) {
+            Ok(pieces) => pieces,
+            Err(reason) => return Place::Unavailable(reason),
+        };
+
+        if let [piece] = &pieces[..]
+            && piece.size_in_bits.is_none()
+        {
+            match piece.location {
+                Location::Address { address } => return Place::Memory(address),
+                Location::Empty => return Place::Unavailable("optimized out"),
+                _ => {}
+            }
+        }
+
+        //anything else gets assembled piece by piece
+        let mut bytes = Vec::new();
+        for piece in pieces {
+            let piece_size = piece.size_in_bits.map_or(size, |bits| bits.div_ceil(8)) as usize;
+
+            let data = match piece.location {
+                Location::Register { register } => frame
+                    .registers
+                    .get(register.0)
+                    .map(|value| value.to_le_bytes().to_vec()),
+                Location::Address { address } => frame.memory.read_bytes(address, piece_size),
+                Location::Value { value } => value
+                    .to_u64(u64::MAX)
+                    .ok()
+                    .map(|value| value.to_le_bytes().to_vec()),
+                Location::Bytes { value } => value.to_slice().ok().map(|bytes| bytes.to_vec()),
+                Location::Empty | Location::ImplicitPointer { .. } => None,
+            };
+
+            let Some(mut data) = data else {
+                return Place::Unavailable("optimized out");
+            };
+
+            data.resize(piece_size, 0);
+            bytes.extend(data);
+        }
+
+        Place::Bytes(bytes)
+    }
+
+    fn evaluate_pieces(
+        &self,
+        unit: &gimli::Unit<Reader>,
+        expression: gimli::Expression<Reader>,
+        frame: &Frame,
+    ) -> Result<Vec<gimli::Piece<Reader>>, &'static str> {
+        const INVALID: &str = "invalid location";
+
+        let mut evaluation = expression.evaluation(unit.encoding());
+        let mut result = evaluation.evaluate().map_err(|_| INVALID)?;
+
+        loop {
+            result = match result {
+                gimli::EvaluationResult::Complete => return Ok(evaluation.result()),
+                gimli::EvaluationResult::RequiresMemory { address, size, .. } => {
+                    let bytes = frame
+                        .memory
+                        .read_bytes(address, size as usize)
+                        .ok_or("unreadable")?;
+
+                    let mut value = [0; 8];
+                    value[..bytes.len()].copy_from_slice(&bytes);
+                    evaluation.resume_with_memory(gimli::Value::Generic(u64::from_le_bytes(value)))
+                }
+                gimli::EvaluationResult::RequiresRegister { register, .. } => {
+                    let value = frame.registers.get(register.0).ok_or("register unknown")?;
+                    evaluation.resume_with_register(gimli::Value::Generic(value))
+                }
+                gimli::EvaluationResult::RequiresFrameBase => {
+                    evaluation.resume_with_frame_base(frame.frame_base.ok_or("no frame base")?)
+                }
+                gimli::EvaluationResult::RequiresCallFrameCfa => {
+                    evaluation.resume_with_call_frame_cfa(frame.cfa.ok_or("no call frame info")?)
+                }
+                gimli::EvaluationResult::RequiresRelocatedAddress(address) => {
+                    evaluation.resume_with_relocated_address(address.wrapping_add(self.load_bias))
+                }
+                gimli::EvaluationResult::RequiresIndexedAddress { index, relocate } => {
+                    let address = self.dwarf.address(unit, index).map_err(|_| INVALID)?;
+                    evaluation.resume_with_indexed_address(if relocate {
+                        address.wrapping_add(self.load_bias)
+                    } else {
+                        address
+                    })
+                }
+                gimli::EvaluationResult::RequiresBaseType(offset) => {
+                    evaluation.resume_with_base_type(self.value_type(unit, offset).ok_or(INVALID)?)
+                }
+                gimli::EvaluationResult::RequiresTls(_) => return Err("thread local"),
+                //values at function entry would need the caller's frame and call site info
+                gimli::EvaluationResult::RequiresEntryValue(_)
+                | gimli::EvaluationResult::RequiresParameterRef(_) => return Err("optimized out"),
+                gimli::EvaluationResult::RequiresAtLocation(_) => return Err(INVALID),
+            }
+            .map_err(|_| INVALID)?;
+        }
+    }
+
+    fn value_type(
+        &self,
+        unit: &gimli::Unit<Reader>,
+        offset: gimli::UnitOffset,
+    ) -> Option<gimli::ValueType> {
+        let entry = unit.entry(offset).ok()?;
+
+        let Ok(Some(AttributeValue::Encoding(encoding))) = entry.attr_value(gimli::DW_AT_encoding)
+        else {
+            return None;
+        };
+        let size = entry
+            .attr_value(gimli::DW_AT_byte_size)
+            .ok()??
+            .udata_value()?;
+
+        gimli::ValueType::from_encoding(encoding, size)
+    }
+
+    pub fn type_size(&self, ty: TypeRef) -> u64 {
+        self.resolve_type(ty, 0, true).size
+    }
+
+    pub fn type_info(&self, ty: TypeRef) -> TypeInfo {
+        self.resolve_type(ty, 0, false)
+    }
+
+    /// `shallow` skips members and enumerators when only the name and size are needed
+    fn resolve_type(&self, ty: TypeRef, depth: usize, shallow: bool) -> TypeInfo {
+        if depth > MAX_TYPE_DEPTH {
+            return TypeInfo::unknown();
+        }
+
+        let Some(unit) = self.units.get(ty.unit) else {
+            return TypeInfo::unknown();
+        };
+        let Ok(entry) = unit.entry(ty.offset) else {
+            return TypeInfo::unknown();
+        };
+
+        let name = entry
+            .attr_value(gimli::DW_AT_name)
+            .ok()
+            .flatten()
+            .and_then(|name| self.string(unit, name));
+        let size = entry
+            .attr_value(gimli::DW_AT_byte_size)
+            .ok()
+            .flatten()
+            .and_then(|size| size.udata_value());
+        let target = entry
+            .attr_value(gimli::DW_AT_type)
+            .ok()
+            .flatten()
+            .and_then(|target| self.type_ref(ty.unit, target));
+
+        let target_name = || {
+            target.map_or(String::from("void"), |t| {
+                self.resolve_type(t, depth + 1, true).name
+            })
+        };
+
+        match entry.tag() {
+            gimli::DW_TAG_base_type => {
+                let kind = match entry.attr_value(gimli::DW_AT_encoding) {
+                    Ok(Some(AttributeValue::Encoding(encoding))) => match encoding {
+                        gimli::DW_ATE_signed => BaseKind::Signed,
+                        gimli::DW_ATE_float => BaseKind::Float,
+                        gimli::DW_ATE_boolean => BaseKind::Bool,
+                        gimli::DW_ATE_signed_char
+                        | gimli::DW_ATE_unsigned_char
+                        | gimli::DW_ATE_UTF => BaseKind::Char,
+                        _ => BaseKind::Unsigned,
+                    },
+                    _ => BaseKind::Unsigned,
+                };
+
+                TypeInfo {
+                    name: name.unwrap_or_else(|| String::from("?")),
+                    size: size.unwrap_or(0),
+                    kind: TypeKind::Base(kind),
+                    parameters: Vec::new(),
+                }
+            }
+            gimli::DW_TAG_unspecified_type => TypeInfo {
+                name: name.unwrap_or_else(|| String::from("void")),
+                size: size.unwrap_or(0),
+                kind: TypeKind::Void,
+                parameters: Vec::new(),
+            },
+            tag @ (gimli::DW_TAG_pointer_type
+            | gimli::DW_TAG_reference_type
+            | gimli::DW_TAG_rvalue_reference_type) => {
+                let suffix = match tag {
+                    gimli::DW_TAG_pointer_type => "*",
+                    gimli::DW_TAG_reference_type => "&",
+                    _ => "&&",
+                };
+
+                //pointers to functions and void have nothing to look at
+                let target = target.filter(|&t| {
+                    !matches!(
+                        self.resolve_type(t, depth + 1, true).kind,
+                        TypeKind::Void | TypeKind::Function
+                    )
+                });
+
+                TypeInfo {
+                    //rust names its pointer types (&str, *const u8)
+                    name: name.unwrap_or_else(|| format!("{} {suffix}", target_name())),
+                    size: size.unwrap_or(8),
+                    kind: TypeKind::Pointer(target),
+                    parameters: Vec::new(),
+                }
+            }
+            tag @ (gimli::DW_TAG_typedef
+            | gimli::DW_TAG_const_type
+            | gimli::DW_TAG_volatile_type
+            | gimli::DW_TAG_restrict_type
+            | gimli::DW_TAG_atomic_type) => {
+                let mut info = match target {
+                    Some(target) => self.resolve_type(target, depth + 1, shallow),
+                    None => TypeInfo {
+                        name: String::from("void"),
+                        size: 0,
+                        kind: TypeKind::Void,
+                        parameters: Vec::new(),
+                    },
+                };
+
+                info.name = match tag {
+                    gimli::DW_TAG_typedef => name.unwrap_or(info.name),
+                    gimli::DW_TAG_const_type => format!("const {}", info.name),
+                    gimli::DW_TAG_volatile_type => format!("volatile {}", info.name),
+                    gimli::DW_TAG_restrict_type => format!("{} restrict", info.name),
+                    _ => format!("_Atomic {}", info.name),
+                };
+                info
+            }
+            tag @ (gimli::DW_TAG_structure_type
+            | gimli::DW_TAG_class_type
+            | gimli::DW_TAG_union_type) => {
+                let is_union = tag == gimli::DW_TAG_union_type;
+
+                let kind = if shallow {
+                    if is_union {
+                        TypeKind::Union(Vec::new())
+                    } else {
+                        TypeKind::Struct(Vec::new())
+                    }
+                } else if let Some((discriminant, variants)) = self.variants(ty) {
+                    TypeKind::Variants {
+                        discriminant,
+                        variants,
+                    }
+                } else if is_union {
+                    TypeKind::Union(self.members(ty))
+                } else {
+                    TypeKind::Struct(self.members(ty))
+                };
+
+                let anonymous = if is_union {
+                    "<anonymous union>"
+                } else {
+                    "<anonymous struct>"
+                };
+
+                TypeInfo {
+                    name: name.unwrap_or_else(|| String::from(anonymous)),
+                    size: size.unwrap_or(0),
+                    kind,
+                    parameters: if shallow {
+                        Vec::new()
+                    } else {
+                        self.parameters(ty)
+                    },
+                }
+            }
+            gimli::DW_TAG_enumeration_type => TypeInfo {
+                name: name.unwrap_or_else(|| String::from("<anonymous enum>")),
+                size: size.unwrap_or(4),
+                kind: TypeKind::Enum(if shallow {
+                    Vec::new()
+                } else {
+                    self.enumerators(ty)
+                }),
+                parameters: Vec::new(),
+            },
+            gimli::DW_TAG_array_type => {
+                let count = self.array_count(ty);
+                let element_size = target.map_or(0, |t| self.type_size(t));
+
+                TypeInfo {
+                    name: format!(
+                        "{}[{}]",
+                        target_name(),
+                        count.map(|c| c.to_string()).unwrap_or_default()
+                    ),
+                    size: size.unwrap_or(element_size * count.unwrap_or(0)),
+                    kind: TypeKind::Array {
+                        element: target,
+                        count,
+                    },
+                    parameters: Vec::new(),
+                }
+            }
+            gimli::DW_TAG_subroutine_type => TypeInfo {
+                name: name.unwrap_or_else(|| format!("fn() -> {}", target_name())),
+                size: 0,
+                kind: TypeKind::Function,
+                parameters: Vec::new(),
+            },
+            _ => TypeInfo {
+                name: name.unwrap_or_else(|| String::from("?")),
+                size: size.unwrap_or(0),
+                kind: TypeKind::Unknown,
+                parameters: Vec::new(),
+            },
+        }
+    }
+
+    /// Direct children of a type DIE
+    fn type_children(&self, ty: TypeRef, mut f: impl FnMut(&Entry)) {
+        let Some(unit) = self.units.get(ty.unit) else {
+            return;
+        };
+        let Ok(mut tree) = unit.entries_tree(Some(ty.offset)) else {
+            return;
+        };
+        let Ok(root) = tree.root() else {
+            return;
+        };
+
+        let mut children = root.children();
+        while let Ok(Some(child)) = children.next() {
+            f(child.entry());
+        }
+    }
+
+    fn members(&self, ty: TypeRef) -> Vec<Member> {
+        let unit = &self.units[ty.unit];
+        let mut members = Vec::new();
+
+        self.type_children(ty, |entry| {
+            let name = match entry.tag() {
+                //static members (dwarf 4) don't live in the struct
+                gimli::DW_TAG_member
+                    if !matches!(entry.attr_value(gimli::DW_AT_declaration), Ok(Some(_))) =>
+                {
+                    self.name(unit, entry)
+                        .unwrap_or_else(|| String::from("<anonymous>"))
+                }
+                gimli::DW_TAG_inheritance => String::from("<base>"),
+                _ => return,
+            };
+
+            members.push(self.member(ty.unit, entry, name));
+        });
+
+        members
+    }
+
+    fn member(&self, unit_index: usize, entry: &Entry, name: String) -> Member {
+        let unit = &self.units[unit_index];
+        let attr = |name| entry.attr_value(name).ok().flatten();
+
+        let ty = attr(gimli::DW_AT_type).and_then(|t| self.type_ref(unit_index, t));
+
+        let mut offset = match attr(gimli::DW_AT_data_member_location) {
+            Some(AttributeValue::Exprloc(expression)) => {
+                //old style DW_OP_plus_uconst, evaluated on top of the struct's address
+                let mut evaluation = expression.evaluation(unit.encoding());
+                evaluation.set_initial_value(0);
+                match (evaluation.evaluate(), evaluation.result().first()) {
+                    (Ok(gimli::EvaluationResult::Complete), Some(piece)) => match piece.location {
+                        Location::Address { address } => address,
+                        _ => 0,
+                    },
+                    _ => 0,
+                }
+            }
+            Some(value) => value.udata_value().unwrap_or(0),
+            None => 0,
+        };
+
+        let bits = attr(gimli::DW_AT_bit_size)
+            .and_then(|size| size.udata_value())
+            .map(|bit_size| {
+                if let Some(data_bit_offset) =
+                    attr(gimli::DW_AT_data_bit_offset).and_then(|o| o.udata_value())
+                {
+                    offset = data_bit_offset / 8;
+                    (data_bit_offset % 8, bit_size)
+                } else {
+                    //dwarf 2 style, counted from the most significant bit of the storage unit
+                    let storage = attr(gimli::DW_AT_byte_size)
+                        .and_then(|size| size.udata_value())
+                        .or_else(|| ty.map(|ty| self.type_size(ty)))
+                        .unwrap_or(4);
+                    let bit_offset = attr(gimli::DW_AT_bit_offset)
+                        .and_then(|o| o.udata_value())
+                        .unwrap_or(0);
+
+                    (
+                        (storage * 8).saturating_sub(bit_offset + bit_size),
+                        bit_size,
+                    )
+                }
+            });
+
+        Member {
+            name,
+            ty,
+            offset,
+            bits,
+        }
+    }
+
+    /// Discriminant and variants of a rust enum, `None` for anything else
+    fn variants(&self, ty: TypeRef) -> Option<(Option<Member>, Vec<Variant>)> {
+        let unit = &self.units[ty.unit];
+        let mut tree = unit.entries_tree(Some(ty.offset)).ok()?;
+        let root = tree.root().ok()?;
+
+        let mut children = root.children();
+        while let Ok(Some(child)) = children.next() {
+            if child.entry().tag() != gimli::DW_TAG_variant_part {
+                continue;
+            }
+
+            let discriminant_offset = match child.entry().attr_value(gimli::DW_AT_discr) {
+                Ok(Some(AttributeValue::UnitRef(offset))) => Some(offset),
+                _ => None,
+            };
+
+            let mut discriminant = None;
+            let mut variants = Vec::new();
+
+            let mut parts = child.children();
+            while let Ok(Some(part)) = parts.next() {
+                let entry = part.entry();
+
+                match entry.tag() {
+                    gimli::DW_TAG_member if Some(entry.offset()) == discriminant_offset => {
+                        discriminant =
+                            Some(self.member(ty.unit, entry, String::from("<discriminant>")));
+                    }
+                    gimli::DW_TAG_variant => {
+                        let value = match entry.attr_value(gimli::DW_AT_discr_value) {
+                            Ok(Some(AttributeValue::Sdata(value))) => Some(value as u64),
+                            Ok(Some(value)) => value.udata_value(),
+                            _ => None,
+                        };
+
+                        let mut fields = part.children();
+                        while let Ok(Some(field)) = fields.next() {
+                            let entry = field.entry();
+
+                            if entry.tag() == gimli::DW_TAG_member {
+                                let name = self
+                                    .name(unit, entry)
+                                    .unwrap_or_else(|| String::from("<anonymous>"));
+
+                                variants.push(Variant {
+                                    value,
+                                    member: self.member(ty.unit, entry, name),
+                                });
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+
+            return Some((discriminant, variants));
+        }
+
+        None
+    }
+
+    fn parameters(&self, ty: TypeRef) -> Vec<(String, TypeRef)> {
+        let unit = &self.units[ty.unit];
+        let mut parameters = Vec::new();
+
+        self.type_children(ty, |entry| {
+            if entry.tag() != gimli::DW_TAG_template_type_parameter {
+                return;
+            }
+
+            let parameter = entry
+                .attr_value(gimli::DW_AT_type)
+                .ok()
+                .flatten()
+                .and_then(|t| self.type_ref(ty.unit, t));
+
+            if let (Some(name), Some(parameter)) = (self.name(unit, entry), parameter) {
+                parameters.push((name, parameter));
+            }
+        });
+
+        parameters
+    }
+
+    fn enumerators(&self, ty: TypeRef) -> Vec<(String, i64)> {
+        let unit = &self.units[ty.unit];
+        let mut enumerators = Vec::new();
+
+        self.type_children(ty, |entry| {
+            if entry.tag() != gimli::DW_TAG_enumerator {
+                return;
+            }
+
+            let name = entry
+                .attr_value(gimli::DW_AT_name)
+                .ok()
+                .flatten()
+                .and_then(|name| self.string(unit, name));
+            let value = match entry.attr_value(gimli::DW_AT_const_value) {
+                Ok(Some(AttributeValue::Sdata(value))) => Some(value),
+                Ok(Some(value)) => value.udata_value().map(|value| value as i64),
+                _ => None,
+            };
+
+            if let (Some(name), Some(value)) = (name, value) {
+                enumerators.push((name, value));
+            }
+        });
+
+        enumerators
+    }
+
+    /// Element count of an array, multi dimensional arrays get flattened
+    fn array_count(&self, ty: TypeRef) -> Option<u64> {
+        let mut count = None;
+
+        self.type_children(ty, |entry| {
+            if entry.tag() != gimli::DW_TAG_subrange_type {
+                return;
+            }
+
+            let attr =
+                |name| {
+                    entry.attr_value(name).ok().flatten().and_then(
+                        |value: AttributeValue<Reader>| match value {
+                            AttributeValue::Sdata(value) => Some(value.max(-1) as u64),
+                            value => value.udata_value(),
+                        },
+                    )
+                };
+
+            let dimension = attr(gimli::DW_AT_count).or_else(|| {
+                let lower = attr(gimli::DW_AT_lower_bound).unwrap_or(0);
+                Some(
+                    attr(gimli::DW_AT_upper_bound)?
+                        .wrapping_add(1)
+                        .wrapping_sub(lower),
+                )
+            });
+
+            count = match (count, dimension) {
+                (None, dimension) => dimension,
+                (Some(count), Some(dimension)) => Some(count * dimension),
+                (Some(_), None) => None,
+            };
+        });
+
+        count
+    }
+
+    /// What expanding a value shows: members, elements or the pointee
+    pub fn children(&self, info: &TypeInfo, place: &Place, memory: &dyn Memory) -> Vec<Child> {
+        if let Place::Unavailable(_) = place {
+            return Vec::new();
+        }
+
+        match &info.kind {
+            TypeKind::Struct(members) | TypeKind::Union(members) => members
+                .iter()
+                .map(|member| {
+                    let size = member.ty.map_or(0, |ty| self.type_size(ty));
+
+                    let place = match member.bits {
+                        Some((bit_offset, bit_size)) => {
+                            let storage = (bit_offset + bit_size).div_ceil(8);
+                            match place.offset(member.offset, storage).read(storage, memory) {
+                                Some(bytes) => {
+                                    let mut value = [0; 16];
+                                    value[..bytes.len().min(16)]
+                                        .copy_from_slice(&bytes[..bytes.len().min(16)]);
+                                    let value = (u128::from_le_bytes(value) >> bit_offset)
+                                        & ((1u128 << bit_size.min(127)) - 1);
+                                    Place::Bytes(value.to_le_bytes()[..size as usize].to_vec())
+                                }
+                                None => Place::Unavailable("unreadable"),
+                            }
+                        }
+                        None => place.offset(member.offset, size),
+                    };
+
+                    Child {
+                        name: member.name.clone(),
+                        ty: member.ty,
+                        place,
+                    }
+                })
+                .collect(),
+            TypeKind::Array {
+                element,
+                count: Some(count),
+            } => {
+                let size = element.map_or(0, |ty| self.type_size(ty));
+
+                (0..(*count).min(MAX_ELEMENTS))
+                    .map(|i| Child {
+                        name: format!("[{i}]"),
+                        ty: *element,
+                        place: place.offset(i * size, size),
+                    })
+                    .collect()
+            }
+            TypeKind::Variants {
+                discriminant,
+                variants,
+            } => match self.active_variant(discriminant.as_ref(), variants, place, memory) {
+                Some(Variant {
+                    member:
+                        Member {
+                            ty: Some(ty),
+                            offset,
+                            ..
+                        },
+                    ..
+                }) => {
+                    let info = self.type_info(*ty);
+                    self.children(&info, &place.offset(*offset, info.size), memory)
+                }
+                _ => Vec::new(),
+            },
+            TypeKind::Pointer(Some(target)) => match place.read(8, memory) {
+                Some(bytes) => {
+                    let address = u64::from_le_bytes(bytes.try_into().unwrap());
+
+                    if address == 0 {
+                        Vec::new()
+                    } else {
+                        vec![Child {
+                            name: String::from("*"),
+                            ty: Some(*target),
+                            place: Place::Memory(address),
+                        }]
+                    }
+                }
+                None => Vec::new(),
+            },
+            _ => Vec::new(),
+        }
+    }
+
+    /// One line summary of a value, aggregates only get their contents previewed when they're strings
+    pub fn format_value(&self, info: &TypeInfo, place: &Place, memory: &dyn Memory) -> String {
+        if let Place::Unavailable(reason) = place {
+            return format!("<{reason}>");
+        }
+
+        let read = |size| place.read(size, memory);
+
+        match &info.kind {
+            TypeKind::Void => String::from("void"),
+            TypeKind::Base(kind) => read(info.size).map_or_else(
+                || String::from("<unreadable>"),
+                |bytes| format_base(*kind, &bytes),
+            ),
+            TypeKind::Pointer(target) => {
+                let Some(bytes) = read(8) else {
+                    return String::from("<unreadable>");
+                };
+                let address = u64::from_le_bytes(bytes.try_into().unwrap());
+
+                let is_string = target.is_some_and(|target| self.is_char(target));
+                match memory
+                    .read_bytes(address, MAX_STRING)
+                    .filter(|_| is_string && address != 0)
+                {
+                    Some(bytes) => format!("{address:#x} {}", format_string(&bytes)),
+                    None => format!("{address:#x}"),
+                }
+            }
+            TypeKind::Enum(enumerators) => {
+                let Some(bytes) = read(info.size) else {
+                    return String::from("<unreadable>");
+                };
+                let value = sign_extend(&bytes);
+
+                enumerators
+                    .iter()
+                    .find(|(_, v)| *v as i128 == value)
+                    .map_or_else(|| value.to_string(), |(name, _)| name.clone())
+            }
+            TypeKind::Array { element, count } => {
+                let is_string = element.is_some_and(|element| self.is_char(element));
+
+                match (is_string, count) {
+                    (true, Some(count)) => read((*count).min(MAX_STRING as u64)).map_or_else(
+                        || String::from("<unreadable>"),
+                        |bytes| format_string(&bytes),
+                    ),
+                    _ => String::from("[...]"),
+                }
+            }
+            TypeKind::Variants {
+                discriminant,
+                variants,
+            } => self
+                .active_variant(discriminant.as_ref(), variants, place, memory)
+                .map_or_else(
+                    || String::from("<invalid discriminant>"),
+                    |variant| variant.member.name.clone(),
+                ),
+            TypeKind::Struct(_) | TypeKind::Union(_) => String::from("{...}"),
+            TypeKind::Function => String::from("<function>"),
+            TypeKind::Unknown => String::from("?"),
+        }
+    }
+
+    /// Variant of a rust enum value picked by its discriminant
+    pub fn active_variant<'a>(
+        &self,
+        discriminant: Option<&Member>,
+        variants: &'a [Variant],
+        place: &Place,
+        memory: &dyn Memory,
+    ) -> Option<&'a Variant> {
+        let value = discriminant.and_then(|discriminant| {
+            let size = discriminant.ty.map_or(0, |ty| self.type_size(ty)).min(8);
+            let bytes = place.offset(discriminant.offset, size).read(size, memory)?;
+
+            let mut value = [0; 8];
+            value[..bytes.len()].copy_from_slice(&bytes);
+            Some((u64::from_le_bytes(value), size))
+        });
+
+        //signed discriminants are sign extended in the DWARF, only the stored bytes count
+        let matches = |expected: u64| {
+            value.is_some_and(|(value, size)| {
+                let mask = u64::MAX.checked_shr(64 - size as u32 * 8).unwrap_or(0);
+                expected & mask == value
+            })
+        };
+
+        variants
+            .iter()
+            .find(|variant| variant.value.is_some_and(matches))
+            .or_else(|| variants.iter().find(|variant| variant.value.is_none()))
+    }
+
+    /// Single byte characters, pointers to them and arrays of them get shown as strings
+    pub fn is_char(&self, ty: TypeRef) -> bool {
+        let info = self.resolve_type(ty, 0, true);
+        matches!(info.kind, TypeKind::Base(BaseKind::Char)) && info.size == 1
+    }
+}
+
+fn sign_extend(bytes: &[u8]) -> i128 {
+    let size = bytes.len().clamp(1, 16);
+    let mut value = [0; 16];
+    value[..size].copy_from_slice(&bytes[..size]);
+
+    let shift = 128 - size as u32 * 8;
+    (i128::from_le_bytes(value) << shift) >> shift
+}
+
+fn format_base(kind: BaseKind, bytes: &[u8]) -> String {
+    let mut value = [0; 16];
+    value[..bytes.len().min(16)].copy_from_slice(&bytes[..bytes.len().min(16)]);
+    let unsigned = u128::from_le_bytes(value);
+
+    match kind {
+        BaseKind::Signed => sign_extend(bytes).to_string(),
+        BaseKind::Unsigned => unsigned.to_string(),
+        BaseKind::Bool => (unsigned != 0).to_string(),
+        BaseKind::Float => match bytes.len() {
+            4 => f32::from_le_bytes(bytes.try_into().unwrap()).to_string(),
+            8 => f64::from_le_bytes(bytes.try_into().unwrap()).to_string(),
+            size => format!("<{size} byte float>"),
+        },
+        BaseKind::Char => match char::from_u32(unsigned as u32) {
+            Some(c) if bytes.len() > 1 || c.is_ascii() => format!("{unsigned} {c:?}"),
+            _ => unsigned.to_string(),
+        },
+    }
+}
+
+/// Quoted up to the first nul
+fn format_string(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    format!("{:?}", String::from_utf8_lossy(&bytes[..end]))
+}
@@ -1,10 +1,29 @@
+use std::collections::HashMap;
 use std::error::Error;
+use std::path::PathBuf;
 
 use eframe::egui;
 
+use super::bookmarks_view::BookmarksView;
+use super::breakpoints_view::BreakpointsView;
+use super::call_stack_view::CallStackView;
+use super::disassembly_format::{HexStyle, MemorySize, Syntax};
 use super::disassembly_view::DisassemblyView;
+use super::dynamic_symbols_view::DynamicSymbolsView;
 use super::hex_view::HexView;
-use crate::debugger::{self, Debugee};
+use super::locals_view::LocalsView;
+use super::memory_map_view::MemoryMapView;
+use super::patches_view::PatchesView;
+use super::project::{self, Project};
+use super::source_view::SourceView;
+use super::stack_view::StackView;
+use super::symbols_view::SymbolsView;
+use super::Follow;
+use crate::debugger::debug_info::DebugInfoSettings;
+use crate::debugger::pretty::PrettyPrinters;
+use crate::debugger::rendezvous::ModuleEvent;
+use crate::debugger::symbols::DemangleSettings;
+use crate::debugger::{self, Debugee, StepKind, StepProgress};
 use crate::gui::widgets;
 use crate::WINDOW_TITLE;
 
@@ -48,10 +67,69 @@ macro_rules! instruction {
                     }
                 }
             }
+
+            let value = $debugee.context().$name;
+            let telescope = $self
+                .telescopes
+                .entry(value)
+                .or_insert_with(|| $debugee.telescope(value).describe());
+            ui.add(
+                egui::Label::new(egui::RichText::new(telescope.as_str()).monospace().weak())
+                    .truncate(true),
+            )
+            .on_hover_text(telescope.as_str());
         });
     };
 }
 
+#[derive(Clone, Copy, PartialEq)]
+enum BottomTab {
+    Dump,
+    Breakpoints,
+    Patches,
+    Bookmarks,
+    MemoryMap,
+    Modules,
+    Symbols,
+    Exports,
+    Imports,
+    Locals,
+    CallStack,
+}
+
+impl BottomTab {
+    const ALL: [Self; 11] = [
+        Self::Dump,
+        Self::Breakpoints,
+        Self::Patches,
+        Self::Bookmarks,
+        Self::MemoryMap,
+        Self::Modules,
+        Self::Symbols,
+        Self::Exports,
+        Self::Imports,
+        Self::Locals,
+        Self::CallStack,
+    ];
+
+    /// Name in project files
+    const fn key(self) -> &'static str {
+        match self {
+            Self::Dump => "dump",
+            Self::Breakpoints => "breakpoints",
+            Self::Patches => "patches",
+            Self::Bookmarks => "bookmarks",
+            Self::MemoryMap => "memory_map",
+            Self::Modules => "modules",
+            Self::Symbols => "symbols",
+            Self::Exports => "exports",
+            Self::Imports => "imports",
+            Self::Locals => "locals",
+            Self::CallStack => "call_stack",
+        }
+    }
+}
+
 #[derive(Clone)]
 struct Process {
     pid: u32,
@@ -60,30 +138,92 @@ struct Process {
 
 pub struct App {
     debugee: Option<Debugee>,
+    /// Binary we started ourselves (as opposed to attached to), used for restarting
+    launch_path: Option<PathBuf>,
+    /// Executable of the process, launched or attached to, its project file is named after it
+    executable: Option<project::Executable>,
+    /// Project breakpoints and patches in modules that aren't loaded yet
+    project_pending: project::Pending,
     disassembly_view: DisassemblyView,
     hex_view: HexView,
+    stack_view: StackView,
+    source_view: SourceView,
+    breakpoints_view: BreakpointsView,
+    patches_view: PatchesView,
+    bookmarks_view: BookmarksView,
+    memory_map_view: MemoryMapView,
+    symbols_view: SymbolsView,
+    locals_view: LocalsView,
+    call_stack_view: CallStackView,
+    dynamic_symbols_view: DynamicSymbolsView,
+    bottom_tab: BottomTab,
     pub status: String,
+    /// Stop when ld.so maps a new library instead of silently continuing
+    break_on_library_load: bool,
+    demangle: DemangleSettings,
+    debug_info: DebugInfoSettings,
+    pretty_printers: PrettyPrinters,
+    /// Everything ld.so reported (un)loading since the process started
+    module_events: Vec<ModuleEvent>,
 
     since_reg_refresh: std::time::SystemTime,
     regs_dirty: bool,
+    /// Where register values lead when followed as pointers, dropped along with the registers
+    telescopes: HashMap<u64, String>,
 
     render_attach_modal: bool,
     process_list: Vec<Process>,
+
+    render_debug_info_modal: bool,
+    debug_directories_input: String,
+    debuginfod_cache_input: String,
+
+    render_pretty_printers_modal: bool,
+    pretty_printers_input: String,
+    pretty_printers_error: Option<String>,
 }
 
 impl App {
     pub fn new() -> Self {
         Self {
             debugee: None,
+            launch_path: None,
+            executable: None,
+            project_pending: project::Pending::default(),
             disassembly_view: DisassemblyView::new(),
             hex_view: HexView::new(),
+            stack_view: StackView::new(),
+            source_view: SourceView::new(),
+            breakpoints_view: BreakpointsView::new(),
+            patches_view: PatchesView::new(),
+            bookmarks_view: BookmarksView::new(),
+            memory_map_view: MemoryMapView::new(),
+            symbols_view: SymbolsView::new(),
+            locals_view: LocalsView::new(),
+            call_stack_view: CallStackView::new(),
+            dynamic_symbols_view: DynamicSymbolsView::new(),
+            bottom_tab: BottomTab::Dump,
             status: String::from("Idle"),
+            break_on_library_load: false,
+            demangle: DemangleSettings::default(),
+            debug_info: DebugInfoSettings::default(),
+            pretty_printers: PrettyPrinters::default(),
+            module_events: Vec::new(),
 
             since_reg_refresh: std::time::SystemTime::UNIX_EPOCH,
             regs_dirty: false,
+            telescopes: HashMap::new(),
 
             render_attach_modal: false,
             process_list: Vec::new(),
+
+            render_debug_info_modal: false,
+            debug_directories_input: String::new(),
+            debuginfod_cache_input: String::new(),
+
+            render_pretty_printers_modal: false,
+            pretty_printers_input: String::new(),
+            pretty_printers_error: None,
         }
     }
 
@@ -110,9 +250,12 @@ impl App {
                 .show()
                 != rfd::MessageDialogResult::No
             {
-                let child_process = std::process::Command::new(file).spawn()?;
-
-                self.debugee = Some(Debugee::new(child_process.id())?);
+                self.autosave_project();
+                self.debugee = Some(Debugee::launch(file)?);
+                self.module_events.clear();
+                self.apply_symbol_settings();
+                self.launch_path = Some(file.clone());
+                self.load_project(&file.to_string_lossy());
 
                 ctx.send_viewport_cmd(egui::ViewportCommand::Title(format!(
                     "{WINDOW_TITLE} - {}",
@@ -148,7 +291,12 @@ impl App {
         ctx: &egui::Context,
         process: &Process,
     ) -> Result<(), Box<dyn Error>> {
+        self.autosave_project();
         self.debugee = Some(Debugee::new(process.pid)?);
+        self.module_events.clear();
+        self.apply_symbol_settings();
+        self.launch_path = None;
+        self.load_project(&process.exe_path);
 
         ctx.send_viewport_cmd(egui::ViewportCommand::Title(format!(
             "{WINDOW_TITLE} - {}",
@@ -158,14 +306,171 @@ impl App {
         Ok(())
     }
 
+    /// Kills the process and starts it again, everything a project file would hold carries over.
+    /// Symbolic breakpoints get re-resolved, the rest is module relative and placed once its
+    /// module is loaded again
+    fn restart(&mut self) -> Result<(), Box<dyn Error>> {
+        let Some(path) = self.launch_path.clone() else {
+            return Ok(());
+        };
+
+        self.autosave_project();
+        let mut project = None;
+
+        if let Some(mut debugee) = self.debugee.take() {
+            let executable = self
+                .executable
+                .take()
+                .unwrap_or_else(|| project::Executable::new(&path.to_string_lossy()));
+            project = Some(Project::capture(
+                &debugee,
+                &executable,
+                self.layout(),
+                &self.project_pending,
+            ));
+            debugee.kill();
+        }
+
+        self.hex_view.purge_cache();
+        self.stack_view.purge_cache();
+        self.disassembly_view.purge_cache();
+        self.source_view.purge_cache();
+
+        let mut debugee = Debugee::launch(&path)?;
+        //it might've been rebuilt in the meantime
+        self.executable = Some(project::Executable::new(&path.to_string_lossy()));
+
+        self.project_pending.clear();
+        let errors = project.map_or_else(Vec::new, |project| {
+            project.restore(&mut debugee, &mut self.project_pending)
+        });
+
+        self.debugee = Some(debugee);
+        self.module_events.clear();
+        self.apply_symbol_settings();
+        self.status = if errors.is_empty() {
+            String::from("Restarted")
+        } else {
+            format!("Restarted, {}", errors.join(", "))
+        };
+
+        Ok(())
+    }
+
+    fn layout(&self) -> project::Layout {
+        project::Layout::new(
+            self.bottom_tab.key(),
+            self.disassembly_view.format(),
+            self.disassembly_view.interleave_source,
+            self.break_on_library_load,
+        )
+    }
+
+    /// Loads the project file of `executable` into the fresh debugee, if there is one for this build
+    fn load_project(&mut self, executable: &str) {
+        let executable = project::Executable::new(executable);
+        self.executable = Some(executable.clone());
+        self.project_pending.clear();
+
+        let Some(path) = project::find(&executable) else {
+            return;
+        };
+
+        let project = match Project::load(&path) {
+            Ok(project) => project,
+            Err(error) => {
+                self.status = format!("Failed to load project: {error}");
+                return;
+            }
+        };
+
+        if !project::matches_build(&project, &executable) {
+            self.status = format!(
+                "{} is for a different build of {}, not loaded",
+                path.display(),
+                executable.path
+            );
+            return;
+        }
+
+        let layout = &project.layout;
+        if let Some(tab) = BottomTab::ALL
+            .into_iter()
+            .find(|tab| tab.key() == layout.bottom_tab)
+        {
+            self.bottom_tab = tab;
+        }
+        let format = layout.format(self.disassembly_view.format());
+        self.disassembly_view.set_format(format);
+        self.disassembly_view.interleave_source = layout.interleave_source;
+        self.break_on_library_load = layout.break_on_library_load;
+
+        let Some(debugee) = self.debugee.as_mut() else {
+            return;
+        };
+
+        let errors = project.restore(debugee, &mut self.project_pending);
+        self.status = if errors.is_empty() {
+            format!("Loaded project {}", path.display())
+        } else {
+            format!("Loaded project {}, {}", path.display(), errors.join(", "))
+        };
+    }
+
+    fn save_project(&mut self) -> Result<PathBuf, String> {
+        let (Some(debugee), Some(executable)) = (&self.debugee, &self.executable) else {
+            return Err(String::from("No process"));
+        };
+
+        Project::capture(debugee, executable, self.layout(), &self.project_pending).save()
+    }
+
+    /// Keeps an existing project file up to date before the process goes away,
+    /// binaries without one don't get one until it's saved by hand
+    fn autosave_project(&mut self) {
+        if self.executable.as_ref().and_then(project::find).is_none() {
+            return;
+        }
+
+        if let Err(error) = self.save_project() {
+            self.status = format!("Failed to save project: {error}");
+        }
+    }
+
+    /// Pushes the name style and debug directories to the debugee,
+    /// cached disassembly has the old names baked in
+    fn apply_symbol_settings(&mut self) {
+        let Some(debugee) = self.debugee.as_mut() else {
+            return;
+        };
+
+        debugee.set_demangle_settings(self.demangle);
+        debugee.set_debug_info_settings(self.debug_info.clone());
+
+        self.disassembly_view.purge_cache();
+        self.stack_view.purge_cache();
+        if debugee.stopped {
+            self.disassembly_view.refresh_cache(debugee);
+        }
+    }
+
+    fn follow(&mut self, follow: Follow) {
+        let Some(debugee) = self.debugee.as_mut() else {
+            return;
+        };
+
+        match follow {
+            Follow::Disassembly(address) => self.disassembly_view.go_to(address),
+            Follow::Dump(address) => {
+                self.hex_view.go_to(address, debugee);
+                self.bottom_tab = BottomTab::Dump;
+            }
+        }
+    }
+
     fn handle_status(&mut self, status: i32) {
         let debugee = self.debugee.as_mut().unwrap();
         debugee.update_context();
-        self.disassembly_view.set_rip(debugee.context().rip);
-        self.disassembly_view.refresh_cache(&debugee);
-
-        self.hex_view.update_cache(debugee);
-        self.hex_view.clean_cache();
 
         if libc::WIFEXITED(status) {
             self.status = format!("Process exited with code {}", libc::WEXITSTATUS(status));
@@ -188,18 +493,98 @@ impl App {
             debugee.stopped = true;
             self.regs_dirty = true;
 
-            if libc::WSTOPSIG(status) == libc::SIGTRAP {
-                let rip = debugee.context().rip - 1;
+            //a long source step letting a frame through
+            if debugee.source_step_progress() == Some(StepProgress::Paused) {
+                debugee.continue_source_step();
+                return;
+            }
+
+            if debugger::is_exec_event(status) {
+                debugee.handle_exec();
+                self.status = String::from("Process started");
+            } else if libc::WSTOPSIG(status) == libc::SIGTRAP
+                && let Some(hit) = debugee.process_breakpoint_hit()
+            {
+                self.module_events.extend(hit.module_events.iter().cloned());
+
+                //a source step ran over a call and is back in its own frame
+                if hit.internal && hit.condition_met && debugee.resume_source_step() {
+                    return;
+                }
+
+                let library_loaded = hit
+                    .module_events
+                    .iter()
+                    .any(|event| matches!(event, ModuleEvent::Loaded { .. }));
+
+                let should_stop =
+                    hit.condition_met || (library_loaded && self.break_on_library_load);
 
-                //TODO fix breakpoints completely, they broke again xddddddddddddddddddddddddddddddddddd
-                if let Some(bp) = debugee.breakpoint_at_address(rip)
-                    && !bp.hardware()
-                {
-                    let new_rip = rip + bp.size() as u64;
-                    debugee.set_rip(new_rip);
+                if !should_stop {
+                    //the breakpoint on ld.so's notification is the only chance before the library runs
+                    let errors = self.project_pending.apply(debugee);
+                    if !errors.is_empty() {
+                        self.status = errors.join(", ");
+                    }
+
+                    if let Err(error) = debugee.r#continue() {
+                        self.status = error;
+                    }
+                    return;
                 }
+
+                self.status = match hit.condition_error {
+                    Some(error) => format!(
+                        "Breakpoint at {:#x} hit, failed to evaluate condition: {error}",
+                        hit.address
+                    ),
+                    None if !hit.module_events.is_empty() => hit
+                        .module_events
+                        .iter()
+                        .map(|event| match event {
+                            ModuleEvent::Loaded { path, load_bias } => {
+                                format!("Loaded {path} ({load_bias:#x})")
+                            }
+                            ModuleEvent::Unloaded { path, load_bias } => {
+                                format!("Unloaded {path} ({load_bias:#x})")
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    None if hit.internal => format!("Reached {:#x}", hit.address),
+                    None => format!("Breakpoint at {:#x} hit", hit.address),
+                };
+            }
+
+            let errors = self.project_pending.apply(debugee);
+            if !errors.is_empty() {
+                self.status = format!("{}, {}", self.status, errors.join(", "));
+            }
+
+            if debugee.source_step_progress() == Some(StepProgress::GaveUp) {
+                self.status = format!(
+                    "Gave up stepping after {} instructions without reaching another line",
+                    debugger::MAX_STEP_INSTRUCTIONS
+                );
+            }
+
+            //a step over or run to cursor that got interrupted by something else is abandoned
+            debugee.remove_internal_breakpoints();
+            debugee.cancel_source_step();
+
+            if let Some(location) = debugee.symbols().source_location(debugee.context().rip) {
+                let file_name = location.path.rsplit('/').next().unwrap_or(location.path);
+                self.status = format!("{} at {file_name}:{}", self.status, location.line);
             }
         }
+
+        debugee.refresh_memory_map();
+
+        self.disassembly_view.set_rip(debugee.context().rip);
+        self.disassembly_view.refresh_cache(debugee);
+
+        self.hex_view.update_cache(debugee);
+        self.hex_view.clean_cache();
     }
 }
 
@@ -213,6 +598,7 @@ impl eframe::App for App {
 
             if self.regs_dirty {
                 debugee.update_context();
+                self.telescopes.clear();
             }
         }
 
@@ -277,6 +663,91 @@ impl eframe::App for App {
             }
         }
 
+        if self.render_debug_info_modal {
+            let modal = egui_modal::Modal::new(ctx, "debug_info_modal");
+            modal.open();
+
+            modal.show(|ui| {
+                modal.title(ui, "Debug info");
+
+                modal.frame(ui, |ui| {
+                    ui.label("Debug directories (one per line)");
+                    ui.text_edit_multiline(&mut self.debug_directories_input);
+
+                    ui.label("debuginfod cache");
+                    ui.text_edit_singleline(&mut self.debuginfod_cache_input);
+                });
+
+                modal.buttons(ui, |ui| {
+                    if modal.button(ui, "Cancel").clicked() {
+                        modal.close();
+                        self.render_debug_info_modal = false;
+                    }
+
+                    if modal.suggested_button(ui, "Apply").clicked() {
+                        modal.close();
+                        self.render_debug_info_modal = false;
+
+                        self.debug_info = DebugInfoSettings {
+                            directories: self
+                                .debug_directories_input
+                                .lines()
+                                .map(str::trim)
+                                .filter(|line| !line.is_empty())
+                                .map(str::to_owned)
+                                .collect(),
+                            debuginfod_cache: Some(self.debuginfod_cache_input.trim())
+                                .filter(|path| !path.is_empty())
+                                .map(str::to_owned),
+                        };
+                        self.apply_symbol_settings();
+                    }
+                });
+            });
+        }
+
+        if self.render_pretty_printers_modal {
+            let modal = egui_modal::Modal::new(ctx, "pretty_printers_modal");
+            modal.open();
+
+            modal.show(|ui| {
+                modal.title(ui, "Pretty-printers");
+
+                modal.frame(ui, |ui| {
+                    ui.label("One per line: <type regex> => <summary with {member.path}>");
+                    ui.label("Optionally followed by ; <pointer>[<length>] to list elements");
+                    ui.weak("^MyVec<.*> => {len} items ; data[len]");
+                    ui.add(
+                        egui::TextEdit::multiline(&mut self.pretty_printers_input)
+                            .code_editor()
+                            .desired_width(f32::INFINITY),
+                    );
+
+                    if let Some(error) = &self.pretty_printers_error {
+                        ui.colored_label(ui.visuals().error_fg_color, error);
+                    }
+                });
+
+                modal.buttons(ui, |ui| {
+                    if modal.button(ui, "Cancel").clicked() {
+                        modal.close();
+                        self.render_pretty_printers_modal = false;
+                    }
+
+                    //stays open on errors so they can be fixed
+                    if modal.suggested_button(ui, "Apply").clicked() {
+                        match self.pretty_printers.set_custom(&self.pretty_printers_input) {
+                            Ok(()) => {
+                                modal.close();
+                                self.render_pretty_printers_modal = false;
+                            }
+                            Err(error) => self.pretty_printers_error = Some(error),
+                        }
+                    }
+                });
+            });
+        }
+
         let open_shortcut = egui::KeyboardShortcut::new(egui::Modifiers::CTRL, egui::Key::O);
         let attach_shortcut = egui::KeyboardShortcut::new(egui::Modifiers::CTRL, egui::Key::A);
 
@@ -286,6 +757,15 @@ impl eframe::App for App {
             }
         }
 
+        if self
+            .debugee
+            .as_ref()
+            .and_then(Debugee::source_step_progress)
+            == Some(StepProgress::Paused)
+        {
+            ctx.request_repaint();
+        }
+
         egui::TopBottomPanel::top("menu_bar").show(ctx, |ui| {
             if !ui.ctx().wants_keyboard_input()
                 && ui.input_mut(|i| i.consume_shortcut(&open_shortcut))
@@ -340,6 +820,97 @@ impl eframe::App for App {
                         let _ = self.refresh_process_list();
                         self.render_attach_modal = true;
                     }
+
+                    ui.separator();
+
+                    if ui
+                        .add_enabled(self.debugee.is_some(), egui::Button::new("Save project"))
+                        .on_hover_text(
+                            "Breakpoints, labels, comments, patches and layout for this binary, \
+                             loaded again whenever it's opened or attached to",
+                        )
+                        .clicked()
+                    {
+                        self.status = match self.save_project() {
+                            Ok(path) => format!("Saved project to {}", path.display()),
+                            Err(error) => format!("Failed to save project: {error}"),
+                        };
+                        ui.close_menu();
+                    }
+                });
+
+                ui.menu_button("Options", |ui| {
+                    ui.style_mut().wrap = Some(false);
+                    ui.checkbox(&mut self.break_on_library_load, "Break on library load");
+
+                    ui.separator();
+
+                    let demangle = self.demangle;
+                    ui.checkbox(&mut self.demangle.demangle, "Demangle names");
+                    ui.add_enabled(
+                        self.demangle.demangle,
+                        egui::Checkbox::new(&mut self.demangle.hide_hash, "Hide Rust hashes"),
+                    );
+
+                    if self.demangle != demangle {
+                        self.apply_symbol_settings();
+                    }
+
+                    ui.checkbox(
+                        &mut self.disassembly_view.interleave_source,
+                        "Interleave source in disassembly",
+                    );
+
+                    ui.menu_button("Disassembly syntax", |ui| {
+                        let mut format = self.disassembly_view.format();
+
+                        for syntax in Syntax::ALL {
+                            ui.radio_value(&mut format.syntax, syntax, syntax.name());
+                        }
+
+                        ui.separator();
+
+                        ui.label("Hex numbers");
+                        for style in HexStyle::ALL {
+                            ui.radio_value(&mut format.hex_style, style, style.name());
+                        }
+
+                        ui.label("Memory operand size");
+                        for size in MemorySize::ALL {
+                            ui.radio_value(&mut format.memory_size, size, size.name());
+                        }
+
+                        ui.separator();
+
+                        ui.checkbox(&mut format.uppercase, "Uppercase");
+                        ui.add_enabled(
+                            format.syntax != Syntax::Fast,
+                            egui::Checkbox::new(
+                                &mut format.branch_size,
+                                "Branch size (short/near)",
+                            ),
+                        );
+
+                        self.disassembly_view.set_format(format);
+                    });
+
+                    ui.separator();
+
+                    if ui.button("Debug directories...").clicked() {
+                        self.debug_directories_input = self.debug_info.directories.join("\n");
+                        self.debuginfod_cache_input =
+                            self.debug_info.debuginfod_cache.clone().unwrap_or_default();
+                        self.render_debug_info_modal = true;
+                        ui.close_menu();
+                    }
+
+                    if ui.button("Pretty-printers...").clicked() {
+                        self.pretty_printers_input =
+                            self.pretty_printers.custom_source().to_owned();
+                        self.pretty_printers_error = None;
+                        self.render_pretty_printers_modal = true;
+                        ui.close_menu();
+                    }
                 });
             });
         });
@@ -357,6 +928,7 @@ impl eframe::App for App {
                         //detach
                         //TODO: icon
                         if ui.button("DETACH").clicked() {
+                            self.autosave_project();
                             if let Some(debugee) = self.debugee.as_mut() {
                                 debugee.detach();
                             }
@@ -368,12 +940,15 @@ impl eframe::App for App {
                             ));
 
                             self.hex_view.purge_cache();
+                            self.stack_view.purge_cache();
                             self.disassembly_view.purge_cache();
+                            self.source_view.purge_cache();
                         }
 
                         ui.separator();
 
                         if ui.button("⏹").clicked() {
+                            self.autosave_project();
                             if let Some(debugee) = self.debugee.as_mut() {
                                 debugee.kill();
                             }
@@ -384,23 +959,39 @@ impl eframe::App for App {
                             ));
 
                             self.hex_view.purge_cache();
+                            self.stack_view.purge_cache();
                             self.disassembly_view.purge_cache();
+                            self.source_view.purge_cache();
+                        }
+
+                        if ui
+                            .add_enabled(self.launch_path.is_some(), egui::Button::new("🔄"))
+                            .on_hover_text("Restart")
+                            .clicked()
+                            && let Err(error) = self.restart()
+                        {
+                            self.status = format!("Failed to restart: {error}");
                         }
 
                         if ui.button("▶").clicked() {
                             if let Some(debugee) = self.debugee.as_mut() {
                                 if debugee.stopped {
-                                    debugee.r#continue();
-                                    self.status = String::from("Resumed");
+                                    self.status = match debugee.r#continue() {
+                                        Ok(()) => String::from("Resumed"),
+                                        Err(error) => error,
+                                    };
                                 }
                             }
                         }
 
                         if ui.button("⏸").clicked() {
                             if let Some(debugee) = self.debugee.as_mut() {
-                                if !debugee.stopped {
+                                if debugee.source_step_progress() == Some(StepProgress::Paused) {
+                                    debugee.cancel_source_step();
+                                    self.status = String::from("Step cancelled");
+                                } else if !debugee.stopped {
                                     debugee.stop();
-                                    self.status = String::from("Stopped");
+                                    self.status = String::from("Stopping...");
                                 }
                             }
                         }
@@ -417,20 +1008,167 @@ impl eframe::App for App {
                                 }
                             }
                         }
+
+                        if ui.button("⏭").on_hover_text("Step over").clicked()
+                            && let Some(debugee) = self.debugee.as_mut()
+                        {
+                            if debugee.stopped {
+                                debugee.step_over();
+                                self.status = String::from("Stepping over");
+                            } else {
+                                self.status = String::from("Can't step over while unstopped");
+                            }
+                        }
+
+                        ui.separator();
+
+                        for (icon, name, kind) in [
+                            ("⤵", "Step into line", StepKind::Into),
+                            ("⤼", "Step over line", StepKind::Over),
+                            ("⤴", "Step out", StepKind::Out),
+                        ] {
+                            if ui.button(icon).on_hover_text(name).clicked()
+                                && let Some(debugee) = self.debugee.as_mut()
+                            {
+                                if debugee.stopped {
+                                    debugee.step_source(kind);
+                                    self.status = format!("{name}...");
+                                } else {
+                                    self.status =
+                                        format!("Can't {} while unstopped", name.to_lowercase());
+                                }
+                            }
+                        }
                     });
                 });
 
+                //every view that cares about a frame shows the one selected in the call stack
+                self.call_stack_view.update(&self.debugee);
+                let frame_registers = self
+                    .debugee
+                    .as_ref()
+                    .map(|debugee| self.call_stack_view.registers(debugee));
+
                 egui::TopBottomPanel::bottom("data")
                     .min_height(200.0)
                     .show_inside(ui, |ui| {
-                        self.hex_view.show(ui, &mut self.debugee);
+                        egui::SidePanel::right("stack")
+                            .resizable(true)
+                            .default_width(500.0)
+                            .show_inside(ui, |ui| {
+                                if let Some(follow) = self.stack_view.show(ui, &self.debugee) {
+                                    self.follow(follow);
+                                }
+                            });
+
+                        ui.horizontal(|ui| {
+                            ui.selectable_value(&mut self.bottom_tab, BottomTab::Dump, "Dump");
+                            ui.selectable_value(
+                                &mut self.bottom_tab,
+                                BottomTab::Breakpoints,
+                                "Breakpoints",
+                            );
+                            ui.selectable_value(
+                                &mut self.bottom_tab,
+                                BottomTab::Patches,
+                                "Patches",
+                            );
+                            ui.selectable_value(
+                                &mut self.bottom_tab,
+                                BottomTab::Bookmarks,
+                                "Bookmarks",
+                            );
+                            ui.selectable_value(
+                                &mut self.bottom_tab,
+                                BottomTab::MemoryMap,
+                                "Memory Map",
+                            );
+                            ui.selectable_value(
+                                &mut self.bottom_tab,
+                                BottomTab::Modules,
+                                "Modules",
+                            );
+                            ui.selectable_value(
+                                &mut self.bottom_tab,
+                                BottomTab::Symbols,
+                                "Symbols",
+                            );
+                            ui.selectable_value(
+                                &mut self.bottom_tab,
+                                BottomTab::Exports,
+                                "Exports",
+                            );
+                            ui.selectable_value(
+                                &mut self.bottom_tab,
+                                BottomTab::Imports,
+                                "Imports",
+                            );
+                            ui.selectable_value(&mut self.bottom_tab, BottomTab::Locals, "Locals");
+                            ui.selectable_value(
+                                &mut self.bottom_tab,
+                                BottomTab::CallStack,
+                                "Call Stack",
+                            );
+                        });
+
+                        ui.separator();
+
+                        let follow = match self.bottom_tab {
+                            BottomTab::Dump => {
+                                self.hex_view.show(ui, &mut self.debugee);
+                                None
+                            }
+                            BottomTab::Breakpoints => {
+                                self.breakpoints_view
+                                    .show(ui, &mut self.debugee, &mut self.status)
+                            }
+                            BottomTab::Patches => {
+                                self.patches_view
+                                    .show(ui, &mut self.debugee, &mut self.status)
+                            }
+                            BottomTab::Bookmarks => {
+                                self.bookmarks_view
+                                    .show(ui, &mut self.debugee, &mut self.status)
+                            }
+                            BottomTab::MemoryMap => {
+                                self.memory_map_view.show_regions(ui, &self.debugee)
+                            }
+                            BottomTab::Modules => self.memory_map_view.show_modules(
+                                ui,
+                                &self.debugee,
+                                &self.module_events,
+                            ),
+                            BottomTab::Symbols => self.symbols_view.show(ui, &self.debugee),
+                            BottomTab::Exports => {
+                                self.dynamic_symbols_view.show_exports(ui, &self.debugee)
+                            }
+                            BottomTab::Imports => {
+                                self.dynamic_symbols_view.show_imports(ui, &self.debugee)
+                            }
+                            BottomTab::Locals => self.locals_view.show(
+                                ui,
+                                &self.debugee,
+                                frame_registers,
+                                &self.pretty_printers,
+                            ),
+                            BottomTab::CallStack => self.call_stack_view.show(ui, &self.debugee),
+                        };
+
+                        if let Some(follow) = follow {
+                            self.follow(follow);
+                        }
                     });
 
                 egui::SidePanel::right("registers")
                     .min_width(275.0)
-                    .max_width(300.0)
+                    .default_width(420.0)
+                    .max_width(700.0)
                     .show_inside(ui, |ui| {
-                        if let Some(debugee) = self.debugee.as_mut() {
+                        if self.call_stack_view.selected_caller().is_some()
+                            && let Some(debugee) = &self.debugee
+                        {
+                            self.call_stack_view.show_frame_registers(ui, debugee);
+                        } else if let Some(debugee) = self.debugee.as_mut() {
                             let is_dirty = self.regs_dirty;
 
                             instruction!(self, ui, debugee, rax, is_dirty);
@@ -469,6 +1207,18 @@ impl eframe::App for App {
                         }
                     });
 
+                egui::SidePanel::left("source")
+                    .resizable(true)
+                    .default_width(450.0)
+                    .show_inside(ui, |ui| {
+                        self.source_view.show(
+                            ui,
+                            &mut self.debugee,
+                            &mut self.status,
+                            frame_registers.map(|registers| registers.location()),
+                        );
+                    });
+
                 egui::CentralPanel::default().show_inside(ui, |ui| {
                     if let Some(debugee) = self.debugee.as_mut() {
                         self.disassembly_view.show(ui, debugee);
@@ -479,4 +1229,8 @@ impl eframe::App for App {
             });
         });
     }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        self.autosave_project();
+    }
 }
@@ -0,0 +1,156 @@
+use eframe::egui;
+
+use super::widgets;
+use super::Follow;
+use crate::debugger::notes::ModuleAddress;
+use crate::debugger::Debugee;
+
+enum Action {
+    SetLabel(ModuleAddress, String),
+    SetComment(ModuleAddress, String),
+    SetBookmark(ModuleAddress, bool),
+    Remove(ModuleAddress),
+}
+
+pub struct BookmarksView {
+    is_display_dirty: bool,
+    /// Every address with a label or comment, not only the bookmarked ones
+    show_all: bool,
+}
+
+impl BookmarksView {
+    pub const fn new() -> Self {
+        Self {
+            is_display_dirty: true,
+            show_all: false,
+        }
+    }
+
+    /// Bookmarked addresses with their labels and comments, double clicking one follows it in the
+    /// disassembly
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        debugee: &mut Option<Debugee>,
+        status: &mut String,
+    ) -> Option<Follow> {
+        let Some(debugee) = debugee else {
+            ui.label("No process");
+            return None;
+        };
+
+        let mut actions = Vec::new();
+        let mut follow = None;
+
+        ui.checkbox(&mut self.show_all, "Everything labeled or commented");
+        ui.separator();
+
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                egui::Grid::new("bookmarks_grid")
+                    .num_columns(6)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.label("🔖");
+                        ui.label("Location");
+                        ui.label("Address");
+                        ui.label("Label");
+                        ui.label("Comment");
+                        ui.label("");
+                        ui.end_row();
+
+                        let notes = debugee
+                            .notes()
+                            .iter()
+                            .filter(|(_, note)| self.show_all || note.bookmark);
+
+                        for (location, note) in notes {
+                            let id = location.to_string();
+
+                            let mut bookmark = note.bookmark;
+                            if ui.checkbox(&mut bookmark, "").changed() {
+                                actions.push(Action::SetBookmark(location.clone(), bookmark));
+                            }
+
+                            let address = debugee.resolve_module_address(location);
+                            let address_text = address.map_or_else(
+                                || String::from("not loaded"),
+                                |address| format!("{address:#x}"),
+                            );
+
+                            for text in [id.clone(), address_text] {
+                                if ui
+                                    .add(
+                                        egui::Label::new(egui::RichText::new(text).monospace())
+                                            .sense(egui::Sense::click()),
+                                    )
+                                    .double_clicked()
+                                    && let Some(address) = address
+                                {
+                                    follow = Some(Follow::Disassembly(address));
+                                }
+                            }
+
+                            let mut label = note.label.clone().unwrap_or_default();
+                            let mut modified = false;
+                            ui.add(widgets::editable_label(
+                                &mut label,
+                                &mut modified,
+                                self.is_display_dirty,
+                                64,
+                                150.0,
+                                format!("__note_label_{id}"),
+                            ));
+
+                            if modified {
+                                actions.push(Action::SetLabel(location.clone(), label));
+                            }
+
+                            let mut comment = note.comment.clone().unwrap_or_default();
+                            let mut modified = false;
+                            ui.add(widgets::editable_label(
+                                &mut comment,
+                                &mut modified,
+                                self.is_display_dirty,
+                                256,
+                                300.0,
+                                format!("__note_comment_{id}"),
+                            ));
+
+                            if modified {
+                                actions.push(Action::SetComment(location.clone(), comment));
+                            }
+
+                            if ui
+                                .button("🗑")
+                                .on_hover_text("Removes the label and comment too")
+                                .clicked()
+                            {
+                                actions.push(Action::Remove(location.clone()));
+                            }
+
+                            ui.end_row();
+                        }
+                    });
+            });
+
+        self.is_display_dirty = !actions.is_empty();
+
+        let notes = debugee.notes_mut();
+        for action in actions {
+            match action {
+                Action::SetLabel(location, label) => {
+                    if let Err(error) = notes.set_label(location, &label) {
+                        *status = error;
+                    }
+                }
+                Action::SetComment(location, comment) => notes.set_comment(location, &comment),
+                Action::SetBookmark(location, bookmark) => notes.set_bookmark(location, bookmark),
+                Action::Remove(location) => notes.remove(&location),
+            }
+        }
+
+        follow
+    }
+}
@@ -0,0 +1,298 @@
+use eframe::egui;
+
+use super::widgets;
+use super::Follow;
+use crate::debugger::breakpoint::BreakpointId;
+use crate::debugger::Debugee;
+
+enum Action {
+    SetEnabled(BreakpointId, bool),
+    Move(BreakpointId, u64),
+    SetCondition(BreakpointId, Option<String>),
+    SetGroup(BreakpointId, Option<String>),
+    SetOneShot(BreakpointId, bool),
+    Remove(BreakpointId),
+    SetAllEnabled(bool),
+    SetGroupEnabled(String, bool),
+    RemoveAll,
+    AddSymbolic(String, bool),
+    RemoveSymbolic(usize),
+}
+
+pub struct BreakpointsView {
+    is_display_dirty: bool,
+    symbol_input: String,
+    symbol_is_regex: bool,
+}
+
+impl BreakpointsView {
+    pub const fn new() -> Self {
+        Self {
+            is_display_dirty: true,
+            symbol_input: String::new(),
+            symbol_is_regex: false,
+        }
+    }
+
+    /// Returns where to navigate to when a breakpoint gets double clicked
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        debugee: &mut Option<Debugee>,
+        status: &mut String,
+    ) -> Option<Follow> {
+        let Some(debugee) = debugee else {
+            ui.label("No process");
+            return None;
+        };
+
+        let mut actions = Vec::new();
+        let mut follow = None;
+        //placing and removing int3s needs the process stopped
+        let stopped = debugee.stopped;
+
+        ui.add_enabled_ui(stopped, |ui| {
+            ui.horizontal(|ui| {
+                if ui.button("Enable all").clicked() {
+                    actions.push(Action::SetAllEnabled(true));
+                }
+
+                if ui.button("Disable all").clicked() {
+                    actions.push(Action::SetAllEnabled(false));
+                }
+
+                if ui.button("Delete all").clicked() {
+                    actions.push(Action::RemoveAll);
+                }
+
+                for group in debugee.breakpoint_groups() {
+                    ui.separator();
+                    ui.label(&group);
+
+                    if ui.small_button("Enable").clicked() {
+                        actions.push(Action::SetGroupEnabled(group.clone(), true));
+                    }
+
+                    if ui.small_button("Disable").clicked() {
+                        actions.push(Action::SetGroupEnabled(group, false));
+                    }
+                }
+            })
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Symbol");
+
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut self.symbol_input)
+                    .hint_text("malloc, libssl.so!SSL_read, mycrate::parse"),
+            );
+            ui.checkbox(&mut self.symbol_is_regex, "Regex");
+
+            if ui.add_enabled(stopped, egui::Button::new("Add")).clicked()
+                || (response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)))
+            {
+                actions.push(Action::AddSymbolic(
+                    std::mem::take(&mut self.symbol_input),
+                    self.symbol_is_regex,
+                ));
+            }
+        });
+
+        for (i, symbolic) in debugee.symbolic_breakpoints().iter().enumerate() {
+            let description = symbolic.description();
+            let locations = debugee
+                .breakpoints()
+                .iter()
+                .filter(|bp| bp.location.as_ref() == Some(&description))
+                .count();
+
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(stopped, egui::Button::new("🗑").small())
+                    .clicked()
+                {
+                    actions.push(Action::RemoveSymbolic(i));
+                }
+
+                ui.monospace(&description);
+
+                if locations == 0 {
+                    ui.weak("pending");
+                } else {
+                    ui.weak(format!("{locations} location(s)"));
+                }
+            });
+        }
+
+        ui.separator();
+
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                egui::Grid::new("breakpoints_grid")
+                    .num_columns(10)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.label("");
+                        ui.label("#");
+                        ui.label("Address");
+                        ui.label("Symbol");
+                        ui.label("Type");
+                        ui.label("Hits");
+                        ui.label("Condition");
+                        ui.label("Group");
+                        ui.label("One-shot");
+                        ui.label("");
+                        ui.end_row();
+
+                        for bp in debugee.breakpoints().iter().filter(|bp| !bp.internal) {
+                            let id = bp.id;
+                            let address = bp.breakpoint.address();
+
+                            let mut enabled = bp.breakpoint.enabled();
+                            if ui
+                                .add_enabled(stopped, egui::Checkbox::new(&mut enabled, ""))
+                                .changed()
+                            {
+                                actions.push(Action::SetEnabled(id, enabled));
+                            }
+
+                            ui.label(id.to_string());
+
+                            let mut address_text = format!("{address:#x}");
+                            let mut modified = false;
+                            ui.add_enabled(
+                                stopped,
+                                widgets::editable_label(
+                                    &mut address_text,
+                                    &mut modified,
+                                    self.is_display_dirty,
+                                    18,
+                                    135.0,
+                                    format!("__bp_address_{id}"),
+                                ),
+                            );
+
+                            if modified {
+                                match address_text
+                                    .rsplit('x')
+                                    .next()
+                                    .map(|hex| u64::from_str_radix(hex, 16))
+                                {
+                                    Some(Ok(new_address)) => {
+                                        actions.push(Action::Move(id, new_address))
+                                    }
+                                    _ => *status = format!("Invalid address {address_text}"),
+                                }
+                            }
+
+                            let symbol = debugee.describe_address(address).unwrap_or_default();
+
+                            let kind = if bp.breakpoint.hardware() {
+                                "Hardware"
+                            } else {
+                                "Software"
+                            };
+
+                            for text in [symbol, kind.to_owned(), bp.hit_count.to_string()] {
+                                if ui
+                                    .add(egui::Label::new(text).sense(egui::Sense::click()))
+                                    .double_clicked()
+                                {
+                                    follow = Some(Follow::Disassembly(address));
+                                }
+                            }
+
+                            let mut condition = bp.condition.clone().unwrap_or_default();
+                            let mut modified = false;
+                            ui.add(widgets::editable_label(
+                                &mut condition,
+                                &mut modified,
+                                self.is_display_dirty,
+                                256,
+                                200.0,
+                                format!("__bp_condition_{id}"),
+                            ));
+
+                            if modified {
+                                actions.push(Action::SetCondition(id, Some(condition)));
+                            }
+
+                            let mut group = bp.group.clone().unwrap_or_default();
+                            let mut modified = false;
+                            ui.add(widgets::editable_label(
+                                &mut group,
+                                &mut modified,
+                                self.is_display_dirty,
+                                64,
+                                100.0,
+                                format!("__bp_group_{id}"),
+                            ));
+
+                            if modified {
+                                actions.push(Action::SetGroup(id, Some(group)));
+                            }
+
+                            let mut one_shot = bp.one_shot;
+                            if ui.checkbox(&mut one_shot, "").changed() {
+                                actions.push(Action::SetOneShot(id, one_shot));
+                            }
+
+                            if ui.add_enabled(stopped, egui::Button::new("🗑")).clicked() {
+                                actions.push(Action::Remove(id));
+                            }
+
+                            ui.end_row();
+                        }
+                    });
+            });
+
+        self.is_display_dirty = !actions.is_empty();
+
+        for action in actions {
+            let touches_memory = !matches!(
+                action,
+                Action::SetCondition(..) | Action::SetGroup(..) | Action::SetOneShot(..)
+            );
+            if touches_memory && !debugee.stopped {
+                *status = String::from("Can't change breakpoints while unstopped");
+                continue;
+            }
+
+            match action {
+                Action::SetEnabled(id, enabled) => debugee.set_breakpoint_enabled(id, enabled),
+                Action::Move(id, new_address) => {
+                    if let Err(error) = debugee.move_breakpoint(id, new_address) {
+                        *status = error;
+                    }
+                }
+                Action::SetCondition(id, condition) => {
+                    debugee.set_breakpoint_condition(id, condition)
+                }
+                Action::SetGroup(id, group) => debugee.set_breakpoint_group(id, group),
+                Action::SetOneShot(id, one_shot) => debugee.set_breakpoint_one_shot(id, one_shot),
+                Action::Remove(id) => debugee.remove_breakpoint(id),
+                Action::SetAllEnabled(enabled) => debugee.set_all_breakpoints_enabled(enabled),
+                Action::SetGroupEnabled(group, enabled) => {
+                    debugee.set_group_enabled(&group, enabled)
+                }
+                Action::RemoveAll => debugee.remove_all_breakpoints(),
+                Action::AddSymbolic(pattern, is_regex) => {
+                    match debugee.add_symbolic_breakpoint(&pattern, is_regex) {
+                        Ok(0) => {
+                            *status = format!("{pattern} is pending until a module defines it")
+                        }
+                        Ok(locations) => {
+                            *status = format!("{pattern} resolved to {locations} location(s)")
+                        }
+                        Err(error) => *status = error,
+                    }
+                }
+                Action::RemoveSymbolic(index) => debugee.remove_symbolic_breakpoint(index),
+            }
+        }
+
+        follow
+    }
+}
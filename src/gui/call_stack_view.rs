@@ -0,0 +1,220 @@
+use eframe::egui;
+
+use super::Follow;
+use crate::debugger::unwind::{FrameRegisters, StackFrame, UnwindMethod};
+use crate::debugger::Debugee;
+
+/// DWARF numbers of the registers shown for a selected frame, in the order the registers panel has them
+const REGISTERS: [(&str, u16); 17] = [
+    ("RAX", 0),
+    ("RBX", 3),
+    ("RCX", 2),
+    ("RDX", 1),
+    ("R8", 8),
+    ("R9", 9),
+    ("R10", 10),
+    ("R11", 11),
+    ("R12", 12),
+    ("R13", 13),
+    ("R14", 14),
+    ("R15", 15),
+    ("RSI", 4),
+    ("RDI", 5),
+    ("RBP", 6),
+    ("RSP", 7),
+    ("RIP", 16),
+];
+
+pub struct CallStackView {
+    frames: Vec<StackFrame>,
+    /// Symbol table generation and registers the stack was unwound from
+    built_for: Option<(u64, FrameRegisters)>,
+    selected: usize,
+    /// Value and telescope of every register of the selected caller, `None` when it wasn't saved
+    telescopes: Vec<Option<(u64, String)>>,
+    telescopes_for: Option<(u64, FrameRegisters, usize)>,
+}
+
+impl CallStackView {
+    pub const fn new() -> Self {
+        Self {
+            frames: Vec::new(),
+            built_for: None,
+            selected: 0,
+            telescopes: Vec::new(),
+            telescopes_for: None,
+        }
+    }
+
+    /// Unwinds again whenever the process stopped somewhere else, going back to the innermost frame
+    pub fn update(&mut self, debugee: &Option<Debugee>) {
+        let Some(debugee) = debugee.as_ref().filter(|debugee| debugee.stopped) else {
+            self.frames.clear();
+            self.built_for = None;
+            self.selected = 0;
+            return;
+        };
+
+        let key = (
+            debugee.symbols().generation(),
+            FrameRegisters::from_context(debugee.context()),
+        );
+
+        if self.built_for != Some(key) {
+            self.frames = debugee.backtrace();
+            self.built_for = Some(key);
+            self.selected = 0;
+        }
+    }
+
+    /// Registers of the selected frame, the process' own when it's running
+    pub fn registers(&self, debugee: &Debugee) -> FrameRegisters {
+        match self.frames.get(self.selected) {
+            Some(frame) => frame.registers,
+            None => FrameRegisters::from_context(debugee.context()),
+        }
+    }
+
+    /// Only callers are worth showing apart from the live registers
+    pub fn selected_caller(&self) -> Option<(usize, &StackFrame)> {
+        (self.selected > 0).then(|| (self.selected, &self.frames[self.selected]))
+    }
+
+    /// Clicking a frame selects it and shows its code, the context menu follows its stack
+    pub fn show(&mut self, ui: &mut egui::Ui, debugee: &Option<Debugee>) -> Option<Follow> {
+        let Some(debugee) = debugee else {
+            ui.label("No process");
+            return None;
+        };
+
+        if !debugee.stopped {
+            ui.label("Process is running");
+            return None;
+        }
+
+        let mut follow = None;
+
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                egui::Grid::new("call_stack_grid")
+                    .num_columns(5)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.label("#");
+                        ui.label("Address");
+                        ui.label("Symbol");
+                        ui.label("Source");
+                        ui.label("Unwound by");
+                        ui.end_row();
+
+                        for (index, frame) in self.frames.iter().enumerate() {
+                            let pc = frame.registers.pc();
+
+                            let response = ui.selectable_label(
+                                self.selected == index,
+                                egui::RichText::new(format!("{index}")).monospace(),
+                            );
+
+                            if response.clicked() {
+                                self.selected = index;
+                                follow = Some(Follow::Disassembly(pc));
+                            }
+
+                            response.context_menu(|ui| {
+                                if ui.button("Follow in disassembly").clicked() {
+                                    follow = Some(Follow::Disassembly(pc));
+                                    ui.close_menu();
+                                }
+
+                                if let Some(rsp) = frame.registers.sp()
+                                    && ui.button("Follow stack in dump").clicked()
+                                {
+                                    follow = Some(Follow::Dump(rsp));
+                                    ui.close_menu();
+                                }
+                            });
+
+                            ui.monospace(format!("{pc:#014x}"));
+                            ui.label(debugee.describe_address(pc).unwrap_or_default());
+
+                            match debugee
+                                .symbols()
+                                .source_location(frame.registers.location())
+                            {
+                                Some(location) => ui
+                                    .label(format!(
+                                        "{}:{}",
+                                        location.path.rsplit('/').next().unwrap_or(location.path),
+                                        location.line
+                                    ))
+                                    .on_hover_text(location.path),
+                                None => ui.weak("?"),
+                            };
+
+                            ui.weak(match frame.method {
+                                UnwindMethod::Context => "",
+                                UnwindMethod::CallFrameInfo => "CFI",
+                                UnwindMethod::FramePointer => "RBP chain",
+                                UnwindMethod::Signal => "Signal frame",
+                            });
+
+                            ui.end_row();
+                        }
+                    });
+            });
+
+        follow
+    }
+
+    /// Registers recovered for the selected caller, read only since they only exist on the stack if at all
+    pub fn show_frame_registers(&mut self, ui: &mut egui::Ui, debugee: &Debugee) {
+        let Some((index, frame)) = self.selected_caller() else {
+            return;
+        };
+
+        //telescoping reads memory, only do it again once the process stopped somewhere else
+        let key = self
+            .built_for
+            .map(|(generation, registers)| (generation, registers, index));
+        if self.telescopes_for != key {
+            self.telescopes = REGISTERS
+                .iter()
+                .map(|&(_, register)| {
+                    frame
+                        .registers
+                        .get(register)
+                        .map(|value| (value, debugee.telescope(value).describe()))
+                })
+                .collect();
+            self.telescopes_for = key;
+        }
+
+        ui.label(format!("Frame #{index}"));
+        ui.separator();
+
+        for ((name, _), telescope) in REGISTERS.iter().zip(&self.telescopes) {
+            ui.horizontal(|ui| {
+                ui.add_sized(egui::vec2(32.0, 4.0), egui::Label::new(format!("{name}:")));
+
+                match telescope {
+                    Some((value, telescope)) => {
+                        ui.monospace(format!("0x{value:016x}"));
+                        ui.add(
+                            egui::Label::new(egui::RichText::new(telescope).monospace().weak())
+                                .truncate(true),
+                        )
+                        .on_hover_text(telescope);
+                    }
+                    None => {
+                        ui.weak("<not saved>");
+                    }
+                };
+            });
+
+            if matches!(*name, "RDX" | "R15" | "RDI") {
+                ui.separator();
+            }
+        }
+    }
+}
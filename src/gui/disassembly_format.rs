@@ -0,0 +1,271 @@
+use eframe::egui;
+use iced_x86::{Formatter, FormatterTextKind};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Syntax {
+    Nasm,
+    Masm,
+    /// AT&T
+    Gas,
+    /// Intel-ish, no options besides hex and memory sizes but a lot quicker
+    Fast,
+}
+
+impl Syntax {
+    pub const ALL: [Self; 4] = [Self::Nasm, Self::Masm, Self::Gas, Self::Fast];
+
+    /// Name in project files
+    pub const fn key(self) -> &'static str {
+        match self {
+            Self::Nasm => "nasm",
+            Self::Masm => "masm",
+            Self::Gas => "gas",
+            Self::Fast => "fast",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|x| x.key() == key)
+    }
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Nasm => "Intel (NASM)",
+            Self::Masm => "Intel (MASM)",
+            Self::Gas => "AT&T (GAS)",
+            Self::Fast => "Fast",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum HexStyle {
+    /// Whatever the syntax usually uses
+    Default,
+    /// `0x10`
+    Prefix,
+    /// `10h`
+    Suffix,
+}
+
+impl HexStyle {
+    pub const ALL: [Self; 3] = [Self::Default, Self::Prefix, Self::Suffix];
+
+    /// Name in project files
+    pub const fn key(self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::Prefix => "prefix",
+            Self::Suffix => "suffix",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|x| x.key() == key)
+    }
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Default => "Syntax default",
+            Self::Prefix => "0x10",
+            Self::Suffix => "10h",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum MemorySize {
+    /// Only when the other operands don't already tell
+    Default,
+    Always,
+    /// Only when the instruction can't be assembled without it
+    Minimal,
+    Never,
+}
+
+impl MemorySize {
+    pub const ALL: [Self; 4] = [Self::Default, Self::Always, Self::Minimal, Self::Never];
+
+    /// Name in project files
+    pub const fn key(self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::Always => "always",
+            Self::Minimal => "minimal",
+            Self::Never => "never",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|x| x.key() == key)
+    }
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Default => "When ambiguous",
+            Self::Always => "Always",
+            Self::Minimal => "When required",
+            Self::Never => "Never",
+        }
+    }
+
+    const fn options(self) -> iced_x86::MemorySizeOptions {
+        match self {
+            Self::Default => iced_x86::MemorySizeOptions::Default,
+            Self::Always => iced_x86::MemorySizeOptions::Always,
+            Self::Minimal => iced_x86::MemorySizeOptions::Minimal,
+            Self::Never => iced_x86::MemorySizeOptions::Never,
+        }
+    }
+}
+
+/// How instructions in the disassembly get formatted
+#[derive(Clone, Copy, PartialEq)]
+pub struct FormatSettings {
+    pub syntax: Syntax,
+    pub hex_style: HexStyle,
+    /// `MOV RAX,[RBP-8]`, only hex digits for the fast formatter
+    pub uppercase: bool,
+    /// `qword ptr`/`qword` before memory operands
+    pub memory_size: MemorySize,
+    /// `jmp short`/`jmp near`
+    pub branch_size: bool,
+}
+
+impl Default for FormatSettings {
+    fn default() -> Self {
+        Self {
+            syntax: Syntax::Nasm,
+            hex_style: HexStyle::Default,
+            uppercase: false,
+            memory_size: MemorySize::Default,
+            branch_size: true,
+        }
+    }
+}
+
+/// What a piece of formatted instruction is, for coloring it
+#[derive(Clone, Copy, PartialEq)]
+pub enum TokenKind {
+    Mnemonic,
+    Register,
+    Number,
+    /// Brackets and size hints of memory operands
+    Memory,
+    Symbol,
+    Text,
+}
+
+impl TokenKind {
+    pub const fn color(self, dark_mode: bool) -> Option<egui::Color32> {
+        use egui::Color32;
+
+        Some(match (self, dark_mode) {
+            (Self::Mnemonic, true) => Color32::from_rgb(0x7a, 0xb8, 0xf5),
+            (Self::Mnemonic, false) => Color32::from_rgb(0x10, 0x50, 0xa0),
+            (Self::Register, true) => Color32::from_rgb(0xf0, 0xa0, 0x60),
+            (Self::Register, false) => Color32::from_rgb(0xa0, 0x50, 0x00),
+            (Self::Number, true) => Color32::from_rgb(0x90, 0xd0, 0x80),
+            (Self::Number, false) => Color32::from_rgb(0x20, 0x80, 0x20),
+            (Self::Memory, true) => Color32::from_rgb(0xe0, 0xd0, 0x70),
+            (Self::Memory, false) => Color32::from_rgb(0x90, 0x70, 0x00),
+            (Self::Symbol, true) => Color32::from_rgb(0xd0, 0x90, 0xe0),
+            (Self::Symbol, false) => Color32::from_rgb(0x80, 0x30, 0x90),
+            (Self::Text, _) => return None,
+        })
+    }
+}
+
+/// Collects the formatter's output piece by piece along with what each piece is
+#[derive(Default)]
+struct Tokens(Vec<(String, TokenKind)>);
+
+impl iced_x86::FormatterOutput for Tokens {
+    fn write(&mut self, text: &str, kind: FormatterTextKind) {
+        let kind = match kind {
+            FormatterTextKind::Mnemonic | FormatterTextKind::Prefix => TokenKind::Mnemonic,
+            FormatterTextKind::Register => TokenKind::Register,
+            FormatterTextKind::Number => TokenKind::Number,
+            FormatterTextKind::Label | FormatterTextKind::Function | FormatterTextKind::Data => {
+                TokenKind::Symbol
+            }
+            //`qword ptr`, and the brackets (parentheses in AT&T) around the address
+            FormatterTextKind::Keyword => TokenKind::Memory,
+            FormatterTextKind::Punctuation if text.contains(['[', ']', '(', ')']) => {
+                TokenKind::Memory
+            }
+            _ => TokenKind::Text,
+        };
+
+        self.0.push((text.to_owned(), kind));
+    }
+}
+
+/// Formats instructions with the chosen syntax and options
+pub enum InstructionFormatter {
+    Full(Box<dyn Formatter>),
+    Fast(Box<iced_x86::FastFormatter>),
+}
+
+impl InstructionFormatter {
+    pub fn new(
+        settings: FormatSettings,
+        symbol_resolver: Option<Box<dyn iced_x86::SymbolResolver>>,
+    ) -> Self {
+        let mut formatter: Box<dyn Formatter> = match settings.syntax {
+            Syntax::Nasm => Box::new(iced_x86::NasmFormatter::with_options(symbol_resolver, None)),
+            Syntax::Masm => Box::new(iced_x86::MasmFormatter::with_options(symbol_resolver, None)),
+            Syntax::Gas => Box::new(iced_x86::GasFormatter::with_options(symbol_resolver, None)),
+            Syntax::Fast => {
+                //only fails when the trait options disable symbol resolvers, the default ones don't
+                let mut formatter =
+                    iced_x86::FastFormatter::try_with_options(symbol_resolver).unwrap();
+                let options = formatter.options_mut();
+
+                options.set_uppercase_hex(settings.uppercase);
+                options.set_always_show_memory_size(settings.memory_size == MemorySize::Always);
+                match settings.hex_style {
+                    HexStyle::Default => {}
+                    HexStyle::Prefix => options.set_use_hex_prefix(true),
+                    HexStyle::Suffix => options.set_use_hex_prefix(false),
+                }
+
+                return Self::Fast(Box::new(formatter));
+            }
+        };
+
+        let options = formatter.options_mut();
+        options.set_uppercase_all(settings.uppercase);
+        options.set_memory_size_options(settings.memory_size.options());
+        options.set_show_branch_size(settings.branch_size);
+        match settings.hex_style {
+            HexStyle::Default => {}
+            HexStyle::Prefix => {
+                options.set_hex_prefix("0x");
+                options.set_hex_suffix("");
+            }
+            HexStyle::Suffix => {
+                options.set_hex_prefix("");
+                options.set_hex_suffix("h");
+            }
+        }
+
+        Self::Full(formatter)
+    }
+
+    /// The fast formatter can't tell what's what, it comes back as a single `Text` token
+    pub fn format(&mut self, instruction: &iced_x86::Instruction) -> Vec<(String, TokenKind)> {
+        match self {
+            Self::Full(formatter) => {
+                let mut tokens = Tokens::default();
+                formatter.format(instruction, &mut tokens);
+                tokens.0
+            }
+            Self::Fast(formatter) => {
+                let mut text = String::new();
+                formatter.format(instruction, &mut text);
+                vec![(text, TokenKind::Text)]
+            }
+        }
+    }
+}
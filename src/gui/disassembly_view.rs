@@ -1,34 +1,57 @@
-use crate::debugger::Debugee;
+use crate::debugger::{assembler, expression, instruction_info, Debugee};
 use eframe::egui;
-use iced_x86::Formatter;
 
-const CACHE_RANGE: u64 = 0x150;
-
-#[derive(Clone)]
-pub struct Instruction {
-    addr: u64,
-    bytes: Vec<u8>,
-    inner: iced_x86::Instruction,
+use super::disassembly_format::FormatSettings;
+use super::instruction_cache::{Instruction, InstructionCache};
+use super::source_view::SourceFiles;
+
+//height of an instruction row, separator included
+const ROW_HEIGHT: f32 = 24.0;
+//average instruction length, how far the scrollbar moves per row
+const BYTES_PER_ROW: f32 = 4.0;
+//bytes the scrollbar spans at once, egui's f32 offsets get too coarse for whole regions
+const WINDOW_BYTES: u64 = 0x40000;
+//places remembered for going back
+const HISTORY_SIZE: usize = 256;
+//room left of each row for jump arrows
+const ARROW_GUTTER: f32 = 40.0;
+//space between arrows running side by side
+const ARROW_LANE: f32 = 5.0;
+//width of the instruction text, comments line up after it
+const TEXT_WIDTH: f32 = 360.0;
+
+/// Something picked from an instruction's context menu that the view takes care of
+pub enum RowAction {
+    Assemble(u64),
+    EditNotes(u64),
 }
 
 impl Instruction {
     //WARNING!!! THIS SUCKS
     //okay it's not that bad, man
-    pub fn show(&self, ui: &mut egui::Ui, debugee: &mut Debugee, largest_instruction: usize) {
+    /// Returns the response of the instruction text, for selecting and following it.
+    /// `action` is set when something the view handles got picked from the context menu
+    pub fn show(
+        &self,
+        ui: &mut egui::Ui,
+        debugee: &mut Debugee,
+        largest_instruction: usize,
+        selected: bool,
+        action: &mut Option<RowAction>,
+    ) -> egui::Response {
         if debugee.context().rip == self.addr {
             ui.label("▶");
         }
 
-        let mut formatted = String::new();
-        let mut formatter = iced_x86::NasmFormatter::new();
-        formatter.format(&self.inner, &mut formatted);
-
         let mut btn_text = egui::RichText::new("○");
-        if let Some(bp) = debugee.breakpoint_at_address(self.addr) {
+        if let Some(bp) = debugee.user_breakpoint_at_address(self.addr) {
             btn_text = egui::RichText::new("◎");
-            if bp.hardware() {
+            if bp.breakpoint.hardware() {
                 btn_text = btn_text.color(egui::Color32::LIGHT_RED);
             }
+            if !bp.breakpoint.enabled() {
+                btn_text = btn_text.weak();
+            }
         }
 
         if ui
@@ -36,14 +59,15 @@ impl Instruction {
             .clicked()
         {
             let mut should_remove = false;
-            if let Some(bp) = debugee.breakpoint_at_address(self.addr) {
+            if let Some(bp) = debugee.user_breakpoint_at_address(self.addr) {
                 should_remove = true;
 
-                if !bp.hardware() {
-                    debugee.add_hardware_breakpoint(self.addr);
+                if !bp.breakpoint.hardware() {
+                    debugee.try_remove_breakpoint(self.addr);
+                    should_remove = debugee.add_hardware_breakpoint(self.addr).is_err();
                 }
             } else {
-                debugee.add_software_breakpoint(self.addr, self.bytes.len() as u64);
+                debugee.add_software_breakpoint(self.addr);
             }
 
             if should_remove {
@@ -51,10 +75,82 @@ impl Instruction {
             }
         }
 
+        let note = debugee.note(self.addr).cloned().unwrap_or_default();
+
+        let mut address_text = self
+            .location
+            .clone()
+            .unwrap_or_else(|| format!("{:#x}", self.addr));
+        if note.bookmark {
+            address_text.insert_str(0, "🔖 ");
+        }
+
         ui.add_sized(
-            egui::vec2(100.0, 16.0),
-            egui::widgets::Label::new(format!("{:#x}", self.addr)),
-        );
+            egui::vec2(220.0, 16.0),
+            egui::widgets::Label::new(address_text)
+                .truncate(true)
+                .sense(egui::Sense::click()),
+        )
+        .on_hover_text(format!("{:#x}", self.addr))
+        .context_menu(|ui| {
+            if ui.button("Run to here").clicked() {
+                if debugee.stopped {
+                    debugee.run_to(self.addr);
+                }
+                ui.close_menu();
+            }
+
+            if ui.button("Copy instruction").clicked() {
+                ui.output_mut(|output| output.copied_text = self.text());
+                ui.close_menu();
+            }
+
+            if ui.button("Set one-shot breakpoint").clicked() {
+                if debugee.user_breakpoint_at_address(self.addr).is_none() {
+                    let id = debugee.add_software_breakpoint(self.addr);
+                    debugee.set_breakpoint_one_shot(id, true);
+                }
+                ui.close_menu();
+            }
+
+            if ui.button("Assemble at address…").clicked() {
+                *action = Some(RowAction::Assemble(self.addr));
+                ui.close_menu();
+            }
+
+            ui.separator();
+
+            let module_address = debugee.module_address(self.addr);
+
+            if ui
+                .add_enabled(
+                    module_address.is_some(),
+                    egui::Button::new("Label and comment…"),
+                )
+                .on_disabled_hover_text("Only addresses inside of modules")
+                .clicked()
+            {
+                *action = Some(RowAction::EditNotes(self.addr));
+                ui.close_menu();
+            }
+
+            let bookmark_text = if note.bookmark {
+                "Remove bookmark"
+            } else {
+                "Bookmark"
+            };
+            if ui
+                .add_enabled(module_address.is_some(), egui::Button::new(bookmark_text))
+                .clicked()
+            {
+                if let Some(module_address) = module_address {
+                    debugee
+                        .notes_mut()
+                        .set_bookmark(module_address, !note.bookmark);
+                }
+                ui.close_menu();
+            }
+        });
 
         ui.add_sized(egui::vec2(4.0, 16.0), egui::Separator::default()); //gotta do this otherwise it takes up the entirety of the available space
 
@@ -72,186 +168,752 @@ impl Instruction {
 
         ui.add_sized(egui::vec2(4.0, 16.0), egui::Separator::default());
 
-        ui.label(formatted);
+        let mut text = egui::text::LayoutJob::default();
+        let font = egui::TextStyle::Monospace.resolve(ui.style());
+        for (token, kind) in &self.tokens {
+            let color = kind
+                .color(ui.visuals().dark_mode)
+                .unwrap_or_else(|| ui.visuals().text_color());
+            text.append(token, 0.0, egui::TextFormat::simple(font.clone(), color));
+        }
+
+        let response = ui.selectable_label(selected, text).on_hover_ui(|ui| {
+            egui::Grid::new("instruction_details")
+                .num_columns(2)
+                .striped(true)
+                .show(ui, |ui| {
+                    for (what, description) in instruction_info::details(&self.inner) {
+                        ui.label(egui::RichText::new(what).strong());
+                        ui.label(egui::RichText::new(description).monospace());
+                        ui.end_row();
+                    }
+                });
+        });
+
+        if let Some(comment) = &note.comment {
+            let width = response.rect.width();
+            if width < TEXT_WIDTH {
+                ui.add_space(TEXT_WIDTH - width);
+            }
+
+            ui.label(
+                egui::RichText::new(format!("; {comment}"))
+                    .monospace()
+                    .italics()
+                    .color(ui.visuals().weak_text_color()),
+            );
+        }
+
+        response
+    }
+
+    /// Where a jmp/call/jcc goes, through the pointer for `jmp [rip+x]` style ones (PLT stubs)
+    fn branch_target(&self, debugee: &Debugee) -> Option<u64> {
+        use iced_x86::FlowControl;
+
+        match self.inner.flow_control() {
+            FlowControl::UnconditionalBranch
+            | FlowControl::ConditionalBranch
+            | FlowControl::Call => Some(self.inner.near_branch_target()).filter(|&t| t != 0),
+            FlowControl::IndirectBranch | FlowControl::IndirectCall
+                if self.inner.is_ip_rel_memory_operand() =>
+            {
+                Some(debugee.read_u64(self.inner.ip_rel_memory_address()))
+            }
+            _ => None,
+        }
     }
 }
 
 pub struct DisassemblyView {
+    /// Address of the topmost row
     rip: u64,
-    cache: Vec<Instruction>,
+    cache: InstructionCache,
+    /// Move the scrollbar to `rip` instead of the other way around
+    scroll_to_rip: bool,
+    /// Byte the scrollbar pointed at last frame, `rip` only follows it once it moves
+    scrolled_to: Option<u64>,
+    /// First byte of the part of the region the scrollbar spans
+    window_start: u64,
+    /// Show the source line above the instructions generated for it
+    pub interleave_source: bool,
+    source_files: SourceFiles,
+
+    selected: Option<u64>,
+    /// Where the view was before each jump, newest last
+    back: Vec<u64>,
+    /// Where going back came from, newest last
+    forward: Vec<u64>,
 
     render_goto_modal: bool,
     goto_input: String,
+    goto_error: Option<String>,
+
+    /// Address the assemble modal patches, open while set
+    assemble_address: Option<u64>,
+    assemble_input: String,
+    /// Bytes `assemble_input` assembles to, redone whenever it changes
+    assembled: Option<Result<Vec<u8>, String>>,
+
+    /// Address the label and comment modal edits, open while set
+    notes_address: Option<u64>,
+    label_input: String,
+    comment_input: String,
+    notes_error: Option<String>,
 }
 
 impl DisassemblyView {
-    pub const fn new() -> Self {
+    pub fn new() -> Self {
         Self {
             rip: 0,
-            cache: Vec::new(),
+            cache: InstructionCache::new(),
+            scroll_to_rip: true,
+            scrolled_to: None,
+            window_start: 0,
+            interleave_source: false,
+            source_files: SourceFiles::default(),
+
+            selected: None,
+            back: Vec::new(),
+            forward: Vec::new(),
 
             render_goto_modal: false,
             goto_input: String::new(),
+            goto_error: None,
+
+            assemble_address: None,
+            assemble_input: String::new(),
+            assembled: None,
+
+            notes_address: None,
+            label_input: String::new(),
+            comment_input: String::new(),
+            notes_error: None,
         }
     }
 
     pub fn set_rip(&mut self, rip: u64) {
         self.rip = rip;
+        self.cache.anchor(rip);
+        self.scroll_to_rip = true;
     }
 
-    #[allow(unused)]
-    pub fn clean_cache(&mut self) {
-        self.cache
-            .retain(|i| (i.addr as i128 - self.rip as i128).abs() < CACHE_RANGE as i128 * 2);
+    /// Scrolls the view to `address`, which is decoded as the start of an instruction from now on.
+    /// Going back returns to where the view was
+    pub fn go_to(&mut self, address: u64) {
+        if address != self.rip {
+            self.back.push(self.rip);
+            if self.back.len() > HISTORY_SIZE {
+                self.back.remove(0);
+            }
+            self.forward.clear();
+        }
+
+        self.selected = Some(address);
+        self.set_rip(address);
+    }
+
+    pub fn go_back(&mut self) {
+        if let Some(address) = self.back.pop() {
+            self.forward.push(self.rip);
+            self.set_rip(address);
+        }
+    }
+
+    pub fn go_forward(&mut self) {
+        if let Some(address) = self.forward.pop() {
+            self.back.push(self.rip);
+            self.set_rip(address);
+        }
+    }
+
+    pub const fn format(&self) -> FormatSettings {
+        self.cache.format()
+    }
+
+    pub fn set_format(&mut self, format: FormatSettings) {
+        self.cache.set_format(format);
     }
 
     pub fn purge_cache(&mut self) {
         self.cache.clear();
+        self.source_files.clear();
     }
 
+    /// Drops what the process could've changed since it last stopped
     pub fn refresh_cache(&mut self, debugee: &Debugee) {
-        let cache_start = self.rip;
-        //error handle?
-        let mut data = debugee.read_memory(cache_start as usize, CACHE_RANGE as usize);
-        let mut instructions = Vec::new();
-
-        for bp in debugee.breakpoints() {
-            if bp.enabled() && !bp.hardware() {
-                let addr = bp.address();
-                let size = bp.size() as u64;
-
-                //is within cache bounds
-                if addr >= cache_start && addr + size < cache_start + CACHE_RANGE {
-                    for (i, b) in bp.original_bytes().unwrap().iter().enumerate() {
-                        data[addr as usize - cache_start as usize + i] = *b;
-                    }
-                }
-            }
-        }
+        self.cache.invalidate(debugee, true);
+    }
 
-        let mut decoder = iced_x86::Decoder::new(64, &data, iced_x86::DecoderOptions::NONE);
+    /// Rows of the region `rip` is in, the scrollbar spans `WINDOW_BYTES` of it around `rip`
+    /// at a rough `BYTES_PER_ROW` per instruction and moves along once `rip` nears either end.
+    /// Only the rows in view are decoded and laid out
+    pub fn show(&mut self, ui: &mut egui::Ui, debugee: &mut Debugee) {
+        let rect = egui::Rect::from_min_size(ui.next_widget_position(), ui.available_size());
 
-        while decoder.can_decode() {
-            instructions.push(decoder.decode());
+        if !debugee.stopped {
+            ui.label("Process is running");
+            return;
         }
 
-        self.cache.extend_from_slice(
-            &instructions
-                .into_iter()
-                .map(|i| Instruction {
-                    addr: self.rip + i.ip(),
-                    bytes: data[i.ip() as usize..i.ip() as usize + i.len()].to_vec(),
-                    inner: i,
-                })
-                .collect::<Vec<Instruction>>(),
-        );
+        self.cache.invalidate(debugee, false);
 
-        self.cache.sort_by(|a, b| a.addr.cmp(&b.addr));
-        self.cache.dedup_by(|a, b| a.addr == b.addr);
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(!self.back.is_empty(), egui::Button::new("⏴"))
+                .on_hover_text("Back (Esc, Alt+Left)")
+                .clicked()
+            {
+                self.go_back();
+            }
 
-        //self.clean_cache();
-    }
+            if ui
+                .add_enabled(!self.forward.is_empty(), egui::Button::new("⏵"))
+                .on_hover_text("Forward (Alt+Right)")
+                .clicked()
+            {
+                self.go_forward();
+            }
 
-    pub fn show(&mut self, ui: &mut egui::Ui, debugee: &mut Debugee) {
-        let rect = egui::Rect::from_min_size(ui.next_widget_position(), ui.available_size());
+            if ui.button("Go to RIP").clicked() {
+                self.go_to(debugee.context().rip);
+            }
 
-        let instruction_index = if let Some(index) =
-            self.cache.iter().position(|x| x.addr == self.rip)
-            && !self.cache.is_empty()
-        {
-            index
-        } else {
-            //rip is invalid or smth :P
-            ui.label("RIP is invalid or the current memory region has not been yet cached");
-            if debugee.stopped {
-                self.refresh_cache(debugee);
+            if ui.button("Go to…").on_hover_text("Ctrl+G").clicked() {
+                self.render_goto_modal = true;
             }
-            return;
-        };
+        });
 
-        if ui.rect_contains_pointer(rect) {
-            let scroll_delta = ui.input(|input| input.raw_scroll_delta);
+        if self.render_goto_modal {
+            self.show_goto_modal(ui, debugee);
+        }
 
-            if scroll_delta.y < 0.0 {
-                //scroll down
-                let estimated_amount_of_instructions_per_page = ui.available_height() / 16.0;
-                if instruction_index + estimated_amount_of_instructions_per_page as usize
-                    > self.cache.len() && debugee.stopped
+        if let Some(address) = self.assemble_address {
+            self.show_assemble_modal(ui, debugee, address);
+        }
+
+        if let Some(address) = self.notes_address {
+            self.show_notes_modal(ui, debugee, address);
+        }
+
+        let hovered = ui.rect_contains_pointer(rect);
+        //keys typed into a text field aren't meant for the view
+        let typing = self.render_goto_modal
+            || self.assemble_address.is_some()
+            || self.notes_address.is_some()
+            || ui.memory(|memory| memory.focus().is_some());
+
+        if hovered && !typing {
+            ui.input_mut(|input| {
+                if input.consume_key(egui::Modifiers::CTRL, egui::Key::G) {
+                    self.render_goto_modal = true;
+                }
+
+                if input.consume_key(egui::Modifiers::NONE, egui::Key::Escape)
+                    || input.consume_key(egui::Modifiers::ALT, egui::Key::ArrowLeft)
                 {
-                    self.refresh_cache(debugee);
+                    self.go_back();
                 }
 
-                self.rip += self.cache[instruction_index].inner.len() as u64;
-            } else if scroll_delta.y > 0.0 {
-                if instruction_index != 0 {
-                    self.rip -= self.cache[instruction_index - 1].inner.len() as u64;
+                if input.consume_key(egui::Modifiers::ALT, egui::Key::ArrowRight) {
+                    self.go_forward();
                 }
+            });
+        }
+
+        let Some(region) = debugee.memory_map().region_at(self.rip) else {
+            ui.label(format!("{:#x} isn't mapped", self.rip));
+            return;
+        };
+        let (start, end) = (region.start, region.end);
+
+        if hovered {
+            //the wheel goes a row at a time like the arrow keys, the scrollbar only goes by pixels
+            let wheel = ui.input_mut(|input| {
+                input.smooth_scroll_delta = egui::Vec2::ZERO;
+                input.raw_scroll_delta.y
+            });
+
+            let up = !typing
+                && ui.input_mut(|x| x.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp));
+            if (up || wheel > 0.0)
+                && let Some(previous) = self.cache.previous(debugee, self.rip)
+            {
+                self.rip = previous;
+                self.scroll_to_rip = true;
             }
 
-            if ui
-                .ctx()
-                .input_mut(|x| x.consume_key(egui::Modifiers::CTRL, egui::Key::G))
+            let down = !typing
+                && ui.input_mut(|x| x.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown));
+            if (down || wheel < 0.0)
+                && let Some(first) = self.cache.instructions(debugee, self.rip, 1).first()
             {
-                self.render_goto_modal = true;
+                self.rip = first.end().min(end - 1);
+                self.scroll_to_rip = true;
             }
         }
 
-        //unwrapping is safe here since we alr know cache is not empty
-        let largest_instruction = self
-            .cache
-            .iter()
-            .skip(instruction_index)
-            .take((ui.available_height() / 16.0 * 1.4) as usize)
-            .max_by(|a, b| a.inner.len().cmp(&b.inner.len()))
-            .unwrap();
-
-        let mut i = 0;
-        while ui.available_height() > 16.0 {
-            ui.with_layout(
-                egui::Layout::left_to_right(egui::emath::Align::default()),
-                |ui| {
-                    self.cache[instruction_index + i].show(
-                        ui,
-                        debugee,
-                        largest_instruction.inner.len(),
-                    );
-                },
-            );
-            ui.separator();
+        let row_height = ROW_HEIGHT;
+        let pixels_per_byte = row_height / BYTES_PER_ROW;
+
+        let mut jumped = std::mem::take(&mut self.scroll_to_rip);
 
-            i += 1;
+        //recentered on rip whenever it gets close to an end that isn't the region's
+        let margin = WINDOW_BYTES / 8;
+        let window_end = self.window_start.saturating_add(WINDOW_BYTES).min(end);
+        if jumped
+            || self.rip < self.window_start
+            || self.rip >= window_end
+            || (self.window_start > start && self.rip - self.window_start < margin)
+            || (window_end < end && window_end - self.rip < margin)
+        {
+            self.window_start = self
+                .rip
+                .saturating_sub(WINDOW_BYTES / 2)
+                .min(end.saturating_sub(WINDOW_BYTES))
+                .max(start);
+            jumped = true;
         }
+        let window_start = self.window_start;
+        let window_end = window_start.saturating_add(WINDOW_BYTES).min(end);
 
-        if self.render_goto_modal {
-            let modal = egui_modal::Modal::new(ui.ctx(), "disassembly_view_goto_modal")
-                .with_close_on_outside_click(true);
-            modal.open();
+        let mut scroll_area = egui::ScrollArea::vertical().auto_shrink([false, false]);
+        if jumped {
+            scroll_area = scroll_area
+                .vertical_scroll_offset((self.rip - window_start) as f32 * pixels_per_byte);
+        }
 
-            modal.show(|ui| {
-                modal.title(ui, "Go to (disassembly)");
+        let mut follow = None;
+        let mut row_action = None;
 
-                modal.frame(ui, |ui| {
-                    ui.horizontal(|ui| {
-                        ui.label("Address (hex)");
-                        ui.text_edit_singleline(&mut self.goto_input);
-                    });
+        scroll_area.show_viewport(ui, |ui, viewport| {
+            ui.set_height((window_end - window_start) as f32 * pixels_per_byte);
+
+            let top = window_start + (viewport.min.y / pixels_per_byte) as u64;
+            if !jumped
+                && self
+                    .scrolled_to
+                    .is_some_and(|scrolled_to| scrolled_to != top)
+            {
+                self.rip = self.cache.instruction_start(debugee, top.min(end - 1));
+            }
+            self.scrolled_to = Some(top);
+
+            let rows = (viewport.height() / row_height) as usize + 1;
+            let page = self.cache.instructions(debugee, self.rip, rows);
+            let largest_instruction = page.iter().map(|i| i.bytes.len()).max().unwrap_or(1);
+
+            let follow_selected = hovered
+                && !typing
+                && ui.input_mut(|x| x.consume_key(egui::Modifiers::NONE, egui::Key::Enter));
+
+            let rip = debugee.context().rip;
+            let mut rows = Vec::with_capacity(page.len());
+
+            let rows_rect = viewport.translate(ui.max_rect().min.to_vec2());
+            ui.allocate_ui_at_rect(rows_rect, |ui| {
+                let mut previous_source = None;
+                for instruction in &page {
+                    if ui.available_height() < row_height {
+                        break;
+                    }
+
+                    if self.interleave_source
+                        && let Some((path, line)) = &instruction.source
+                        && previous_source != Some((path, *line))
+                    {
+                        previous_source = Some((path, *line));
+
+                        let file_name = path.rsplit('/').next().unwrap_or(path);
+                        let text = self.source_files.line(path, *line).unwrap_or_default();
+
+                        ui.label(
+                            egui::RichText::new(format!("{file_name}:{line}  {}", text.trim()))
+                                .monospace()
+                                .color(ui.visuals().warn_fg_color),
+                        );
+                        ui.separator();
+                    }
+
+                    let selected = self.selected == Some(instruction.addr);
+                    let (response, gutter) = ui
+                        .with_layout(
+                            egui::Layout::left_to_right(egui::emath::Align::default()),
+                            |ui| {
+                                let (gutter, _) = ui.allocate_exact_size(
+                                    egui::vec2(ARROW_GUTTER, 16.0),
+                                    egui::Sense::hover(),
+                                );
+                                let response = instruction.show(
+                                    ui,
+                                    debugee,
+                                    largest_instruction,
+                                    selected,
+                                    &mut row_action,
+                                );
+
+                                if instruction.addr == rip {
+                                    let notes = annotations(debugee, instruction);
+                                    if !notes.is_empty() {
+                                        ui.label(
+                                            egui::RichText::new(format!("; {notes}"))
+                                                .monospace()
+                                                .weak(),
+                                        );
+                                    }
+                                }
+
+                                (response, gutter)
+                            },
+                        )
+                        .inner;
+                    ui.separator();
+                    rows.push((instruction, gutter));
+
+                    if response.clicked() {
+                        self.selected = Some(instruction.addr);
+                    }
+
+                    if response.double_clicked() || (selected && follow_selected) {
+                        follow = instruction.branch_target(debugee);
+                    }
+                }
+
+                let taken = page
+                    .iter()
+                    .find(|i| i.addr == rip)
+                    .and_then(|i| instruction_info::branch_taken(&i.inner, debugee.context()));
+                paint_jump_arrows(ui, &rows, rip, taken);
+            });
+        });
+
+        if let Some(target) = follow {
+            self.go_to(target);
+        }
+
+        match row_action {
+            Some(RowAction::Assemble(address)) => {
+                self.assemble_address = Some(address);
+                self.assembled = None;
+            }
+            Some(RowAction::EditNotes(address)) => {
+                let note = debugee.note(address).cloned().unwrap_or_default();
+                self.notes_address = Some(address);
+                self.label_input = note.label.unwrap_or_default();
+                self.comment_input = note.comment.unwrap_or_default();
+                self.notes_error = None;
+            }
+            None => {}
+        }
+    }
+
+    fn show_goto_modal(&mut self, ui: &mut egui::Ui, debugee: &Debugee) {
+        let modal = egui_modal::Modal::new(ui.ctx(), "disassembly_view_goto_modal")
+            .with_close_on_outside_click(true);
+        modal.open();
+
+        modal.show(|ui| {
+            modal.title(ui, "Go to (disassembly)");
+
+            modal.frame(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Expression");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.goto_input)
+                            .hint_text("main, libc.so.6!puts+4, [rsp+0x20]"),
+                    );
                 });
 
-                modal.buttons(ui, |ui| {
-                    if modal.suggested_button(ui, "Go").clicked() || modal.was_outside_clicked() {
-                        modal.close();
-                        self.render_goto_modal = false;
+                if let Some(error) = &self.goto_error {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+            });
 
-                        if let Some(hex_string) = self.goto_input.split('x').last() {
-                            if let Ok(new_address) = u64::from_str_radix(&hex_string, 16) {
-                                self.rip = new_address;
-                            }
+            modal.buttons(ui, |ui| {
+                if modal.was_outside_clicked() || modal.button(ui, "Cancel").clicked() {
+                    self.render_goto_modal = false;
+                    self.goto_error = None;
+                    return;
+                }
+
+                let submitted = ui.input(|input| input.key_pressed(egui::Key::Enter));
+                if modal.suggested_button(ui, "Go").clicked() || submitted {
+                    match expression::evaluate(&self.goto_input, debugee) {
+                        Ok(address) => {
+                            modal.close();
+                            self.render_goto_modal = false;
+                            self.goto_error = None;
+                            self.goto_input.clear();
+                            self.go_to(address);
                         }
+                        Err(error) => self.goto_error = Some(error),
+                    }
+                }
+            });
+        });
+    }
 
-                        self.goto_input.clear();
+    fn show_assemble_modal(&mut self, ui: &mut egui::Ui, debugee: &mut Debugee, address: u64) {
+        let modal = egui_modal::Modal::new(ui.ctx(), "disassembly_view_assemble_modal")
+            .with_close_on_outside_click(true);
+        modal.open();
+
+        //instructions the patch spans, it gets padded up to the end of the last one
+        let following = self.cache.instructions(debugee, address, 16);
+
+        modal.show(|ui| {
+            modal.title(ui, format!("Assemble at {address:#x}"));
+
+            modal.frame(ui, |ui| {
+                ui.label("Intel syntax, one instruction per line or separated by ';'");
+                let response = ui.add(
+                    egui::TextEdit::multiline(&mut self.assemble_input)
+                        .code_editor()
+                        .desired_rows(4)
+                        .hint_text("mov eax, 1\njmp main+0x20"),
+                );
+
+                if response.changed() || self.assembled.is_none() {
+                    self.assembled = Some(if self.assemble_input.trim().is_empty() {
+                        Err(String::from("Nothing to assemble"))
+                    } else {
+                        assembler::assemble(&self.assemble_input, address, debugee)
+                    });
+                }
+
+                match &self.assembled {
+                    Some(Ok(bytes)) => {
+                        let (padding, overwritten) = padding(&following, address, bytes.len());
+
+                        ui.label(
+                            egui::RichText::new(
+                                bytes
+                                    .iter()
+                                    .map(|byte| format!("{byte:02x}"))
+                                    .collect::<Vec<_>>()
+                                    .join(" "),
+                            )
+                            .monospace(),
+                        );
+                        ui.label(format!("{} bytes, padded with {padding} NOPs", bytes.len()));
+
+                        if overwritten > 0 {
+                            ui.colored_label(
+                                ui.visuals().warn_fg_color,
+                                format!(
+                                    "Overruns the instruction at {address:#x}, {overwritten} more \
+                                     instruction(s) after it get overwritten"
+                                ),
+                            );
+                        }
                     }
-                });
+                    Some(Err(error)) => {
+                        ui.colored_label(ui.visuals().error_fg_color, error);
+                    }
+                    None => {}
+                }
             });
+
+            modal.buttons(ui, |ui| {
+                if modal.was_outside_clicked() || modal.button(ui, "Cancel").clicked() {
+                    self.assemble_address = None;
+                    return;
+                }
+
+                let assembled = match &self.assembled {
+                    Some(Ok(bytes)) => Some(bytes.clone()),
+                    _ => None,
+                };
+
+                if ui
+                    .add_enabled(assembled.is_some(), egui::Button::new("Assemble"))
+                    .clicked()
+                    && let Some(mut bytes) = assembled
+                {
+                    let (padding, _) = padding(&following, address, bytes.len());
+                    bytes.resize(bytes.len() + padding, 0x90);
+
+                    //the cache notices the write on its own
+                    debugee.write_memory(address as usize, &bytes);
+
+                    modal.close();
+                    self.assemble_address = None;
+                    self.assembled = None;
+                    self.assemble_input.clear();
+                }
+            });
+        });
+    }
+
+    fn show_notes_modal(&mut self, ui: &mut egui::Ui, debugee: &mut Debugee, address: u64) {
+        let modal = egui_modal::Modal::new(ui.ctx(), "disassembly_view_notes_modal")
+            .with_close_on_outside_click(true);
+        modal.open();
+
+        let Some(module_address) = debugee.module_address(address) else {
+            self.notes_address = None;
+            return;
+        };
+
+        modal.show(|ui| {
+            modal.title(ui, format!("Label and comment at {module_address}"));
+
+            modal.frame(ui, |ui| {
+                egui::Grid::new("disassembly_view_notes_grid")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label("Label");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.label_input)
+                                .hint_text("parse_header"),
+                        );
+                        ui.end_row();
+
+                        ui.label("Comment");
+                        ui.add(egui::TextEdit::singleline(&mut self.comment_input));
+                        ui.end_row();
+                    });
+
+                ui.weak("Leave empty to remove");
+
+                if let Some(error) = &self.notes_error {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+            });
+
+            modal.buttons(ui, |ui| {
+                if modal.was_outside_clicked() || modal.button(ui, "Cancel").clicked() {
+                    self.notes_address = None;
+                    return;
+                }
+
+                let submitted = ui.input(|input| input.key_pressed(egui::Key::Enter));
+                if modal.suggested_button(ui, "Save").clicked() || submitted {
+                    let notes = debugee.notes_mut();
+
+                    match notes.set_label(module_address.clone(), &self.label_input) {
+                        Ok(()) => {
+                            notes.set_comment(module_address, &self.comment_input);
+                            modal.close();
+                            self.notes_address = None;
+                        }
+                        Err(error) => self.notes_error = Some(error),
+                    }
+                }
+            });
+        });
+    }
+}
+
+/// NOPs needed after a patch of `length` bytes at `address` so it ends on an instruction boundary,
+/// and how many instructions besides the one at `address` it (partly) overwrites
+fn padding(following: &[Instruction], address: u64, length: usize) -> (usize, usize) {
+    let end = address + length as u64;
+
+    match following
+        .iter()
+        .position(|instruction| instruction.end() >= end)
+    {
+        Some(last) => ((following[last].end() - end) as usize, last),
+        //longer than everything decoded, nothing sensible to pad up to
+        None => (0, following.len().saturating_sub(1)),
+    }
+}
+
+/// Whether the branch at rip is taken and the values its operands read, `jump is taken` or
+/// `[rbp-0x14]=0x2a, eax=0x1`
+fn annotations(debugee: &Debugee, instruction: &Instruction) -> String {
+    let mut notes = Vec::new();
+
+    match instruction_info::branch_taken(&instruction.inner, debugee.context()) {
+        Some(true) => notes.push(String::from("jump is taken")),
+        Some(false) => notes.push(String::from("jump is not taken")),
+        None => {}
+    }
+
+    notes.extend(instruction_info::operand_values(
+        debugee,
+        &instruction.inner,
+    ));
+    notes.join(", ")
+}
+
+/// Draws a line in the gutter from each jmp/jcc to its target. Targets off screen get a line running
+/// off the top or bottom, the one at rip is highlighted unless it's known not to be taken
+fn paint_jump_arrows(
+    ui: &egui::Ui,
+    rows: &[(&Instruction, egui::Rect)],
+    rip: u64,
+    taken: Option<bool>,
+) {
+    use iced_x86::FlowControl;
+
+    let (Some((first, top)), Some((last, bottom))) = (rows.first(), rows.last()) else {
+        return;
+    };
+
+    //(source, source y, target y, target visible)
+    let mut jumps = Vec::new();
+    for (instruction, gutter) in rows {
+        let flow = instruction.inner.flow_control();
+        let target = instruction.inner.near_branch_target();
+        if target == 0
+            || !matches!(
+                flow,
+                FlowControl::UnconditionalBranch | FlowControl::ConditionalBranch
+            )
+        {
+            continue;
+        }
+
+        let target_y = if let Some((_, rect)) = rows.iter().find(|(i, _)| i.addr == target) {
+            Some(rect.center().y)
+        } else if target < first.addr {
+            Some(top.top())
+        } else if target >= last.end() {
+            Some(bottom.bottom())
+        } else {
+            //lands in the middle of a row, something's misaligned
+            None
+        };
+
+        if let Some(target_y) = target_y {
+            let visible = rows.iter().any(|(i, _)| i.addr == target);
+            jumps.push((instruction.addr, gutter.center().y, target_y, visible));
+        }
+    }
+
+    //short jumps go on the inside, each in the first lane nothing it overlaps took yet
+    jumps.sort_by(|a, b| (a.1 - a.2).abs().total_cmp(&(b.1 - b.2).abs()));
+    let max_lanes = (ARROW_GUTTER / ARROW_LANE) as usize - 1;
+    let mut lanes: Vec<Vec<(f32, f32)>> = vec![Vec::new(); max_lanes];
+
+    let painter = ui.painter();
+    let right = top.right() - 2.0;
+
+    for (source, source_y, target_y, visible) in jumps {
+        let span = (source_y.min(target_y), source_y.max(target_y));
+        let lane = lanes
+            .iter()
+            .position(|spans| spans.iter().all(|&(a, b)| b < span.0 || span.1 < a))
+            .unwrap_or(max_lanes - 1);
+        lanes[lane].push(span);
+
+        let x = right - ARROW_LANE * (lane + 1) as f32;
+        let color = if source == rip && taken != Some(false) {
+            ui.visuals().warn_fg_color
+        } else {
+            ui.visuals().weak_text_color()
+        };
+        let stroke = egui::Stroke::new(1.0, color);
+
+        painter.line_segment(
+            [egui::pos2(right, source_y), egui::pos2(x, source_y)],
+            stroke,
+        );
+        painter.line_segment([egui::pos2(x, source_y), egui::pos2(x, target_y)], stroke);
+        if visible {
+            painter.arrow(egui::pos2(x, target_y), egui::vec2(right - x, 0.0), stroke);
         }
     }
 }
@@ -0,0 +1,292 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use eframe::egui;
+
+use super::Follow;
+use crate::debugger::symbols::DemangleSettings;
+use crate::debugger::Debugee;
+
+const ROW_HEIGHT: f32 = 18.0;
+
+struct ExportRow {
+    module: String,
+    name: String,
+    address: u64,
+    is_function: bool,
+}
+
+struct ImportRow {
+    module: String,
+    name: String,
+    got_address: u64,
+    plt_address: Option<u64>,
+    jump_slot: bool,
+    /// Where the importing module is mapped, a jump slot pointing in there isn't bound yet
+    module_range: Range<u64>,
+}
+
+/// Symbol table generation, search and name style rows were built from
+type RowsKey = (u64, String, DemangleSettings);
+
+/// rsp, rip, symbol and notes generation and name style the GOT slots were read and described for
+type SlotsKey = (u64, u64, u64, u64, DemangleSettings);
+
+/// Exports and Imports tabs, built from the dynamic symbol tables and relocations of every module
+pub struct DynamicSymbolsView {
+    search: String,
+    exports: Vec<ExportRow>,
+    exports_built_for: Option<RowsKey>,
+    imports: Vec<ImportRow>,
+    imports_built_for: Option<RowsKey>,
+    /// Where GOT slots point and its description, filled in as rows become visible
+    slot_targets: HashMap<u64, (u64, String)>,
+    slots_read_for: Option<SlotsKey>,
+}
+
+impl DynamicSymbolsView {
+    pub fn new() -> Self {
+        Self {
+            search: String::new(),
+            exports: Vec::new(),
+            exports_built_for: None,
+            imports: Vec::new(),
+            imports_built_for: None,
+            slot_targets: HashMap::new(),
+            slots_read_for: None,
+        }
+    }
+
+    fn key(&self, debugee: &Debugee) -> RowsKey {
+        let symbols = debugee.symbols();
+        (symbols.generation(), self.search.clone(), symbols.demangle)
+    }
+
+    fn search_bar(&mut self, ui: &mut egui::Ui, count: usize) {
+        ui.horizontal(|ui| {
+            ui.label("Search");
+            ui.text_edit_singleline(&mut self.search);
+            ui.weak(format!("{count} symbol(s)"));
+        });
+
+        ui.separator();
+    }
+
+    fn rebuild_exports(&mut self, debugee: &Debugee) {
+        let search = self.search.to_lowercase();
+        let settings = debugee.symbols().demangle;
+
+        self.exports = debugee
+            .symbols()
+            .modules()
+            .iter()
+            .flat_map(|m| {
+                m.exports
+                    .iter()
+                    .map(move |s| (m, s, s.display_name(settings)))
+            })
+            .filter(|(_, _, name)| name.to_lowercase().contains(&search))
+            .map(|(m, s, name)| ExportRow {
+                module: m.name().to_owned(),
+                name: name.into_owned(),
+                address: s.address,
+                is_function: s.is_function,
+            })
+            .collect();
+
+        self.exports_built_for = Some(self.key(debugee));
+    }
+
+    fn rebuild_imports(&mut self, debugee: &Debugee) {
+        let search = self.search.to_lowercase();
+        let settings = debugee.symbols().demangle;
+
+        self.imports = debugee
+            .symbols()
+            .modules()
+            .iter()
+            .flat_map(|m| {
+                m.imports
+                    .iter()
+                    .map(move |i| (m, i, i.display_name(settings)))
+            })
+            .filter(|(_, _, name)| name.to_lowercase().contains(&search))
+            .map(|(m, i, name)| ImportRow {
+                module: m.name().to_owned(),
+                name: name.into_owned(),
+                got_address: i.got_address,
+                plt_address: i.plt_address,
+                jump_slot: i.jump_slot,
+                module_range: m.base..m.base + m.size,
+            })
+            .collect();
+
+        self.imports_built_for = Some(self.key(debugee));
+    }
+
+    pub fn show_exports(&mut self, ui: &mut egui::Ui, debugee: &Option<Debugee>) -> Option<Follow> {
+        let Some(debugee) = debugee else {
+            ui.label("No process");
+            return None;
+        };
+
+        self.search_bar(ui, self.exports.len());
+
+        if self.exports_built_for != Some(self.key(debugee)) {
+            self.rebuild_exports(debugee);
+        }
+
+        let mut follow = None;
+
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, false])
+            .show_rows(ui, ROW_HEIGHT, self.exports.len(), |ui, range| {
+                egui::Grid::new("exports_grid")
+                    .num_columns(4)
+                    .striped(true)
+                    .min_row_height(ROW_HEIGHT)
+                    .show(ui, |ui| {
+                        for row in &self.exports[range] {
+                            let response = ui.add(
+                                egui::Label::new(
+                                    egui::RichText::new(format!("{:#014x}", row.address))
+                                        .monospace(),
+                                )
+                                .sense(egui::Sense::click()),
+                            );
+
+                            if response.clicked() {
+                                follow = Some(if row.is_function {
+                                    Follow::Disassembly(row.address)
+                                } else {
+                                    Follow::Dump(row.address)
+                                });
+                            }
+
+                            ui.label(&row.module);
+                            ui.label(if row.is_function { "Function" } else { "Data" });
+                            ui.label(&row.name);
+                            ui.end_row();
+                        }
+                    });
+            });
+
+        follow
+    }
+
+    /// Every GOT slot (and its PLT stub) along with where it currently points.
+    /// A jump slot still pointing into its own module hasn't been bound by ld.so yet
+    pub fn show_imports(&mut self, ui: &mut egui::Ui, debugee: &Option<Debugee>) -> Option<Follow> {
+        let Some(debugee) = debugee else {
+            ui.label("No process");
+            return None;
+        };
+
+        self.search_bar(ui, self.imports.len());
+
+        if self.imports_built_for != Some(self.key(debugee)) {
+            self.rebuild_imports(debugee);
+        }
+
+        //slots get rebound while the process runs
+        let slots_key = (
+            debugee.context().rsp,
+            debugee.context().rip,
+            debugee.symbols().generation(),
+            debugee.notes().generation(),
+            debugee.symbols().demangle,
+        );
+        if self.slots_read_for != Some(slots_key) {
+            self.slots_read_for = Some(slots_key);
+            self.slot_targets.clear();
+        }
+
+        let mut follow = None;
+
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, false])
+            .show_rows(ui, ROW_HEIGHT, self.imports.len(), |ui, range| {
+                egui::Grid::new("imports_grid")
+                    .num_columns(6)
+                    .striped(true)
+                    .min_row_height(ROW_HEIGHT)
+                    .show(ui, |ui| {
+                        for row in &self.imports[range] {
+                            if ui
+                                .add(
+                                    egui::Label::new(
+                                        egui::RichText::new(format!("{:#014x}", row.got_address))
+                                            .monospace(),
+                                    )
+                                    .sense(egui::Sense::click()),
+                                )
+                                .clicked()
+                            {
+                                follow = Some(Follow::Dump(row.got_address));
+                            }
+
+                            match row.plt_address {
+                                Some(plt_address) => {
+                                    if ui
+                                        .add(
+                                            egui::Label::new(
+                                                egui::RichText::new(format!("{plt_address:#x}"))
+                                                    .monospace(),
+                                            )
+                                            .sense(egui::Sense::click()),
+                                        )
+                                        .clicked()
+                                    {
+                                        follow = Some(Follow::Disassembly(plt_address));
+                                    }
+                                }
+                                None => {
+                                    ui.label("");
+                                }
+                            }
+
+                            ui.label(&row.module);
+                            ui.label(&row.name);
+
+                            //slots can only be read while the process is stopped
+                            if debugee.stopped {
+                                let (target, description) =
+                                    self.slot_targets.entry(row.got_address).or_insert_with(|| {
+                                        let target = debugee.read_u64(row.got_address);
+                                        let description = debugee
+                                            .describe_address(target)
+                                            .unwrap_or_else(|| format!("{target:#x}"));
+                                        (target, description)
+                                    });
+                                let target = *target;
+
+                                if ui
+                                    .add(
+                                        egui::Label::new(description.as_str())
+                                            .sense(egui::Sense::click()),
+                                    )
+                                    .clicked()
+                                {
+                                    follow = Some(Follow::Disassembly(target));
+                                }
+
+                                ui.label(if target == 0 {
+                                    "Unbound"
+                                } else if row.jump_slot && row.module_range.contains(&target) {
+                                    "Lazy"
+                                } else {
+                                    "Bound"
+                                });
+                            } else {
+                                ui.label("?");
+                                ui.label("");
+                            }
+
+                            ui.end_row();
+                        }
+                    });
+            });
+
+        follow
+    }
+}
@@ -45,6 +45,13 @@ impl HexView {
         self.cache.clear();
     }
 
+    /// Scrolls the dump to `address` and puts the cursor on it
+    pub fn go_to(&mut self, address: u64, debugee: &mut Debugee) {
+        self.address = address;
+        self.cursor_address = address;
+        self.update_cache(debugee);
+    }
+
     pub fn update_cache(&mut self, debugee: &mut Debugee) {
         self.since_last_update = std::time::SystemTime::now();
         let cache_start = (self.address as usize).saturating_sub(CACHE_RANGE);
@@ -124,9 +131,7 @@ impl HexView {
                                 if modified {
                                     if let Ok(new_value) = u8::from_str_radix(&byte_text, 16) {
                                         if let Some(debugee) = debugee {
-                                            //why tf do i have to add 1 here? TODO figure this out
-                                            debugee
-                                                .write_memory(address as usize + 1, &[new_value]);
+                                            debugee.write_memory(address as usize, &[new_value]);
                                         }
                                     }
                                 }
@@ -0,0 +1,503 @@
+use std::collections::{BTreeSet, HashMap};
+
+use crate::debugger::memory_map::MemoryRegion;
+use crate::debugger::Debugee;
+
+use super::disassembly_format::{FormatSettings, InstructionFormatter, TokenKind};
+
+//bytes decoded from an anchor at once
+const BLOCK_SIZE: u64 = 0x200;
+//longest x86 instruction
+const MAX_INSTRUCTION: u64 = 15;
+//bytes before an address tried as decode starts when nothing says where its instruction starts
+const CONVERGE_WINDOW: u64 = MAX_INSTRUCTION * 4;
+
+/// Names for the branch targets and rip relative operands of the instructions being formatted,
+/// looked up beforehand since the formatter can't borrow the debugee
+struct SymbolLookup {
+    names: HashMap<u64, String>,
+}
+
+impl iced_x86::SymbolResolver for SymbolLookup {
+    fn symbol(
+        &mut self,
+        _instruction: &iced_x86::Instruction,
+        _operand: u32,
+        _instruction_operand: Option<u32>,
+        address: u64,
+        _address_size: u32,
+    ) -> Option<iced_x86::SymbolResult<'_>> {
+        self.names
+            .get(&address)
+            .map(|name| iced_x86::SymbolResult::with_str(address, name))
+    }
+}
+
+#[derive(Clone)]
+pub struct Instruction {
+    pub addr: u64,
+    pub bytes: Vec<u8>,
+    pub inner: iced_x86::Instruction,
+    /// Formatted text, piece by piece
+    pub tokens: Vec<(String, TokenKind)>,
+    /// `module!symbol+off` of the address
+    pub location: Option<String>,
+    /// Source file and line the instruction was generated for
+    pub source: Option<(String, u32)>,
+}
+
+impl Instruction {
+    pub fn end(&self) -> u64 {
+        self.addr + self.bytes.len() as u64
+    }
+
+    pub fn text(&self) -> String {
+        self.tokens.iter().map(|(text, _)| text.as_str()).collect()
+    }
+}
+
+/// Instructions decoded in one go from an anchor, back to back
+struct Block {
+    instructions: Vec<Instruction>,
+}
+
+impl Block {
+    fn start(&self) -> u64 {
+        self.instructions[0].addr
+    }
+
+    fn end(&self) -> u64 {
+        self.instructions[self.instructions.len() - 1].end()
+    }
+
+    fn overlaps(&self, start: u64, end: u64) -> bool {
+        self.start() < end && start < self.end()
+    }
+}
+
+struct RegionCache {
+    region: MemoryRegion,
+    /// Sorted by address, never overlapping
+    blocks: Vec<Block>,
+}
+
+/// Decoded instructions per memory region. Decoding starts at anchors (rip, symbols, branch
+/// targets and wherever the user went to) and stops before running misaligned into another
+/// anchor or block, bytes left in between show up as `db`
+pub struct InstructionCache {
+    regions: Vec<RegionCache>,
+    /// Addresses known to start an instruction, besides symbols
+    anchors: BTreeSet<u64>,
+    /// How many of the debugee's memory writes were already taken into account
+    seen_writes: usize,
+    format: FormatSettings,
+    /// Generation of the user's notes the labels in the text come from
+    notes_generation: u64,
+}
+
+impl InstructionCache {
+    pub fn new() -> Self {
+        Self {
+            regions: Vec::new(),
+            anchors: BTreeSet::new(),
+            seen_writes: 0,
+            format: FormatSettings::default(),
+            notes_generation: 0,
+        }
+    }
+
+    pub const fn format(&self) -> FormatSettings {
+        self.format
+    }
+
+    /// Everything cached was formatted the old way, it's all decoded again
+    pub fn set_format(&mut self, format: FormatSettings) {
+        if format != self.format {
+            self.format = format;
+            self.regions.clear();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.regions.clear();
+        self.anchors.clear();
+        self.seen_writes = 0;
+    }
+
+    /// Drops whatever memory writes and remapped regions made stale.
+    /// Writable code (JIT, self modifying) could've changed under us on every stop, so it goes too
+    pub fn invalidate(&mut self, debugee: &Debugee, stopped_again: bool) {
+        let memory_map = debugee.memory_map();
+
+        self.regions.retain(|cache| {
+            let writable = cache.region.permissions.as_bytes().get(1) == Some(&b'w');
+
+            !(stopped_again && writable)
+                && memory_map
+                    .region_at(cache.region.start)
+                    .is_some_and(|region| same_mapping(region, &cache.region))
+        });
+
+        let writes = debugee.memory_writes();
+        //a new process started over, nothing is known about it
+        if writes.len() < self.seen_writes {
+            self.clear();
+        }
+
+        for write in &writes[self.seen_writes..] {
+            //an instruction starting before the write can reach into it
+            let start = write.start.saturating_sub(MAX_INSTRUCTION);
+
+            for cache in &mut self.regions {
+                cache
+                    .blocks
+                    .retain(|block| !block.overlaps(start, write.end));
+            }
+        }
+
+        self.seen_writes = writes.len();
+
+        //labels got renamed, they're baked into the text
+        let notes_generation = debugee.notes().generation();
+        if notes_generation != self.notes_generation {
+            self.notes_generation = notes_generation;
+            self.regions.clear();
+        }
+    }
+
+    /// Makes `address` start an instruction from now on, blocks that had it in the middle of one are
+    /// cut short before it
+    pub fn anchor(&mut self, address: u64) {
+        self.anchors.insert(address);
+
+        for cache in &mut self.regions {
+            let Some(index) = cache
+                .blocks
+                .iter()
+                .position(|block| block.start() < address && address < block.end())
+            else {
+                continue;
+            };
+
+            let block = &mut cache.blocks[index];
+            if block.instructions.iter().any(|i| i.addr == address) {
+                continue;
+            }
+
+            block.instructions.retain(|i| i.end() <= address);
+            if block.instructions.is_empty() {
+                cache.blocks.remove(index);
+            }
+        }
+    }
+
+    /// Up to `count` instructions starting at `address`, decoding whatever isn't cached yet.
+    /// Fewer come back when the region ends first, none when `address` isn't mapped
+    pub fn instructions(
+        &mut self,
+        debugee: &Debugee,
+        address: u64,
+        count: usize,
+    ) -> Vec<Instruction> {
+        let mut instructions = Vec::with_capacity(count);
+        let mut next = address;
+
+        while instructions.len() < count {
+            let Some(region) = self.region(debugee, next) else {
+                break;
+            };
+
+            let block = match self.regions[region]
+                .blocks
+                .iter()
+                .position(|block| block.start() <= next && next < block.end())
+            {
+                Some(block) => block,
+                None => self.decode(debugee, region, next),
+            };
+
+            let block = &self.regions[region].blocks[block];
+            let Some(first) = block.instructions.iter().position(|i| i.addr == next) else {
+                //`next` is in the middle of an instruction of a block decoded from an earlier anchor
+                self.anchor(next);
+                continue;
+            };
+
+            instructions.extend(
+                block.instructions[first..]
+                    .iter()
+                    .take(count - instructions.len())
+                    .cloned(),
+            );
+            next = block.end();
+        }
+
+        instructions
+    }
+
+    /// Start of the instruction ending right at `address`
+    pub fn previous(&mut self, debugee: &Debugee, address: u64) -> Option<u64> {
+        if let Some(previous) = self.cached_previous(address) {
+            return Some(previous);
+        }
+
+        let before = address.checked_sub(1)?;
+        self.boundary(debugee, before, Some(address))
+    }
+
+    /// Start of the instruction `address` is in, for landing somewhere arbitrary like the middle of
+    /// a scrollbar. `address` itself when nothing better turns up
+    pub fn instruction_start(&mut self, debugee: &Debugee, address: u64) -> u64 {
+        self.boundary(debugee, address, None).unwrap_or(address)
+    }
+
+    /// Start of the instruction covering `address` (and ending at `end` if given). Cached blocks are
+    /// trusted first, then the function around it decoded from its start, failing that a decode
+    /// from each of the bytes before it, most of which fall into step after a couple instructions
+    fn boundary(&mut self, debugee: &Debugee, address: u64, end: Option<u64>) -> Option<u64> {
+        let covering = |cache: &Self| {
+            cache
+                .regions
+                .iter()
+                .flat_map(|cache| &cache.blocks)
+                .flat_map(|block| &block.instructions)
+                .find(|i| i.addr <= address && address < i.end())
+                .filter(|i| end.is_none_or(|end| i.end() == end))
+                .map(|i| i.addr)
+        };
+
+        if let Some(start) = covering(self) {
+            return Some(start);
+        }
+
+        let symbol_start = debugee
+            .symbols()
+            .module_at(address)
+            .and_then(|module| module.symbol_at(address))
+            .filter(|(_, offset)| *offset < BLOCK_SIZE * 8)
+            .map(|(symbol, offset)| (symbol.address, offset));
+
+        if let Some((start, offset)) = symbol_start {
+            //every instruction is at least a byte long, so this many reach `address`
+            self.instructions(debugee, start, offset as usize + 1);
+            if let Some(start) = covering(self) {
+                return Some(start);
+            }
+        }
+
+        converge(debugee, address, end)
+    }
+
+    fn cached_previous(&self, address: u64) -> Option<u64> {
+        self.regions
+            .iter()
+            .flat_map(|cache| &cache.blocks)
+            .flat_map(|block| &block.instructions)
+            .find(|i| i.end() == address)
+            .map(|i| i.addr)
+    }
+
+    /// Index of the cached region `address` is in, caching it first if need be
+    fn region(&mut self, debugee: &Debugee, address: u64) -> Option<usize> {
+        if let Some(index) = self
+            .regions
+            .iter()
+            .position(|cache| cache.region.contains(address))
+        {
+            return Some(index);
+        }
+
+        let region = debugee.memory_map().region_at(address)?;
+        self.regions.push(RegionCache {
+            region: region.clone(),
+            blocks: Vec::new(),
+        });
+
+        Some(self.regions.len() - 1)
+    }
+
+    /// Decodes a new block at `start`, which isn't in any block yet, returning its index
+    fn decode(&mut self, debugee: &Debugee, region: usize, start: u64) -> usize {
+        let cache = &self.regions[region];
+
+        let next_block = cache
+            .blocks
+            .iter()
+            .map(Block::start)
+            .filter(|&block| block > start)
+            .min();
+        let end = (start + BLOCK_SIZE)
+            .min(cache.region.end)
+            .min(next_block.unwrap_or(u64::MAX));
+
+        //the last instruction is allowed to reach past the end, unless the region ends there
+        let read_end = (end + MAX_INSTRUCTION).min(cache.region.end);
+
+        //instructions can't run over these, they'd be decoded misaligned
+        let mut boundaries = self
+            .anchors
+            .range(start + 1..read_end)
+            .copied()
+            .chain(next_block)
+            .collect::<BTreeSet<_>>();
+        if let Some(module) = debugee.symbols().module_at(start) {
+            boundaries.extend(
+                module
+                    .symbols
+                    .iter()
+                    .filter(|symbol| symbol.address > start && symbol.address < read_end)
+                    .map(|symbol| symbol.address),
+            );
+        }
+        let data = debugee.read_code(start as usize, (read_end - start) as usize);
+
+        let mut decoder =
+            iced_x86::Decoder::with_ip(64, &data, start, iced_x86::DecoderOptions::NONE);
+        let mut decoded = Vec::new();
+        let mut address = start;
+
+        while address < end {
+            let offset = (address - start) as usize;
+            decoder.set_ip(address);
+            //offset is always within data, it's below end
+            let _ = decoder.set_position(offset);
+
+            let boundary = boundaries.range(address + 1..).next().copied();
+            let instruction = decoder.decode();
+
+            let instruction = if instruction.is_invalid()
+                || boundary.is_some_and(|boundary| address + instruction.len() as u64 > boundary)
+            {
+                let mut byte = iced_x86::Instruction::with_declare_byte_1(data[offset]);
+                byte.set_len(1);
+                byte.set_ip(address);
+                byte
+            } else {
+                instruction
+            };
+
+            address += instruction.len() as u64;
+            decoded.push(instruction);
+        }
+
+        let block = Block {
+            instructions: format(debugee, self.format, &data, start, decoded),
+        };
+
+        //branch targets are the next best thing to symbols for telling where code starts
+        for instruction in &block.instructions {
+            let target = instruction.inner.near_branch_target();
+            if target != 0 && cache.region.contains(target) {
+                self.anchors.insert(target);
+            }
+        }
+
+        let blocks = &mut self.regions[region].blocks;
+        let index = blocks.partition_point(|b| b.start() < start);
+        blocks.insert(index, block);
+        index
+    }
+}
+
+/// Formats the instructions, naming the addresses they refer to
+fn format(
+    debugee: &Debugee,
+    settings: FormatSettings,
+    data: &[u8],
+    start: u64,
+    instructions: Vec<iced_x86::Instruction>,
+) -> Vec<Instruction> {
+    let mut names = HashMap::new();
+    for i in &instructions {
+        let targets = [
+            (i.near_branch_target() != 0).then(|| i.near_branch_target()),
+            i.is_ip_rel_memory_operand()
+                .then(|| i.ip_rel_memory_address()),
+        ];
+
+        for target in targets.into_iter().flatten() {
+            if let Some(name) = debugee.describe_address(target) {
+                names.insert(target, name);
+            }
+        }
+    }
+
+    let mut formatter = InstructionFormatter::new(settings, Some(Box::new(SymbolLookup { names })));
+
+    instructions
+        .into_iter()
+        .map(|i| {
+            let offset = (i.ip() - start) as usize;
+
+            Instruction {
+                addr: i.ip(),
+                bytes: data[offset..offset + i.len()].to_vec(),
+                tokens: formatter.format(&i),
+                inner: i,
+                location: debugee.describe_address(i.ip()),
+                source: debugee
+                    .symbols()
+                    .source_location(i.ip())
+                    .map(|location| (location.path.to_owned(), location.line)),
+            }
+        })
+        .collect()
+}
+
+/// Decodes from every byte up to `CONVERGE_WINDOW` before `address` and picks the instruction
+/// start most of them agree covers it. Candidates not ending at `end` don't get a vote
+fn converge(debugee: &Debugee, address: u64, end: Option<u64>) -> Option<u64> {
+    let region = debugee.memory_map().region_at(address)?;
+    let first = address.saturating_sub(CONVERGE_WINDOW).max(region.start);
+    let last = (address + MAX_INSTRUCTION).min(region.end);
+    let data = debugee.read_code(first as usize, (last - first) as usize);
+
+    let mut votes = HashMap::<u64, usize>::new();
+    for candidate in first..=address {
+        let mut decoder = iced_x86::Decoder::with_ip(
+            64,
+            &data[(candidate - first) as usize..],
+            candidate,
+            iced_x86::DecoderOptions::NONE,
+        );
+
+        while decoder.can_decode() {
+            let instruction = decoder.decode();
+            //junk only disagrees with everyone else, counting it as a byte keeps the walk going
+            let len = if instruction.is_invalid() {
+                1
+            } else {
+                instruction.len() as u64
+            };
+            let ip = instruction.ip();
+
+            if ip + len > address {
+                if end.is_none_or(|end| ip + len == end) {
+                    *votes.entry(ip).or_default() += 1;
+                }
+                break;
+            }
+
+            if instruction.is_invalid() {
+                decoder.set_ip(ip + 1);
+                //stays within data, ip + 1 is at most `address`
+                let _ = decoder.set_position((ip + 1 - candidate) as usize);
+            }
+        }
+    }
+
+    //ties go to the earlier start, it agreed with more of what came before it
+    votes
+        .into_iter()
+        .max_by_key(|&(start, votes)| (votes, std::cmp::Reverse(start)))
+        .map(|(start, _)| start)
+}
+
+/// Same mapping as far as its contents go, usage stats aside
+fn same_mapping(a: &MemoryRegion, b: &MemoryRegion) -> bool {
+    a.start == b.start
+        && a.end == b.end
+        && a.permissions == b.permissions
+        && a.offset == b.offset
+        && a.path == b.path
+}
@@ -0,0 +1,172 @@
+use eframe::egui;
+
+use super::Follow;
+use crate::debugger::pretty::{Formatter, PrettyPrinters};
+use crate::debugger::unwind::FrameRegisters;
+use crate::debugger::variables::{Place, TypeKind, TypeRef, Variable};
+use crate::debugger::Debugee;
+
+pub struct LocalsView {
+    variables: Vec<Variable>,
+    /// Symbol table generation and registers the variables were located with
+    built_for: Option<(u64, FrameRegisters)>,
+}
+
+impl LocalsView {
+    pub const fn new() -> Self {
+        Self {
+            variables: Vec::new(),
+            built_for: None,
+        }
+    }
+
+    /// Values are read on every frame, only where they live is cached
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        debugee: &Option<Debugee>,
+        registers: Option<FrameRegisters>,
+        printers: &PrettyPrinters,
+    ) -> Option<Follow> {
+        let (Some(debugee), Some(registers)) = (debugee, registers) else {
+            ui.label("No process");
+            return None;
+        };
+
+        if !debugee.stopped {
+            ui.label("Process is running");
+            return None;
+        }
+
+        let key = (debugee.symbols().generation(), registers);
+
+        if self.built_for != Some(key) {
+            self.variables = debugee.locals(&registers);
+            self.built_for = Some(key);
+        }
+
+        let Some(dwarf) = debugee
+            .symbols()
+            .module_at(registers.location())
+            .and_then(|module| module.dwarf.as_ref())
+        else {
+            ui.label("No debug info for this frame");
+            return None;
+        };
+
+        if self.variables.is_empty() {
+            ui.label("No locals in scope");
+            return None;
+        }
+
+        let formatter = Formatter {
+            dwarf,
+            memory: debugee,
+            printers,
+        };
+        let mut follow = None;
+
+        egui::ScrollArea::both()
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                for (index, variable) in self.variables.iter().enumerate() {
+                    let name = if variable.is_parameter {
+                        format!("{} (param)", variable.name)
+                    } else {
+                        variable.name.clone()
+                    };
+
+                    let id = ui.make_persistent_id(("local", index, &variable.name));
+                    show_value(
+                        ui,
+                        &formatter,
+                        id,
+                        &name,
+                        variable.ty,
+                        &variable.place,
+                        &mut follow,
+                    );
+                }
+            });
+
+        follow
+    }
+}
+
+/// One row of the tree, values with members, elements or a pointee expand into their children
+fn show_value(
+    ui: &mut egui::Ui,
+    formatter: &Formatter,
+    id: egui::Id,
+    name: &str,
+    ty: Option<TypeRef>,
+    place: &Place,
+    follow: &mut Option<Follow>,
+) {
+    let Some(ty) = ty else {
+        ui.label(egui::RichText::new(format!("{name} = <no type>")).monospace());
+        return;
+    };
+
+    let info = formatter.dwarf.type_info(ty);
+    let text = egui::RichText::new(format!(
+        "{name}: {} = {}",
+        info.name,
+        formatter.summary(&info, place)
+    ))
+    .monospace();
+
+    let response = if formatter.has_children(&info, place) {
+        let header = egui::CollapsingHeader::new(text)
+            .id_source(id)
+            .show(ui, |ui| {
+                for (index, child) in formatter.children(&info, place).iter().enumerate() {
+                    show_value(
+                        ui,
+                        formatter,
+                        id.with(index),
+                        &child.name,
+                        child.ty,
+                        &child.place,
+                        follow,
+                    );
+                }
+            });
+        header.header_response
+    } else {
+        //leaves line up with the text of headers, past their arrow
+        ui.horizontal(|ui| {
+            ui.add_space(ui.spacing().indent);
+            ui.add(egui::Label::new(text).sense(egui::Sense::click()))
+        })
+        .inner
+    };
+
+    let pointee = match info.kind {
+        TypeKind::Pointer(_) => place
+            .read(8, formatter.memory)
+            .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap())),
+        _ => None,
+    };
+
+    response.context_menu(|ui| {
+        if let Place::Memory(address) = place
+            && ui.button("Follow in dump").clicked()
+        {
+            *follow = Some(Follow::Dump(*address));
+            ui.close_menu();
+        }
+
+        if let Some(address) = pointee {
+            if ui.button("Follow pointer in dump").clicked() {
+                *follow = Some(Follow::Dump(address));
+                ui.close_menu();
+            }
+
+            if ui.button("Follow pointer in disassembly").clicked() {
+                *follow = Some(Follow::Disassembly(address));
+                ui.close_menu();
+            }
+        }
+    });
+}
@@ -0,0 +1,244 @@
+use eframe::egui;
+
+use super::Follow;
+use crate::debugger::memory_map::{MemoryMap, MemoryRegion};
+use crate::debugger::rendezvous::ModuleEvent;
+use crate::debugger::Debugee;
+
+//refreshes per second, smaps is expensive so don't overdo it
+const REFRESH_RATE: f32 = 1.0;
+
+pub struct MemoryMapView {
+    memory_map: MemoryMap,
+    since_last_update: std::time::SystemTime,
+    /// Start address of the selected region or module
+    selected: Option<u64>,
+}
+
+impl MemoryMapView {
+    pub fn new() -> Self {
+        Self {
+            memory_map: MemoryMap::default(),
+            since_last_update: std::time::SystemTime::UNIX_EPOCH,
+            selected: None,
+        }
+    }
+
+    fn update(&mut self, ui: &egui::Ui, debugee: &Debugee) {
+        if std::time::SystemTime::now()
+            .duration_since(self.since_last_update)
+            .map(|x| x > std::time::Duration::from_secs_f32(1.0 / REFRESH_RATE))
+            .unwrap_or(false)
+        {
+            self.force_update(debugee);
+            ui.ctx().request_repaint();
+        }
+    }
+
+    fn force_update(&mut self, debugee: &Debugee) {
+        self.since_last_update = std::time::SystemTime::now();
+
+        if let Ok(memory_map) = MemoryMap::read_smaps(debugee.pid()) {
+            self.memory_map = memory_map;
+        }
+    }
+
+    /// Every mapping of the process, clicking one follows it in the disassembly if it's executable,
+    /// otherwise in the dump
+    pub fn show_regions(&mut self, ui: &mut egui::Ui, debugee: &Option<Debugee>) -> Option<Follow> {
+        let Some(debugee) = debugee else {
+            ui.label("No process");
+            return None;
+        };
+
+        self.update(ui, debugee);
+
+        let mut follow = None;
+
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                egui::Grid::new("memory_map_grid")
+                    .num_columns(7)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.label("Range");
+                        ui.label("Size");
+                        ui.label("Perms");
+                        ui.label("Offset");
+                        ui.label("RSS");
+                        ui.label("Dirty");
+                        ui.label("Path");
+                        ui.end_row();
+
+                        for region in self.memory_map.regions() {
+                            let response = ui.selectable_label(
+                                self.selected == Some(region.start),
+                                egui::RichText::new(format!(
+                                    "{:#014x}-{:#014x}",
+                                    region.start, region.end
+                                ))
+                                .monospace(),
+                            );
+
+                            if let Some(f) = row_follow(&response, region) {
+                                self.selected = Some(region.start);
+                                follow = Some(f);
+                            }
+
+                            ui.label(format_size(region.size()));
+                            ui.monospace(&region.permissions);
+                            ui.monospace(format!("{:#x}", region.offset));
+                            ui.label(region.rss.map(format_size).unwrap_or_default());
+                            ui.label(region.dirty.map(format_size).unwrap_or_default());
+
+                            if region.is_special() {
+                                ui.strong(&region.path);
+                            } else {
+                                ui.label(&region.path);
+                            }
+
+                            ui.end_row();
+                        }
+                    });
+            });
+
+        follow
+    }
+
+    /// Mapped files grouped together, along with the libraries ld.so reported (un)loading
+    pub fn show_modules(
+        &mut self,
+        ui: &mut egui::Ui,
+        debugee: &Option<Debugee>,
+        module_events: &[ModuleEvent],
+    ) -> Option<Follow> {
+        let Some(debugee) = debugee else {
+            ui.label("No process");
+            return None;
+        };
+
+        self.update(ui, debugee);
+
+        let mut follow = None;
+
+        egui::SidePanel::right("module_events")
+            .resizable(true)
+            .default_width(300.0)
+            .show_inside(ui, |ui| {
+                ui.label("Events");
+                ui.separator();
+
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false, false])
+                    .stick_to_bottom(true)
+                    .show(ui, |ui| {
+                        for event in module_events {
+                            match event {
+                                ModuleEvent::Loaded { path, load_bias } => {
+                                    ui.label(format!("+ {path} ({load_bias:#x})"))
+                                }
+                                ModuleEvent::Unloaded { path, load_bias } => {
+                                    ui.weak(format!("- {path} ({load_bias:#x})"))
+                                }
+                            };
+                        }
+                    });
+            });
+
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                egui::Grid::new("modules_grid")
+                    .num_columns(6)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.label("Name");
+                        ui.label("Base");
+                        ui.label("Size");
+                        ui.label("RSS");
+                        ui.label("Path");
+                        ui.label("Debug info");
+                        ui.end_row();
+
+                        for (path, base, size) in self.memory_map.modules() {
+                            let regions = self
+                                .memory_map
+                                .regions()
+                                .iter()
+                                .filter(|r| r.path == path)
+                                .collect::<Vec<_>>();
+
+                            let response = ui.selectable_label(
+                                self.selected == Some(base),
+                                regions[0].file_name(),
+                            );
+
+                            //the base mapping holds the ELF header, follow it like any other region
+                            if let Some(f) = row_follow(&response, regions[0]) {
+                                self.selected = Some(base);
+                                follow = Some(f);
+                            }
+
+                            ui.monospace(format!("{base:#014x}"));
+                            ui.label(format_size(size));
+                            ui.label(format_size(regions.iter().filter_map(|r| r.rss).sum()));
+                            ui.label(path);
+
+                            let debug_file = debugee
+                                .symbols()
+                                .modules()
+                                .iter()
+                                .find(|m| m.path == path && m.base == base)
+                                .and_then(|m| m.debug_file.as_deref());
+
+                            match debug_file {
+                                Some(debug_file) if debug_file == path => ui.label("Built-in"),
+                                Some(debug_file) => ui.label(debug_file),
+                                None => ui.weak("None"),
+                            };
+
+                            ui.end_row();
+                        }
+                    });
+            });
+
+        follow
+    }
+}
+
+/// Where a click or the context menu on a row wants to go
+fn row_follow(response: &egui::Response, region: &MemoryRegion) -> Option<Follow> {
+    let mut follow = None;
+
+    if response.clicked() {
+        follow = Some(if region.is_executable() {
+            Follow::Disassembly(region.start)
+        } else {
+            Follow::Dump(region.start)
+        });
+    }
+
+    response.context_menu(|ui| {
+        if ui.button("Follow in dump").clicked() {
+            follow = Some(Follow::Dump(region.start));
+            ui.close_menu();
+        }
+
+        if ui.button("Follow in disassembly").clicked() {
+            follow = Some(Follow::Disassembly(region.start));
+            ui.close_menu();
+        }
+    });
+
+    follow
+}
+
+fn format_size(bytes: u64) -> String {
+    match bytes {
+        0..=1023 => format!("{bytes} B"),
+        1024..=0xfffff => format!("{:.1} KiB", bytes as f64 / 1024.0),
+        0x100000..=0x3fffffff => format!("{:.1} MiB", bytes as f64 / (1024.0 * 1024.0)),
+        _ => format!("{:.1} GiB", bytes as f64 / (1024.0 * 1024.0 * 1024.0)),
+    }
+}
@@ -1,4 +1,23 @@
 pub mod app;
+pub mod bookmarks_view;
+pub mod breakpoints_view;
+pub mod call_stack_view;
+pub mod disassembly_format;
 pub mod disassembly_view;
+pub mod dynamic_symbols_view;
 pub mod hex_view;
+pub mod instruction_cache;
+pub mod locals_view;
+pub mod memory_map_view;
+pub mod patches_view;
+pub mod project;
+pub mod source_view;
+pub mod stack_view;
+pub mod symbols_view;
 pub mod widgets;
+
+/// Navigation request coming out of a view, handled by the app
+pub enum Follow {
+    Disassembly(u64),
+    Dump(u64),
+}
@@ -0,0 +1,180 @@
+use eframe::egui;
+
+use super::Follow;
+use crate::debugger::{patch, Debugee};
+
+//patched bytes shown before the rest gets cut off
+const MAX_SHOWN_BYTES: usize = 16;
+
+enum Action {
+    Revert(usize),
+    RevertAll,
+    /// Module base
+    Export(u64),
+}
+
+pub struct PatchesView;
+
+impl PatchesView {
+    pub const fn new() -> Self {
+        Self
+    }
+
+    /// Every byte change made through the debugger, double clicking one follows it in the disassembly
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        debugee: &mut Option<Debugee>,
+        status: &mut String,
+    ) -> Option<Follow> {
+        let Some(debugee) = debugee else {
+            ui.label("No process");
+            return None;
+        };
+
+        let mut actions = Vec::new();
+        let mut follow = None;
+
+        //modules with something to export
+        let mut modules = debugee
+            .patches()
+            .iter()
+            .filter_map(|patch| debugee.symbols().module_at(patch.address))
+            .map(|module| (module.base, module.name().to_owned()))
+            .collect::<Vec<_>>();
+        modules.dedup();
+
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(
+                    !debugee.patches().is_empty(),
+                    egui::Button::new("Revert all"),
+                )
+                .clicked()
+            {
+                actions.push(Action::RevertAll);
+            }
+
+            for (base, name) in &modules {
+                ui.separator();
+                if ui
+                    .button(format!("Export patched {name}…"))
+                    .on_hover_text("Writes a copy of the file with the patches applied")
+                    .clicked()
+                {
+                    actions.push(Action::Export(*base));
+                }
+            }
+        });
+
+        ui.separator();
+
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                egui::Grid::new("patches_grid")
+                    .num_columns(7)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.label("Address");
+                        ui.label("Module");
+                        ui.label("File offset");
+                        ui.label("Size");
+                        ui.label("Original");
+                        ui.label("Patched");
+                        ui.label("");
+                        ui.end_row();
+
+                        for (i, patch) in debugee.patches().iter().enumerate() {
+                            let module = debugee.symbols().module_at(patch.address);
+
+                            let location = debugee
+                                .describe_address(patch.address)
+                                .map(|symbol| format!("{:#x} {symbol}", patch.address))
+                                .unwrap_or_else(|| format!("{:#x}", patch.address));
+                            let module_text = module
+                                .map(|module| {
+                                    format!("{}+{:#x}", module.name(), patch.address - module.base)
+                                })
+                                .unwrap_or_else(|| String::from("-"));
+                            let file_offset = module
+                                .and_then(|module| module.file_offset(patch.address))
+                                .map(|offset| format!("{offset:#x}"))
+                                .unwrap_or_else(|| String::from("-"));
+
+                            for text in [
+                                location,
+                                module_text,
+                                file_offset,
+                                patch.patched.len().to_string(),
+                                hex(&patch.original),
+                                hex(&patch.patched),
+                            ] {
+                                if ui
+                                    .add(
+                                        egui::Label::new(egui::RichText::new(text).monospace())
+                                            .sense(egui::Sense::click()),
+                                    )
+                                    .double_clicked()
+                                {
+                                    follow = Some(Follow::Disassembly(patch.address));
+                                }
+                            }
+
+                            if ui.button("Revert").clicked() {
+                                actions.push(Action::Revert(i));
+                            }
+
+                            ui.end_row();
+                        }
+                    });
+            });
+
+        for action in actions {
+            match action {
+                Action::Revert(index) => debugee.revert_patch(index),
+                Action::RevertAll => debugee.revert_all_patches(),
+                Action::Export(base) => {
+                    let Some(module) = debugee.symbols().module_at(base) else {
+                        continue;
+                    };
+
+                    let Some(destination) = rfd::FileDialog::new()
+                        .set_title("Export patched file")
+                        .set_file_name(format!("{}.patched", module.name()))
+                        .save_file()
+                    else {
+                        continue;
+                    };
+
+                    *status = match patch::export(module, debugee.patches(), &destination) {
+                        Ok(0) => format!("Wrote {}", destination.display()),
+                        Ok(skipped) => format!(
+                            "Wrote {}, {skipped} patched byte(s) aren't backed by the file",
+                            destination.display()
+                        ),
+                        Err(error) => error,
+                    };
+                }
+            }
+        }
+
+        follow
+    }
+}
+
+/// `48 89 e5 ...`, cut off after `MAX_SHOWN_BYTES`
+fn hex(bytes: &[u8]) -> String {
+    let mut text = bytes
+        .iter()
+        .take(MAX_SHOWN_BYTES)
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    if bytes.len() > MAX_SHOWN_BYTES {
+        text.push_str(" …");
+    }
+
+    text
+}
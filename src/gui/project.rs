@@ -0,0 +1,666 @@
+//! Per-binary project files, everything the user set up for an executable so it's back the next
+//! time the same binary is opened or attached to.
+//!
+//! The file is TOML, saved as `<executable>.rusty-bugger.toml` next to the executable so it can
+//! be checked in along with it. When that directory isn't writable it goes to
+//! `$XDG_DATA_HOME/rusty-bugger/projects/<name>-<build-id>.toml` instead. Addresses are module
+//! relative (`libfoo.so+0x1a2b`, the module's file name and the offset from where it's loaded),
+//! so they survive ASLR and libraries that aren't loaded yet. They're strings since TOML integers
+//! are signed.
+//!
+//! ```toml
+//! version = 1
+//! executable = "/home/me/project/target/debug/app"
+//! # GNU build-id, a project saved for another build of the executable isn't loaded
+//! build_id = "4f6c0e0c2b55e6b0c2a8f1a51c8f4e6f2a7d9b10"
+//!
+//! [layout]
+//! bottom_tab = "breakpoints"
+//! syntax = "nasm"             # nasm, masm, gas, fast
+//! hex_style = "default"       # default, prefix, suffix
+//! memory_size = "default"     # default, always, minimal, never
+//! uppercase = false
+//! branch_size = true
+//! interleave_source = false
+//! break_on_library_load = false
+//!
+//! [[breakpoints]]
+//! location = "app+0x1139"
+//! hardware = false            # optional, false
+//! enabled = true              # optional, true
+//! condition = "rdi == 3"      # optional
+//! group = "parser"            # optional
+//! one_shot = false            # optional, false
+//!
+//! [[symbolic_breakpoints]]
+//! pattern = "malloc"
+//! regex = false               # optional, false
+//!
+//! [[notes]]
+//! location = "app+0x1149"
+//! label = "after_prologue"    # optional
+//! comment = "rbp is set up"   # optional
+//! bookmark = true             # optional, false
+//!
+//! [[patches]]
+//! location = "app+0x1151"
+//! original = "75 0a"          # only applied if these bytes are still there
+//! patched = "90 90"
+//! ```
+//!
+//! Breakpoints resolved from symbolic breakpoints aren't saved, they get resolved again.
+//! Watch expressions are out of scope, the debugger doesn't have any to save.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::disassembly_format::{FormatSettings, HexStyle, MemorySize, Syntax};
+use crate::debugger::debug_info;
+use crate::debugger::notes::ModuleAddress;
+use crate::debugger::Debugee;
+
+const VERSION: u32 = 1;
+const EXTENSION: &str = "rusty-bugger.toml";
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Project {
+    pub version: u32,
+    pub executable: String,
+    pub build_id: String,
+    pub layout: Layout,
+    pub breakpoints: Vec<Breakpoint>,
+    pub symbolic_breakpoints: Vec<SymbolicBreakpoint>,
+    pub notes: Vec<Note>,
+    pub patches: Vec<Patch>,
+}
+
+/// How the window was set up, the keys of the settings' enums
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct Layout {
+    pub bottom_tab: String,
+    pub syntax: String,
+    pub hex_style: String,
+    pub memory_size: String,
+    pub uppercase: bool,
+    pub branch_size: bool,
+    pub interleave_source: bool,
+    pub break_on_library_load: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Breakpoint {
+    pub location: String,
+    #[serde(default)]
+    pub hardware: bool,
+    #[serde(default = "enabled")]
+    pub enabled: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub condition: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    #[serde(default)]
+    pub one_shot: bool,
+}
+
+const fn enabled() -> bool {
+    true
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SymbolicBreakpoint {
+    pub pattern: String,
+    #[serde(default)]
+    pub regex: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Note {
+    pub location: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    #[serde(default)]
+    pub bookmark: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Patch {
+    pub location: String,
+    pub original: String,
+    pub patched: String,
+}
+
+/// The binary a session debugs, its build-id is read once since that means reading all of it
+#[derive(Clone)]
+pub struct Executable {
+    pub path: String,
+    /// Empty when it doesn't have one
+    pub build_id: String,
+}
+
+impl Executable {
+    pub fn new(path: &str) -> Self {
+        Self {
+            path: path.to_owned(),
+            build_id: debug_info::read_build_id(path).unwrap_or_default(),
+        }
+    }
+}
+
+impl Layout {
+    pub fn new(
+        bottom_tab: &str,
+        format: FormatSettings,
+        interleave_source: bool,
+        break_on_library_load: bool,
+    ) -> Self {
+        Self {
+            bottom_tab: bottom_tab.to_owned(),
+            syntax: format.syntax.key().to_owned(),
+            hex_style: format.hex_style.key().to_owned(),
+            memory_size: format.memory_size.key().to_owned(),
+            uppercase: format.uppercase,
+            branch_size: format.branch_size,
+            interleave_source,
+            break_on_library_load,
+        }
+    }
+
+    /// Unknown keys keep what's in `format`
+    pub fn format(&self, format: FormatSettings) -> FormatSettings {
+        FormatSettings {
+            syntax: Syntax::from_key(&self.syntax).unwrap_or(format.syntax),
+            hex_style: HexStyle::from_key(&self.hex_style).unwrap_or(format.hex_style),
+            memory_size: MemorySize::from_key(&self.memory_size).unwrap_or(format.memory_size),
+            uppercase: self.uppercase,
+            branch_size: self.branch_size,
+        }
+    }
+}
+
+impl Project {
+    /// Everything worth keeping about `debugee` along with what's still `pending`,
+    /// addresses outside of modules can't be saved
+    pub fn capture(
+        debugee: &Debugee,
+        executable: &Executable,
+        layout: Layout,
+        pending: &Pending,
+    ) -> Self {
+        let symbolic = debugee
+            .symbolic_breakpoints()
+            .iter()
+            .map(|symbolic| symbolic.description())
+            .collect::<Vec<_>>();
+
+        let breakpoints = debugee
+            .breakpoints()
+            .iter()
+            .filter(|bp| !bp.internal)
+            .filter(|bp| bp.location.as_ref().is_none_or(|l| !symbolic.contains(l)))
+            .filter_map(|bp| {
+                Some(Breakpoint {
+                    location: debugee.module_address(bp.breakpoint.address())?.to_string(),
+                    hardware: bp.breakpoint.hardware(),
+                    enabled: bp.breakpoint.enabled(),
+                    condition: bp.condition.clone(),
+                    group: bp.group.clone(),
+                    one_shot: bp.one_shot,
+                })
+            })
+            .collect();
+
+        let symbolic_breakpoints = debugee
+            .symbolic_breakpoints()
+            .iter()
+            .map(|symbolic| SymbolicBreakpoint {
+                pattern: symbolic.pattern.clone(),
+                regex: symbolic.is_regex,
+            })
+            .collect();
+
+        let notes = debugee
+            .notes()
+            .iter()
+            .map(|(location, note)| Note {
+                location: location.to_string(),
+                label: note.label.clone(),
+                comment: note.comment.clone(),
+                bookmark: note.bookmark,
+            })
+            .collect();
+
+        let patches = debugee
+            .patches()
+            .iter()
+            .filter_map(|patch| {
+                Some(Patch {
+                    location: debugee.module_address(patch.address)?.to_string(),
+                    original: to_hex(&patch.original),
+                    patched: to_hex(&patch.patched),
+                })
+            })
+            .collect();
+
+        let mut project = Self {
+            version: VERSION,
+            executable: executable.path.clone(),
+            build_id: executable.build_id.clone(),
+            layout,
+            breakpoints,
+            symbolic_breakpoints,
+            notes,
+            patches,
+        };
+        pending.keep_in(&mut project);
+        project
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        let project: Self =
+            toml_edit::de::from_str(&text).map_err(|e| format!("{}: {e}", path.display()))?;
+
+        if project.version > VERSION {
+            return Err(format!(
+                "{}: version {} is newer than this debugger",
+                path.display(),
+                project.version
+            ));
+        }
+
+        Ok(project)
+    }
+
+    /// Writes the project next to the executable, or to the data directory if that fails.
+    /// Returns where it ended up
+    pub fn save(&self) -> Result<PathBuf, String> {
+        let text = toml_edit::ser::to_string_pretty(self).map_err(|e| e.to_string())?;
+
+        let path = local_path(&self.executable);
+        if std::fs::write(&path, &text).is_ok() {
+            return Ok(path);
+        }
+
+        let path = data_path(&self.executable, &self.build_id)
+            .ok_or_else(|| format!("Can't write {}", path.display()))?;
+        if let Some(directory) = path.parent() {
+            std::fs::create_dir_all(directory)
+                .map_err(|e| format!("{}: {e}", directory.display()))?;
+        }
+
+        std::fs::write(&path, &text).map_err(|e| format!("{}: {e}", path.display()))?;
+        Ok(path)
+    }
+
+    /// Hands everything over to `debugee`, whatever is in modules that aren't loaded yet comes back
+    /// as pending. Returns what couldn't be restored
+    pub fn restore(self, debugee: &mut Debugee, pending: &mut Pending) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        for symbolic in self.symbolic_breakpoints {
+            //already there when restarting
+            let _ = debugee.add_symbolic_breakpoint(&symbolic.pattern, symbolic.regex);
+        }
+
+        for note in self.notes {
+            let location = match ModuleAddress::parse(&note.location) {
+                Ok(location) => location,
+                Err(error) => {
+                    errors.push(error);
+                    continue;
+                }
+            };
+
+            let notes = debugee.notes_mut();
+            if let Some(label) = note.label
+                && let Err(error) = notes.set_label(location.clone(), &label)
+            {
+                errors.push(error);
+            }
+            if let Some(comment) = note.comment {
+                notes.set_comment(location.clone(), &comment);
+            }
+            if note.bookmark {
+                notes.set_bookmark(location, true);
+            }
+        }
+
+        pending.breakpoints.extend(self.breakpoints);
+        pending.patches.extend(self.patches);
+        errors.extend(pending.apply(debugee));
+
+        errors
+    }
+}
+
+/// Breakpoints and patches waiting for their module to get loaded
+#[derive(Default)]
+pub struct Pending {
+    breakpoints: Vec<Breakpoint>,
+    patches: Vec<Patch>,
+}
+
+impl Pending {
+    pub fn is_empty(&self) -> bool {
+        self.breakpoints.is_empty() && self.patches.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.breakpoints.clear();
+        self.patches.clear();
+    }
+
+    /// Entries that never got placed still belong in the project
+    fn keep_in(&self, project: &mut Project) {
+        project.breakpoints.extend(self.breakpoints.iter().cloned());
+        project.patches.extend(self.patches.iter().cloned());
+    }
+
+    /// Places whatever's in a loaded module by now, memory can only be touched while stopped.
+    /// Returns what had to be dropped
+    pub fn apply(&mut self, debugee: &mut Debugee) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        if !debugee.stopped || self.is_empty() {
+            return errors;
+        }
+
+        self.breakpoints
+            .retain(|breakpoint| match place_breakpoint(breakpoint, debugee) {
+                Ok(placed) => !placed,
+                Err(error) => {
+                    errors.push(error);
+                    false
+                }
+            });
+
+        self.patches
+            .retain(|patch| match apply_patch(patch, debugee) {
+                Ok(applied) => !applied,
+                Err(error) => {
+                    errors.push(error);
+                    false
+                }
+            });
+
+        errors
+    }
+}
+
+/// False while the module isn't loaded
+fn place_breakpoint(breakpoint: &Breakpoint, debugee: &mut Debugee) -> Result<bool, String> {
+    let location = ModuleAddress::parse(&breakpoint.location)?;
+    let Some(address) = debugee.resolve_module_address(&location) else {
+        return Ok(false);
+    };
+
+    if debugee.user_breakpoint_at_address(address).is_some() {
+        return Ok(true);
+    }
+
+    let id = if breakpoint.hardware {
+        debugee
+            .add_hardware_breakpoint(address)
+            .map_err(|e| format!("Breakpoint at {location}: {e}"))?
+    } else {
+        debugee.add_software_breakpoint(address)
+    };
+
+    debugee.set_breakpoint_condition(id, breakpoint.condition.clone());
+    debugee.set_breakpoint_group(id, breakpoint.group.clone());
+    debugee.set_breakpoint_one_shot(id, breakpoint.one_shot);
+    if !breakpoint.enabled {
+        debugee.set_breakpoint_enabled(id, false);
+    }
+
+    Ok(true)
+}
+
+/// False while the module isn't loaded. Patches are only applied over the bytes they were made
+/// on, anything else means the file changed
+fn apply_patch(patch: &Patch, debugee: &mut Debugee) -> Result<bool, String> {
+    let location = ModuleAddress::parse(&patch.location)?;
+    let original = from_hex(&patch.original)?;
+    let patched = from_hex(&patch.patched)?;
+
+    if original.len() != patched.len() {
+        return Err(format!(
+            "Patch at {location}: original and patched sizes differ"
+        ));
+    }
+
+    let Some(address) = debugee.resolve_module_address(&location) else {
+        return Ok(false);
+    };
+
+    if debugee.read_code(address as usize, original.len()) != original {
+        return Err(format!(
+            "Patch at {location}: the original bytes don't match, not applied"
+        ));
+    }
+
+    debugee.write_memory(address as usize, &patched);
+    Ok(true)
+}
+
+/// The project file for `executable`, next to it or in the data directory
+pub fn find(executable: &Executable) -> Option<PathBuf> {
+    let local = local_path(&executable.path);
+    if local.is_file() {
+        return Some(local);
+    }
+
+    data_path(&executable.path, &executable.build_id).filter(|path| path.is_file())
+}
+
+/// Whether the project file's build matches the executable, projects without a build-id always do
+pub fn matches_build(project: &Project, executable: &Executable) -> bool {
+    project.build_id.is_empty()
+        || executable.build_id.is_empty()
+        || executable.build_id == project.build_id
+}
+
+fn local_path(executable: &str) -> PathBuf {
+    PathBuf::from(format!("{executable}.{EXTENSION}"))
+}
+
+/// Without a build-id the executable's path tells binaries with the same name apart
+fn data_path(executable: &str, build_id: &str) -> Option<PathBuf> {
+    let data = std::env::var("XDG_DATA_HOME").ok().or_else(|| {
+        std::env::var("HOME")
+            .ok()
+            .map(|home| format!("{home}/.local/share"))
+    })?;
+
+    let name = executable.rsplit('/').next().unwrap_or(executable);
+    let key = if build_id.is_empty() {
+        format!("{:08x}", crc32fast::hash(executable.as_bytes()))
+    } else {
+        build_id.to_owned()
+    };
+
+    Some(
+        Path::new(&data)
+            .join("rusty-bugger/projects")
+            .join(format!("{name}-{key}.toml")),
+    )
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn from_hex(text: &str) -> Result<Vec<u8>, String> {
+    text.split_whitespace()
+        .map(|byte| u8::from_str_radix(byte, 16).map_err(|_| format!("Invalid byte '{byte}'")))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breakpoint(location: &str) -> Breakpoint {
+        Breakpoint {
+            location: location.to_owned(),
+            hardware: false,
+            enabled: true,
+            condition: None,
+            group: None,
+            one_shot: false,
+        }
+    }
+
+    fn round_trip(project: &Project) -> Project {
+        let text = toml_edit::ser::to_string_pretty(project).unwrap();
+        toml_edit::de::from_str(&text).unwrap()
+    }
+
+    #[test]
+    fn round_trip_keeps_everything() {
+        let project = Project {
+            version: VERSION,
+            executable: String::from("/tmp/app"),
+            build_id: String::from("33d9a2681f436e86e8a8576d608ac6b414d95b94"),
+            layout: Layout {
+                bottom_tab: String::from("breakpoints"),
+                syntax: String::from("gas"),
+                uppercase: true,
+                ..Layout::default()
+            },
+            breakpoints: vec![Breakpoint {
+                hardware: true,
+                enabled: false,
+                condition: Some(String::from("rdi == 3")),
+                group: Some(String::from("parser")),
+                one_shot: true,
+                ..breakpoint("app+0x1139")
+            }],
+            symbolic_breakpoints: vec![SymbolicBreakpoint {
+                pattern: String::from("^mem"),
+                regex: true,
+            }],
+            notes: vec![Note {
+                location: String::from("libstdc++.so.6+0x10"),
+                label: Some(String::from("start")),
+                comment: None,
+                bookmark: true,
+            }],
+            patches: vec![Patch {
+                location: String::from("app+0x1151"),
+                original: to_hex(&[0x75, 0x0a]),
+                patched: to_hex(&[0x90, 0x90]),
+            }],
+        };
+
+        let loaded = round_trip(&project);
+        assert_eq!(loaded.version, VERSION);
+        assert_eq!(loaded.executable, "/tmp/app");
+        assert_eq!(loaded.build_id, project.build_id);
+        assert_eq!(loaded.layout.bottom_tab, "breakpoints");
+        assert_eq!(loaded.layout.syntax, "gas");
+        assert!(loaded.layout.uppercase);
+
+        let bp = &loaded.breakpoints[0];
+        assert_eq!(bp.location, "app+0x1139");
+        assert!(bp.hardware && !bp.enabled && bp.one_shot);
+        assert_eq!(bp.condition.as_deref(), Some("rdi == 3"));
+        assert_eq!(bp.group.as_deref(), Some("parser"));
+
+        assert_eq!(loaded.symbolic_breakpoints[0].pattern, "^mem");
+        assert!(loaded.symbolic_breakpoints[0].regex);
+
+        let note = &loaded.notes[0];
+        assert_eq!(note.location, "libstdc++.so.6+0x10");
+        assert_eq!(note.label.as_deref(), Some("start"));
+        assert_eq!(note.comment, None);
+        assert!(note.bookmark);
+
+        let patch = &loaded.patches[0];
+        assert_eq!(from_hex(&patch.original).unwrap(), [0x75, 0x0a]);
+        assert_eq!(from_hex(&patch.patched).unwrap(), [0x90, 0x90]);
+    }
+
+    #[test]
+    fn optional_fields_default() {
+        let project: Project = toml_edit::de::from_str(
+            r#"
+            version = 1
+            executable = "/tmp/app"
+
+            [[breakpoints]]
+            location = "app+0x1139"
+
+            [[symbolic_breakpoints]]
+            pattern = "malloc"
+
+            [[notes]]
+            location = "app+0x1149"
+            "#,
+        )
+        .unwrap();
+
+        assert!(project.build_id.is_empty());
+        assert!(project.layout.bottom_tab.is_empty());
+        assert!(project.patches.is_empty());
+
+        let bp = &project.breakpoints[0];
+        assert!(!bp.hardware && bp.enabled && !bp.one_shot);
+        assert_eq!(bp.condition, None);
+        assert_eq!(bp.group, None);
+
+        assert!(!project.symbolic_breakpoints[0].regex);
+
+        let note = &project.notes[0];
+        assert_eq!(note.label, None);
+        assert_eq!(note.comment, None);
+        assert!(!note.bookmark);
+    }
+
+    #[test]
+    fn pending_entries_are_saved() {
+        let mut pending = Pending::default();
+        pending.breakpoints.push(breakpoint("libfoo.so+0x20"));
+        pending.patches.push(Patch {
+            location: String::from("libfoo.so+0x30"),
+            original: String::from("c3"),
+            patched: String::from("90"),
+        });
+
+        let mut project = Project {
+            breakpoints: vec![breakpoint("app+0x1139")],
+            ..Project::default()
+        };
+        pending.keep_in(&mut project);
+
+        let loaded = round_trip(&project);
+        let locations = loaded
+            .breakpoints
+            .iter()
+            .map(|bp| bp.location.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(locations, ["app+0x1139", "libfoo.so+0x20"]);
+        assert_eq!(loaded.patches.len(), 1);
+        assert_eq!(loaded.patches[0].location, "libfoo.so+0x30");
+
+        //still pending, placing them is up to the next session
+        assert_eq!(pending.breakpoints.len(), 1);
+        assert_eq!(pending.patches.len(), 1);
+    }
+
+    #[test]
+    fn hex_round_trip() {
+        assert_eq!(to_hex(&[0x0f, 0x1f, 0x00]), "0f 1f 00");
+        assert_eq!(from_hex("0f 1f 00").unwrap(), [0x0f, 0x1f, 0x00]);
+        assert!(from_hex("0g").is_err());
+    }
+}
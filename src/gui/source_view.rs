@@ -0,0 +1,234 @@
+use std::collections::{HashMap, HashSet};
+
+use eframe::egui;
+
+use crate::debugger::breakpoint::BreakpointId;
+use crate::debugger::Debugee;
+
+const ROW_HEIGHT: f32 = 16.0;
+
+/// Source files read from disk, kept around since they're needed on every frame
+#[derive(Default)]
+pub struct SourceFiles {
+    files: HashMap<String, Option<Vec<String>>>,
+}
+
+impl SourceFiles {
+    /// Lines of `path`, `None` if it can't be read
+    pub fn get(&mut self, path: &str) -> Option<&[String]> {
+        self.files
+            .entry(path.to_owned())
+            .or_insert_with(|| {
+                let data = std::fs::read(path).ok()?;
+                Some(
+                    String::from_utf8_lossy(&data)
+                        .lines()
+                        .map(|line| line.replace('\t', "    "))
+                        .collect(),
+                )
+            })
+            .as_deref()
+    }
+
+    /// `line` is 1 based, like in the line table
+    pub fn line(&mut self, path: &str, line: u32) -> Option<&str> {
+        self.get(path)?
+            .get(line.checked_sub(1)? as usize)
+            .map(String::as_str)
+    }
+
+    /// Forgets everything read so far, the sources might've changed along with the binary
+    pub fn clear(&mut self) {
+        self.files.clear();
+    }
+}
+
+pub struct SourceView {
+    files: SourceFiles,
+    /// File being shown, follows the selected frame on every stop
+    path: Option<String>,
+    /// Location the view last followed, so it only jumps once per stop and can be scrolled around
+    followed_rip: Option<u64>,
+    scroll_to: Option<u32>,
+    /// Lines of the shown file that have code, along with the symbol generation and file they're for
+    code_lines: Option<(u64, String, HashSet<u32>)>,
+}
+
+impl SourceView {
+    pub fn new() -> Self {
+        Self {
+            files: SourceFiles::default(),
+            path: None,
+            followed_rip: None,
+            scroll_to: None,
+            code_lines: None,
+        }
+    }
+
+    /// Drops the cached sources, they might've changed along with the binary
+    pub fn purge_cache(&mut self) {
+        self.files.clear();
+        self.code_lines = None;
+        self.followed_rip = None;
+    }
+
+    /// Clicking the dot next to a line toggles a breakpoint on every address it compiled to.
+    /// `location` is where the selected frame is at, rip unless a caller is selected
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        debugee: &mut Option<Debugee>,
+        status: &mut String,
+        location: Option<u64>,
+    ) {
+        let Some(debugee) = debugee else {
+            ui.label("No process");
+            return;
+        };
+
+        let rip = location.unwrap_or(debugee.context().rip);
+        let current = debugee
+            .symbols()
+            .source_location(rip)
+            .filter(|_| debugee.stopped)
+            .map(|location| (location.path.to_owned(), location.line));
+
+        if debugee.stopped && self.followed_rip != Some(rip) {
+            self.followed_rip = Some(rip);
+
+            if let Some((path, line)) = &current {
+                self.path = Some(path.clone());
+                self.scroll_to = Some(*line);
+            }
+        }
+
+        egui::ComboBox::from_id_source("source_file")
+            .width(ui.available_width())
+            .selected_text(self.path.as_deref().unwrap_or("No source file"))
+            .show_ui(ui, |ui| {
+                for file in debugee.symbols().source_files() {
+                    if ui
+                        .selectable_label(self.path.as_deref() == Some(file), file)
+                        .clicked()
+                    {
+                        self.path = Some(file.to_owned());
+                        self.scroll_to = Some(1);
+                    }
+                }
+            });
+
+        ui.separator();
+
+        let Some(path) = self.path.clone() else {
+            ui.label("No line info at rip, pick a file above");
+            return;
+        };
+
+        let generation = debugee.symbols().generation();
+        if !self
+            .code_lines
+            .as_ref()
+            .is_some_and(|(g, p, _)| *g == generation && *p == path)
+        {
+            let lines = debugee.symbols().source_lines(&path);
+            self.code_lines = Some((generation, path.clone(), lines));
+        }
+
+        let Some(lines) = self.files.get(&path) else {
+            ui.label(format!("Couldn't read {path}"));
+            return;
+        };
+
+        let code_lines = &self.code_lines.as_ref().unwrap().2;
+
+        let mut breakpoint_lines: HashMap<u32, Vec<BreakpointId>> = HashMap::new();
+        for bp in debugee.breakpoints().iter().filter(|bp| !bp.internal) {
+            if let Some(location) = debugee
+                .symbols()
+                .source_location(bp.breakpoint.address())
+                .filter(|location| location.path == path)
+            {
+                breakpoint_lines
+                    .entry(location.line)
+                    .or_default()
+                    .push(bp.id);
+            }
+        }
+
+        let current_line = current
+            .filter(|(current_path, _)| *current_path == path)
+            .map(|(_, line)| line);
+
+        let mut scroll_area = egui::ScrollArea::both().auto_shrink([false, false]);
+        if let Some(line) = self.scroll_to.take() {
+            //keep the line a third of the way down so there's some context above it
+            let row_height = ROW_HEIGHT + ui.spacing().item_spacing.y;
+            let offset = line.saturating_sub(1) as f32 * row_height - ui.available_height() / 3.0;
+            scroll_area = scroll_area.vertical_scroll_offset(offset.max(0.0));
+        }
+
+        let mut toggled = None;
+
+        scroll_area.show_rows(ui, ROW_HEIGHT, lines.len(), |ui, range| {
+            for index in range {
+                let number = index as u32 + 1;
+                let is_current = current_line == Some(number);
+
+                ui.horizontal(|ui| {
+                    ui.add_sized(
+                        egui::vec2(12.0, ROW_HEIGHT),
+                        egui::Label::new(if is_current { "▶" } else { "" }),
+                    );
+
+                    let dot = if breakpoint_lines.contains_key(&number) {
+                        "◎"
+                    } else {
+                        "○"
+                    };
+
+                    if ui
+                        .add(egui::Label::new(dot).sense(egui::Sense::click()))
+                        .clicked()
+                    {
+                        toggled = Some(number);
+                    }
+
+                    let mut number_text = egui::RichText::new(format!("{number:>5}")).monospace();
+                    if !code_lines.contains(&number) {
+                        number_text = number_text.weak();
+                    }
+                    ui.label(number_text);
+
+                    let mut text = egui::RichText::new(&lines[index]).monospace();
+                    if is_current {
+                        text = text.background_color(ui.visuals().selection.bg_fill);
+                    }
+                    ui.label(text);
+                });
+            }
+        });
+
+        let Some(line) = toggled else {
+            return;
+        };
+
+        let file_name = path.rsplit('/').next().unwrap_or(&path);
+
+        if !debugee.stopped {
+            *status = String::from("Can't change breakpoints while unstopped");
+        } else if let Some(ids) = breakpoint_lines.get(&line) {
+            for id in ids {
+                debugee.remove_breakpoint(*id);
+            }
+
+            *status = format!("Removed breakpoint at {file_name}:{line}");
+        } else {
+            *status = match debugee.add_source_breakpoint(&path, line) {
+                Ok((line, locations)) => {
+                    format!("Breakpoint at {file_name}:{line}, {locations} location(s)")
+                }
+                Err(error) => error,
+            };
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+
+use eframe::egui;
+
+use super::Follow;
+use crate::debugger::annotation::{Annotation, Telescope};
+use crate::debugger::variables::Memory;
+use crate::debugger::Debugee;
+
+//qwords shown above rsp, what was just popped or is about to be pushed over
+const ROWS_ABOVE: u64 = 8;
+//rows shown below rsp when it isn't in a mapping we know the end of
+const MAX_ROWS: u64 = 0x2000;
+
+struct Entry {
+    value: Option<u64>,
+    telescope: Option<Telescope>,
+}
+
+pub struct StackView {
+    /// rsp, rip and symbol generation the entries were read for
+    built_for: Option<(u64, u64, u64)>,
+    /// Filled in as rows become visible, annotating the whole stack up front would take a while
+    entries: HashMap<u64, Entry>,
+    scroll_to_rsp: bool,
+}
+
+impl StackView {
+    pub fn new() -> Self {
+        Self {
+            built_for: None,
+            entries: HashMap::new(),
+            scroll_to_rsp: true,
+        }
+    }
+
+    pub fn purge_cache(&mut self) {
+        self.entries.clear();
+        self.built_for = None;
+    }
+
+    /// One qword per row starting a bit above rsp, jumps back to rsp whenever the process stops
+    pub fn show(&mut self, ui: &mut egui::Ui, debugee: &Option<Debugee>) -> Option<Follow> {
+        let Some(debugee) = debugee else {
+            ui.label("No process");
+            return None;
+        };
+
+        if !debugee.stopped {
+            ui.label("Process is running");
+            return None;
+        }
+
+        let rsp = debugee.context().rsp;
+        let rbp = debugee.context().rbp;
+
+        let key = (rsp, debugee.context().rip, debugee.symbols().generation());
+        if self.built_for != Some(key) {
+            self.built_for = Some(key);
+            self.entries.clear();
+            self.scroll_to_rsp = true;
+        }
+
+        let start = (rsp & !7).saturating_sub(ROWS_ABOVE * 8);
+        let end = debugee
+            .memory_map()
+            .region_at(rsp)
+            .map_or(u64::MAX, |region| region.end);
+        let rows = ((end - start) / 8).min(MAX_ROWS) as usize;
+
+        ui.horizontal(|ui| {
+            ui.label("Stack");
+            if ui.small_button("Go to RSP").clicked() {
+                self.scroll_to_rsp = true;
+            }
+        });
+        ui.separator();
+
+        let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+        let mut scroll_area = egui::ScrollArea::vertical().auto_shrink([false, false]);
+
+        if std::mem::take(&mut self.scroll_to_rsp) {
+            let spacing = ui.spacing().item_spacing.y;
+            scroll_area = scroll_area
+                .vertical_scroll_offset((rsp - start) as f32 / 8.0 * (row_height + spacing));
+        }
+
+        let mut follow = None;
+
+        scroll_area.show_rows(ui, row_height, rows, |ui, range| {
+            for row in range {
+                let address = start + row as u64 * 8;
+                let entry = self.entries.entry(address).or_insert_with(|| {
+                    let value = debugee
+                        .read_bytes(address, 8)
+                        .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()));
+
+                    Entry {
+                        value,
+                        telescope: value.map(|value| debugee.telescope(value)),
+                    }
+                });
+
+                let marker = if address == rsp {
+                    "RSP →"
+                } else if address == rbp {
+                    "RBP →"
+                } else {
+                    ""
+                };
+
+                let response = ui
+                    .horizontal(|ui| {
+                        ui.add_sized(
+                            egui::vec2(48.0, row_height),
+                            egui::Label::new(egui::RichText::new(marker).monospace().strong()),
+                        );
+
+                        let text = match entry.value {
+                            Some(value) => format!("{address:016x}  {value:016x}"),
+                            None => format!("{address:016x}  ????????????????"),
+                        };
+                        let mut text = egui::RichText::new(text).monospace();
+                        if address < rsp {
+                            text = text.weak();
+                        }
+                        let response = ui.add(egui::Label::new(text).sense(egui::Sense::click()));
+
+                        if let Some(telescope) = &entry.telescope {
+                            let text = egui::RichText::new(telescope.describe()).monospace();
+                            //return addresses are what the stack is usually read for
+                            ui.label(match telescope.hops.first() {
+                                Some((_, Some(Annotation::ReturnAddress(_)))) => text.strong(),
+                                _ => text.weak(),
+                            });
+                        }
+
+                        response
+                    })
+                    .inner;
+
+                response.context_menu(|ui| {
+                    if let Some(value) = entry.value {
+                        if ui.button("Follow value in dump").clicked() {
+                            follow = Some(Follow::Dump(value));
+                            ui.close_menu();
+                        }
+
+                        if ui.button("Follow value in disassembly").clicked() {
+                            follow = Some(Follow::Disassembly(value));
+                            ui.close_menu();
+                        }
+                    }
+
+                    if ui.button("Follow address in dump").clicked() {
+                        follow = Some(Follow::Dump(address));
+                        ui.close_menu();
+                    }
+                });
+            }
+        });
+
+        follow
+    }
+}
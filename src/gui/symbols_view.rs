@@ -0,0 +1,125 @@
+use eframe::egui;
+
+use super::Follow;
+use crate::debugger::symbols::DemangleSettings;
+use crate::debugger::Debugee;
+
+const ROW_HEIGHT: f32 = 18.0;
+
+struct Row {
+    module: String,
+    name: String,
+    address: u64,
+    size: u64,
+    is_function: bool,
+}
+
+pub struct SymbolsView {
+    search: String,
+    rows: Vec<Row>,
+    /// Symbol table generation, search and name style the rows were built from
+    built_for: Option<(u64, String, DemangleSettings)>,
+}
+
+impl SymbolsView {
+    pub const fn new() -> Self {
+        Self {
+            search: String::new(),
+            rows: Vec::new(),
+            built_for: None,
+        }
+    }
+
+    fn key(&self, debugee: &Debugee) -> (u64, String, DemangleSettings) {
+        let symbols = debugee.symbols();
+        (symbols.generation(), self.search.clone(), symbols.demangle)
+    }
+
+    fn rebuild(&mut self, debugee: &Debugee) {
+        let search = self.search.to_lowercase();
+        let settings = debugee.symbols().demangle;
+
+        self.rows = debugee
+            .symbols()
+            .modules()
+            .iter()
+            .flat_map(|m| {
+                m.symbols
+                    .iter()
+                    .map(move |s| (m, s, s.display_name(settings)))
+            })
+            .filter(|(_, s, name)| {
+                search.is_empty()
+                    || name.to_lowercase().contains(&search)
+                    || s.name.to_lowercase().contains(&search)
+            })
+            .map(|(m, s, name)| Row {
+                module: m.name().to_owned(),
+                name: name.into_owned(),
+                address: s.address,
+                size: s.size,
+                is_function: s.is_function,
+            })
+            .collect();
+
+        self.built_for = Some(self.key(debugee));
+    }
+
+    /// Clicking a function follows it in the disassembly, anything else in the dump
+    pub fn show(&mut self, ui: &mut egui::Ui, debugee: &Option<Debugee>) -> Option<Follow> {
+        let Some(debugee) = debugee else {
+            ui.label("No process");
+            return None;
+        };
+
+        ui.horizontal(|ui| {
+            ui.label("Search");
+            ui.text_edit_singleline(&mut self.search);
+            ui.weak(format!("{} symbol(s)", self.rows.len()));
+        });
+
+        if self.built_for != Some(self.key(debugee)) {
+            self.rebuild(debugee);
+        }
+
+        ui.separator();
+
+        let mut follow = None;
+
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, false])
+            .show_rows(ui, ROW_HEIGHT, self.rows.len(), |ui, range| {
+                egui::Grid::new("symbols_grid")
+                    .num_columns(5)
+                    .striped(true)
+                    .min_row_height(ROW_HEIGHT)
+                    .show(ui, |ui| {
+                        for row in &self.rows[range] {
+                            let response = ui.add(
+                                egui::Label::new(
+                                    egui::RichText::new(format!("{:#014x}", row.address))
+                                        .monospace(),
+                                )
+                                .sense(egui::Sense::click()),
+                            );
+
+                            if response.clicked() {
+                                follow = Some(if row.is_function {
+                                    Follow::Disassembly(row.address)
+                                } else {
+                                    Follow::Dump(row.address)
+                                });
+                            }
+
+                            ui.label(&row.module);
+                            ui.label(if row.is_function { "Function" } else { "Data" });
+                            ui.label(format!("{:#x}", row.size));
+                            ui.label(&row.name);
+                            ui.end_row();
+                        }
+                    });
+            });
+
+        follow
+    }
+}